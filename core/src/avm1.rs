@@ -1,6 +1,6 @@
 #[cfg(test)]
 #[macro_use]
-mod test_utils;
+pub(crate) mod test_utils;
 
 #[macro_use]
 mod function;
@@ -36,6 +36,7 @@ pub use object::script_object::ScriptObject;
 pub use object::sound_object::SoundObject;
 pub use object::stage_object::StageObject;
 pub use object::xml_node_object::XmlNodeObject;
+pub use object::xml_object::{XmlObject, XmlParseError, XmlStatus};
 pub use object::{Object, ObjectPtr, TObject};
 pub use property::Attribute;
 pub use property_map::PropertyMap;
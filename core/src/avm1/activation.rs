@@ -2430,7 +2430,10 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     is_slash_path = true;
                 }
                 path = path.slice(3..).unwrap_or_default();
-                if let Some(parent) = object.as_display_object().and_then(|o| o.avm1_parent()) {
+                if let Some(parent) = object
+                    .as_display_object()
+                    .and_then(|o| o.resolve_slash_path(WStr::from_units(b".."), case_sensitive))
+                {
                     parent.object()
                 } else {
                     // Tried to get parent of root, bail out.
@@ -2468,11 +2471,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     // Get the value from the object.
                     // Resolves display object instances first, then local variables.
                     // This is the opposite of general GetMember property access!
-                    if let Some(child) = object
-                        .as_display_object()
-                        .and_then(|o| o.as_container())
-                        .and_then(|o| o.child_by_name(name, case_sensitive))
-                    {
+                    // `name` has no delimiters left, so it names a single child,
+                    // unless it is empty, which would resolve to `object` itself.
+                    let child = match object.as_display_object() {
+                        Some(o) if !name.is_empty() => o.resolve_slash_path(name, case_sensitive),
+                        _ => None,
+                    };
+                    if let Some(child) = child {
                         child.object()
                     } else {
                         let name = AvmString::new(self.context.gc_context, name);
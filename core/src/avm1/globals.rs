@@ -4,6 +4,7 @@ use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::property::Attribute;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::avm_warn;
 use crate::display_object::{DisplayObject, TDisplayObject, TDisplayObjectContainer};
 use crate::string::{AvmString, WStr, WString};
 use gc_arena::Collect;
@@ -498,6 +499,8 @@ pub struct SystemPrototypes<'gc> {
     pub context_menu_item_constructor: Object<'gc>,
     pub bitmap_filter: Object<'gc>,
     pub bitmap_filter_constructor: Object<'gc>,
+    pub blur_filter_constructor: Object<'gc>,
+    pub bevel_filter_constructor: Object<'gc>,
     pub glow_filter: Object<'gc>,
     pub glow_filter_constructor: Object<'gc>,
     pub drop_shadow_filter: Object<'gc>,
@@ -1139,6 +1142,8 @@ pub fn create_globals<'gc>(
             context_menu_item_constructor: context_menu_item,
             bitmap_filter: bitmap_filter_proto,
             bitmap_filter_constructor: bitmap_filter,
+            blur_filter_constructor: blur_filter,
+            bevel_filter_constructor: bevel_filter,
             glow_filter: glow_filter_proto,
             glow_filter_constructor: glow_filter,
             drop_shadow_filter: drop_shadow_filter_proto,
@@ -1196,12 +1201,22 @@ pub fn remove_display_object<'gc>(
     this: DisplayObject<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
 ) {
-    let depth = this.depth().wrapping_sub(0);
+    let depth = this.depth();
     // Can only remove positive depths (when offset by the AVM depth bias).
     // Generally this prevents you from removing non-dynamically created clips,
     // although you can get around it with swapDepths.
     // TODO: Figure out the derivation of this range.
-    if depth >= AVM_DEPTH_BIAS && depth < AVM_MAX_REMOVE_DEPTH && !this.removed() {
+    if !(AVM_DEPTH_BIAS..AVM_MAX_REMOVE_DEPTH).contains(&depth) {
+        avm_warn!(
+            activation,
+            "Cannot remove {} at depth {}, which is not a dynamic depth",
+            this.path(),
+            depth.wrapping_sub(AVM_DEPTH_BIAS)
+        );
+        return;
+    }
+
+    if !this.removed() {
         // Need a parent to remove from.
         if let Some(mut parent) = this.avm1_parent().and_then(|o| o.as_movie_clip()) {
             parent.remove_child(&mut activation.context, this);
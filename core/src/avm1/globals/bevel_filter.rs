@@ -1,12 +1,13 @@
 //! flash.filters.BevelFilter object
 
 use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::globals::bitmap_filter::{num_passes, strength_to_fixed8};
 use crate::avm1::object::NativeObject;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Activation, Error, Object, ScriptObject, TObject, Value};
 use crate::string::{AvmString, WStr};
 use gc_arena::{Collect, GcCell, MutationContext};
-use swf::Color;
+use swf::{BevelFilter, Color, Fixed16};
 
 #[derive(Copy, Clone, Debug, Collect)]
 #[collect(no_drop)]
@@ -28,6 +29,28 @@ impl From<&WStr> for BevelFilterType {
     }
 }
 
+impl BevelFilterType {
+    /// The `is_inner` and `is_on_top` flags of a SWF filter of this type.
+    pub fn flags(self) -> (bool, bool) {
+        match self {
+            Self::Inner => (true, false),
+            Self::Outer => (false, false),
+            Self::Full => (false, true),
+        }
+    }
+
+    /// The type of a SWF filter with the given `is_inner` and `is_on_top` flags.
+    pub fn from_flags(is_inner: bool, is_on_top: bool) -> Self {
+        if is_on_top {
+            Self::Full
+        } else if is_inner {
+            Self::Inner
+        } else {
+            Self::Outer
+        }
+    }
+}
+
 impl From<BevelFilterType> for &'static WStr {
     fn from(type_: BevelFilterType) -> &'static WStr {
         match type_ {
@@ -75,6 +98,24 @@ impl BevelFilterObject {
         bevel_filter.set_knockout(activation, args.get(11))?;
         Ok(bevel_filter)
     }
+
+    /// The filter this object describes, as used for rendering.
+    pub fn filter(&self) -> BevelFilter {
+        let (is_inner, is_on_top) = self.type_.flags();
+        BevelFilter {
+            shadow_color: self.shadow,
+            highlight_color: self.highlight,
+            blur_x: Fixed16::from_f64(self.blur_x),
+            blur_y: Fixed16::from_f64(self.blur_y),
+            angle: Fixed16::from_f64(self.angle),
+            distance: Fixed16::from_f64(self.distance),
+            strength: strength_to_fixed8(f64::from(self.strength) / 256.0),
+            is_inner,
+            is_knockout: self.knockout,
+            is_on_top,
+            num_passes: num_passes(self.quality),
+        }
+    }
 }
 
 impl Default for BevelFilterObject {
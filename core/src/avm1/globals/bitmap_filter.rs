@@ -2,10 +2,16 @@
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::globals::bevel_filter::BevelFilterType;
 use crate::avm1::object::NativeObject;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
-use crate::avm1::{Attribute, Object, ScriptObject, TObject, Value};
+use crate::avm1::{ArrayObject, Attribute, Object, ScriptObject, TObject, Value};
+use crate::string::{AvmString, WStr};
 use gc_arena::{GcCell, MutationContext};
+use swf::{
+    Color, ColorMatrixFilter, ConvolutionFilter, DropShadowFilter, Filter, Fixed16, Fixed8,
+    GlowFilter, GradientFilter, GradientRecord,
+};
 
 const PROTO_DECLS: &[Declaration] = declare_properties! {
     "clone" => method(clone);
@@ -254,6 +260,318 @@ pub fn clone<'gc>(
     Ok(Value::Undefined)
 }
 
+/// The number of blur passes of a filter with the given `quality`.
+pub(crate) fn num_passes(quality: i32) -> u8 {
+    quality.clamp(0, 15) as u8
+}
+
+/// Converts a filter strength into the `Fixed8` stored in SWF filters.
+///
+/// Strengths range from 0 to 255, so the bits of the `Fixed8` are treated as
+/// an unsigned 8.8 fixed-point number rather than a signed one.
+pub(crate) fn strength_to_fixed8(strength: f64) -> Fixed8 {
+    Fixed8::from_bits((strength.clamp(0.0, 255.0) * 256.0) as u16 as i16)
+}
+
+/// Converts the `Fixed8` stored in SWF filters back into a filter strength.
+fn strength_from_fixed8(strength: Fixed8) -> f64 {
+    f64::from(strength.get() as u16) / 256.0
+}
+
+/// A color from an AVM1 filter's separate RGB color and alpha properties.
+fn color_with_alpha(rgb: u32, alpha: f64) -> Color {
+    Color::from_rgb(rgb, (alpha * 255.0).clamp(0.0, 255.0) as u8)
+}
+
+/// Converts a gradient glow or gradient bevel filter object, which share all
+/// of their properties, into the filter it describes.
+macro_rules! gradient_filter {
+    ($object:expr) => {{
+        let object = $object;
+        let (is_inner, is_on_top) = object.get_type().flags();
+        GradientFilter {
+            colors: object
+                .colors()
+                .into_iter()
+                .zip(object.alphas())
+                .zip(object.ratios())
+                .map(|((color, alpha), ratio)| GradientRecord {
+                    ratio,
+                    color: color_with_alpha(color, alpha),
+                })
+                .collect(),
+            blur_x: Fixed16::from_f64(object.blur_x()),
+            blur_y: Fixed16::from_f64(object.blur_y()),
+            angle: Fixed16::from_f64(object.angle().to_radians()),
+            distance: Fixed16::from_f64(object.distance()),
+            strength: strength_to_fixed8(object.strength()),
+            is_inner,
+            is_knockout: object.knockout(),
+            is_on_top,
+            num_passes: num_passes(object.quality()),
+        }
+    }};
+}
+
+/// Converts an AVM1 filter object into the filter it describes, for use by
+/// `MovieClip.filters`.
+///
+/// Returns `None` for objects that aren't filters, or are displacement map
+/// filters, which have no equivalent in the SWF filter format.
+pub fn avm1_to_filter(object: Object<'_>) -> Option<Filter> {
+    match object.native() {
+        NativeObject::BlurFilter(blur_filter) => {
+            return Some(Filter::BlurFilter(Box::new(blur_filter.read().filter())));
+        }
+        NativeObject::BevelFilter(bevel_filter) => {
+            return Some(Filter::BevelFilter(Box::new(bevel_filter.read().filter())));
+        }
+        _ => {}
+    }
+
+    if let Some(drop_shadow) = object.as_drop_shadow_filter_object() {
+        // `hideObject` has no equivalent in the SWF filter format.
+        return Some(Filter::DropShadowFilter(Box::new(DropShadowFilter {
+            color: color_with_alpha(drop_shadow.color(), drop_shadow.alpha()),
+            blur_x: Fixed16::from_f64(drop_shadow.blur_x()),
+            blur_y: Fixed16::from_f64(drop_shadow.blur_y()),
+            angle: Fixed16::from_f64(drop_shadow.angle().to_radians()),
+            distance: Fixed16::from_f64(drop_shadow.distance()),
+            strength: strength_to_fixed8(drop_shadow.strength()),
+            is_inner: drop_shadow.inner(),
+            is_knockout: drop_shadow.knockout(),
+            num_passes: num_passes(drop_shadow.quality()),
+        })));
+    }
+
+    if let Some(glow) = object.as_glow_filter_object() {
+        return Some(Filter::GlowFilter(Box::new(GlowFilter {
+            color: color_with_alpha(glow.color() as u32, glow.alpha()),
+            blur_x: Fixed16::from_f64(glow.blur_x()),
+            blur_y: Fixed16::from_f64(glow.blur_y()),
+            strength: strength_to_fixed8(glow.strength()),
+            is_inner: glow.inner(),
+            is_knockout: glow.knockout(),
+            num_passes: num_passes(glow.quality()),
+        })));
+    }
+
+    if let Some(color_matrix) = object.as_color_matrix_filter_object() {
+        return Some(Filter::ColorMatrixFilter(Box::new(ColorMatrixFilter {
+            matrix: color_matrix.matrix().map(Fixed16::from_f64),
+        })));
+    }
+
+    if let Some(convolution) = object.as_convolution_filter_object() {
+        return Some(Filter::ConvolutionFilter(Box::new(ConvolutionFilter {
+            num_matrix_rows: convolution.matrix_y(),
+            num_matrix_cols: convolution.matrix_x(),
+            matrix: convolution
+                .matrix()
+                .into_iter()
+                .map(Fixed16::from_f64)
+                .collect(),
+            divisor: Fixed16::from_f64(convolution.divisor()),
+            bias: Fixed16::from_f64(convolution.bias()),
+            default_color: color_with_alpha(convolution.color(), convolution.alpha()),
+            is_clamped: convolution.clamp(),
+            is_preserve_alpha: convolution.preserve_alpha(),
+        })));
+    }
+
+    if let Some(gradient_glow) = object.as_gradient_glow_filter_object() {
+        return Some(Filter::GradientGlowFilter(Box::new(gradient_filter!(
+            gradient_glow
+        ))));
+    }
+
+    if let Some(gradient_bevel) = object.as_gradient_bevel_filter_object() {
+        return Some(Filter::GradientBevelFilter(Box::new(gradient_filter!(
+            gradient_bevel
+        ))));
+    }
+
+    None
+}
+
+/// Creates a new AVM1 gradient glow or gradient bevel filter object, using the
+/// given constructor, describing the given filter.
+fn gradient_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    constructor: Object<'gc>,
+    filter: &GradientFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let gc_context = activation.context.gc_context;
+    let array_proto = activation.context.avm1.prototypes().array;
+    let array = |f: fn(&GradientRecord) -> Value<'gc>| {
+        ArrayObject::new(gc_context, array_proto, filter.colors.iter().map(f))
+    };
+    let colors = array(|record| record.color.to_rgb().into());
+    let alphas = array(|record| (f64::from(record.color.a) / 255.0).into());
+    let ratios = array(|record| record.ratio.into());
+    let type_: &WStr = BevelFilterType::from_flags(filter.is_inner, filter.is_on_top).into();
+    constructor.construct(
+        activation,
+        &[
+            filter.distance.to_f64().into(),
+            filter.angle.to_f64().to_degrees().into(),
+            colors.into(),
+            alphas.into(),
+            ratios.into(),
+            filter.blur_x.to_f64().into(),
+            filter.blur_y.to_f64().into(),
+            strength_from_fixed8(filter.strength).into(),
+            filter.num_passes.into(),
+            AvmString::from(type_).into(),
+            filter.is_knockout.into(),
+        ],
+    )
+}
+
+/// Creates a new AVM1 filter object describing the given filter, for use by
+/// `MovieClip.filters`.
+pub fn filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    filter: &Filter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    match filter {
+        Filter::BlurFilter(filter) => {
+            let constructor = activation.context.avm1.prototypes().blur_filter_constructor;
+            constructor.construct(
+                activation,
+                &[
+                    filter.blur_x.to_f64().into(),
+                    filter.blur_y.to_f64().into(),
+                    filter.num_passes.into(),
+                ],
+            )
+        }
+        Filter::DropShadowFilter(filter) => {
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .drop_shadow_filter_constructor;
+            constructor.construct(
+                activation,
+                &[
+                    filter.distance.to_f64().into(),
+                    filter.angle.to_f64().to_degrees().into(),
+                    filter.color.to_rgb().into(),
+                    (f64::from(filter.color.a) / 255.0).into(),
+                    filter.blur_x.to_f64().into(),
+                    filter.blur_y.to_f64().into(),
+                    strength_from_fixed8(filter.strength).into(),
+                    filter.num_passes.into(),
+                    filter.is_inner.into(),
+                    filter.is_knockout.into(),
+                ],
+            )
+        }
+        Filter::GlowFilter(filter) => {
+            let constructor = activation.context.avm1.prototypes().glow_filter_constructor;
+            let object = constructor.construct(
+                activation,
+                &[
+                    filter.color.to_rgb().into(),
+                    (f64::from(filter.color.a) / 255.0).into(),
+                    filter.blur_x.to_f64().into(),
+                    filter.blur_y.to_f64().into(),
+                    strength_from_fixed8(filter.strength).into(),
+                    filter.num_passes.into(),
+                ],
+            )?;
+            // The constructor doesn't take these.
+            if let Value::Object(glow) = object {
+                glow.set("inner", filter.is_inner.into(), activation)?;
+                glow.set("knockout", filter.is_knockout.into(), activation)?;
+            }
+            Ok(object)
+        }
+        Filter::BevelFilter(filter) => {
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .bevel_filter_constructor;
+            let type_: &WStr =
+                BevelFilterType::from_flags(filter.is_inner, filter.is_on_top).into();
+            constructor.construct(
+                activation,
+                &[
+                    filter.distance.to_f64().into(),
+                    filter.angle.to_f64().to_degrees().into(),
+                    filter.highlight_color.to_rgb().into(),
+                    (f64::from(filter.highlight_color.a) / 255.0).into(),
+                    filter.shadow_color.to_rgb().into(),
+                    (f64::from(filter.shadow_color.a) / 255.0).into(),
+                    filter.blur_x.to_f64().into(),
+                    filter.blur_y.to_f64().into(),
+                    strength_from_fixed8(filter.strength).into(),
+                    filter.num_passes.into(),
+                    AvmString::from(type_).into(),
+                    filter.is_knockout.into(),
+                ],
+            )
+        }
+        Filter::ColorMatrixFilter(filter) => {
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .color_matrix_filter_constructor;
+            let matrix = ArrayObject::new(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().array,
+                filter.matrix.iter().map(|value| value.to_f64().into()),
+            );
+            constructor.construct(activation, &[matrix.into()])
+        }
+        Filter::ConvolutionFilter(filter) => {
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .convolution_filter_constructor;
+            let matrix = ArrayObject::new(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().array,
+                filter.matrix.iter().map(|value| value.to_f64().into()),
+            );
+            constructor.construct(
+                activation,
+                &[
+                    filter.num_matrix_cols.into(),
+                    filter.num_matrix_rows.into(),
+                    matrix.into(),
+                    filter.divisor.to_f64().into(),
+                    filter.bias.to_f64().into(),
+                    filter.is_preserve_alpha.into(),
+                    filter.is_clamped.into(),
+                    filter.default_color.to_rgb().into(),
+                    (f64::from(filter.default_color.a) / 255.0).into(),
+                ],
+            )
+        }
+        Filter::GradientGlowFilter(filter) => {
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .gradient_glow_filter_constructor;
+            gradient_filter_to_avm1(activation, constructor, filter)
+        }
+        Filter::GradientBevelFilter(filter) => {
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .gradient_bevel_filter_constructor;
+            gradient_filter_to_avm1(activation, constructor, filter)
+        }
+    }
+}
+
 pub fn create_proto<'gc>(
     gc_context: MutationContext<'gc, '_>,
     proto: Object<'gc>,
@@ -1,10 +1,12 @@
 //! flash.filters.BlurFilter object
 
 use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::globals::bitmap_filter::num_passes;
 use crate::avm1::object::NativeObject;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Activation, Error, Object, ScriptObject, TObject, Value};
 use gc_arena::{Collect, GcCell, MutationContext};
+use swf::{BlurFilter, Fixed16};
 
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
@@ -14,6 +16,17 @@ pub struct BlurFilterObject {
     quality: i32,
 }
 
+impl BlurFilterObject {
+    /// The filter this object describes, as used for rendering.
+    pub fn filter(&self) -> BlurFilter {
+        BlurFilter {
+            blur_x: Fixed16::from_f64(self.blur_x),
+            blur_y: Fixed16::from_f64(self.blur_y),
+            num_passes: num_passes(self.quality),
+        }
+    }
+}
+
 macro_rules! blur_filter_method {
     ($index:literal) => {
         |activation, this, args| method(activation, this, args, $index)
@@ -142,6 +142,27 @@ pub fn object_to_matrix_or_default<'gc>(
     Ok(Matrix { a, b, c, d, tx, ty })
 }
 
+/// Returns a `Matrix` with the properties from `object`, if they are all numbers.
+///
+/// Like the point conversions of `MovieClip`, this does no coercion, does not search the
+/// prototype chain, and ignores virtual properties.
+pub fn object_to_matrix_if_valid<'gc>(
+    object: Object<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+) -> Option<Matrix> {
+    let mut get = |name: &'static str| match object.get_local_stored(name, activation) {
+        Some(Value::Number(value)) => Some(value),
+        _ => None,
+    };
+    let a = get("a")? as f32;
+    let b = get("b")? as f32;
+    let c = get("c")? as f32;
+    let d = get("d")? as f32;
+    let tx = Twips::from_pixels(get("tx")?);
+    let ty = Twips::from_pixels(get("ty")?);
+    Some(Matrix { a, b, c, d, tx, ty })
+}
+
 pub fn matrix_to_object<'gc>(
     matrix: Matrix,
     activation: &mut Activation<'_, 'gc, '_>,
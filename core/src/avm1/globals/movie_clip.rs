@@ -2,10 +2,12 @@
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
-use crate::avm1::globals::matrix::gradient_object_to_matrix;
-use crate::avm1::globals::{self, AVM_DEPTH_BIAS, AVM_MAX_DEPTH};
+use crate::avm1::globals::matrix::{
+    apply_matrix_to_object, gradient_object_to_matrix, object_to_matrix_if_valid,
+};
+use crate::avm1::globals::{self, bitmap_filter, AVM_DEPTH_BIAS, AVM_MAX_DEPTH};
 use crate::avm1::property_decl::{define_properties_on, Declaration};
-use crate::avm1::{self, Object, ScriptObject, TObject, Value};
+use crate::avm1::{self, ArrayObject, Object, ScriptObject, TObject, Value};
 use crate::avm_error;
 use crate::avm_warn;
 use crate::backend::navigator::NavigationMethod;
@@ -72,10 +74,13 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "getBounds" => method(mc_method!(get_bounds); DONT_ENUM | DONT_DELETE);
     "getBytesLoaded" => method(mc_method!(get_bytes_loaded); DONT_ENUM | DONT_DELETE);
     "getBytesTotal" => method(mc_method!(get_bytes_total); DONT_ENUM | DONT_DELETE);
+    "getChildIndex" => method(mc_method!(get_child_index); DONT_ENUM | DONT_DELETE | VERSION_8);
+    "getDepthList" => method(mc_method!(get_depth_list); DONT_ENUM | DONT_DELETE | VERSION_8);
     "getDepth" => method(globals::get_depth; DONT_ENUM | DONT_DELETE | READ_ONLY | VERSION_6);
     "getInstanceAtDepth" => method(mc_method!(get_instance_at_depth); DONT_ENUM | DONT_DELETE | VERSION_7);
     "getNextHighestDepth" => method(mc_method!(get_next_highest_depth); DONT_ENUM | DONT_DELETE | VERSION_7);
     "getRect" => method(mc_method!(get_rect); DONT_ENUM | DONT_DELETE | VERSION_8);
+    "getSoundTransform" => method(mc_method!(get_sound_transform); DONT_ENUM | DONT_DELETE | VERSION_8);
     "getURL" => method(mc_method!(get_url); DONT_ENUM | DONT_DELETE);
     "globalToLocal" => method(mc_method!(global_to_local); DONT_ENUM | DONT_DELETE);
     "gotoAndPlay" => method(mc_method!(goto_and_play); DONT_ENUM | DONT_DELETE);
@@ -88,6 +93,7 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "play" => method(mc_method!(play); DONT_ENUM | DONT_DELETE);
     "prevFrame" => method(mc_method!(prev_frame); DONT_ENUM | DONT_DELETE);
     "setMask" => method(mc_method!(set_mask); DONT_ENUM | DONT_DELETE | VERSION_6);
+    "setSoundTransform" => method(mc_method!(set_sound_transform); DONT_ENUM | DONT_DELETE | VERSION_8);
     "startDrag" => method(mc_method!(start_drag); DONT_ENUM | DONT_DELETE);
     "stop" => method(mc_method!(stop); DONT_ENUM | DONT_DELETE);
     "stopDrag" => method(mc_method!(stop_drag); DONT_ENUM | DONT_DELETE);
@@ -101,16 +107,21 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "curveTo" => method(mc_method!(curve_to); DONT_ENUM | DONT_DELETE | VERSION_6);
     "endFill" => method(mc_method!(end_fill); DONT_ENUM | DONT_DELETE | VERSION_6);
     "lineStyle" => method(mc_method!(line_style); DONT_ENUM | DONT_DELETE | VERSION_6);
+    "lineGradientStyle" => method(mc_method!(line_gradient_style); DONT_ENUM | DONT_DELETE | VERSION_8);
     "clear" => method(mc_method!(clear); DONT_ENUM | DONT_DELETE | VERSION_6);
     "attachBitmap" => method(mc_method!(attach_bitmap); DONT_ENUM | DONT_DELETE | VERSION_8);
     "removeMovieClip" => method(remove_movie_clip; DONT_ENUM | DONT_DELETE);
     "transform" => property(mc_getter!(transform), mc_setter!(set_transform); DONT_ENUM | VERSION_8);
     "enabled" => property(mc_getter!(enabled), mc_setter!(set_enabled); DONT_DELETE | DONT_ENUM);
     "focusEnabled" => property(mc_getter!(focus_enabled), mc_setter!(set_focus_enabled); DONT_DELETE | DONT_ENUM);
+    "tabEnabled" => property(mc_getter!(tab_enabled), mc_setter!(set_tab_enabled); DONT_DELETE | DONT_ENUM | VERSION_6);
+    "tabIndex" => property(mc_getter!(tab_index), mc_setter!(set_tab_index); DONT_DELETE | DONT_ENUM | VERSION_6);
     "_lockroot" => property(mc_getter!(lock_root), mc_setter!(set_lock_root); DONT_DELETE | DONT_ENUM);
     "useHandCursor" => property(mc_getter!(use_hand_cursor), mc_setter!(set_use_hand_cursor); DONT_DELETE | DONT_ENUM);
     "blendMode" => property(mc_getter!(blend_mode), mc_setter!(set_blend_mode); DONT_DELETE | DONT_ENUM);
     "scrollRect" => property(mc_getter!(scroll_rect), mc_setter!(set_scroll_rect); DONT_DELETE | DONT_ENUM | VERSION_8);
+    "opaqueBackground" => property(mc_getter!(opaque_background), mc_setter!(set_opaque_background); DONT_DELETE | DONT_ENUM | VERSION_8);
+    "filters" => property(mc_getter!(filters), mc_setter!(set_filters); DONT_DELETE | DONT_ENUM | VERSION_8);
 };
 
 /// Implements `MovieClip`
@@ -183,6 +194,68 @@ fn set_scroll_rect<'gc>(
     Ok(())
 }
 
+fn opaque_background<'gc>(
+    this: MovieClip<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(match this.opaque_background() {
+        Some(color) => color.to_rgb().into(),
+        None => Value::Null,
+    })
+}
+
+fn set_opaque_background<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let color = match value {
+        Value::Undefined | Value::Null => None,
+        value => Some(Color::from_rgb(value.coerce_to_u32(activation)?, 255)),
+    };
+    this.set_opaque_background(activation.context.gc_context, color);
+    Ok(())
+}
+
+fn filters<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Each read returns fresh copies, so modifying them doesn't affect the clip.
+    let mut filters = Vec::new();
+    for filter in this.bitmap_filters() {
+        filters.push(bitmap_filter::filter_to_avm1(activation, &filter)?);
+    }
+    Ok(ArrayObject::new(
+        activation.context.gc_context,
+        activation.context.avm1.prototypes().array,
+        filters,
+    )
+    .into())
+}
+
+fn set_filters<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let mut filters = Vec::new();
+    if let Value::Object(array) = value {
+        for i in 0..array.length(activation)? {
+            let element = array.get_element(activation, i);
+            match element {
+                Value::Object(object) => match bitmap_filter::avm1_to_filter(object) {
+                    Some(filter) => filters.push(filter),
+                    None => avm_warn!(activation, "MovieClip.filters: Unsupported filter"),
+                },
+                _ => avm_warn!(activation, "MovieClip.filters: Not a filter: {:?}", element),
+            }
+        }
+    }
+    this.set_bitmap_filters(activation.context.gc_context, filters);
+    Ok(())
+}
+
 #[allow(clippy::comparison_chain)]
 pub fn hit_test<'gc>(
     movie_clip: MovieClip<'gc>,
@@ -220,7 +293,10 @@ pub fn hit_test<'gc>(
             false,
         )?;
         if let Some(other) = other {
-            return Ok(movie_clip.hit_test_object(other).into());
+            // Compare the same global bounds that `getBounds` reports.
+            let bounds = bounds_in_space(movie_clip.into(), None, true);
+            let other_bounds = bounds_in_space(other, None, true);
+            return Ok(bounds.intersects(&other_bounds).into());
         }
     }
 
@@ -468,103 +544,139 @@ fn begin_gradient_fill<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let (Some(method), Some(colors), Some(alphas), Some(ratios), Some(matrix)) = (
-        args.get(0),
-        args.get(1),
-        args.get(2),
-        args.get(3),
-        args.get(4),
-    ) {
-        let method = method.coerce_to_string(activation)?;
-        let colors_object = colors.coerce_to_object(activation);
-        let colors_length = colors_object.length(activation)?;
-        let alphas_object = alphas.coerce_to_object(activation);
-        let alphas_length = alphas_object.length(activation)?;
-        let ratios_object = ratios.coerce_to_object(activation);
-        let ratios_length = ratios_object.length(activation)?;
-        let matrix_object = matrix.coerce_to_object(activation);
-        if colors_length != alphas_length || colors_length != ratios_length {
-            avm_warn!(
-                activation,
-                "beginGradientFill() received different sized arrays for colors, alphas and ratios"
-            );
-            return Ok(Value::Undefined);
-        }
-        let records: Result<Vec<_>, Error<'gc>> = (0..colors_length)
-            .map(|i| {
-                let ratio = ratios_object
-                    .get_element(activation, i)
-                    .coerce_to_f64(activation)?
-                    .clamp(0.0, 255.0) as u8;
-                let rgb = colors_object
-                    .get_element(activation, i)
-                    .coerce_to_u32(activation)?;
-                let alpha = alphas_object
-                    .get_element(activation, i)
-                    .coerce_to_f64(activation)?
-                    .clamp(0.0, 100.0);
-                Ok(GradientRecord {
-                    ratio,
-                    color: Color::from_rgb(rgb, (alpha / 100.0 * 255.0) as u8),
-                })
-            })
-            .collect();
-        let records = records?;
-        let matrix = gradient_object_to_matrix(matrix_object, activation)?;
-        let spread = match args
-            .get(5)
-            .and_then(|v| v.coerce_to_string(activation).ok())
-            .as_deref()
-        {
-            Some(v) if v == b"reflect" => GradientSpread::Reflect,
-            Some(v) if v == b"repeat" => GradientSpread::Repeat,
-            _ => GradientSpread::Pad,
-        };
-        let interpolation = match args
-            .get(6)
-            .and_then(|v| v.coerce_to_string(activation).ok())
-            .as_deref()
-        {
-            Some(v) if v == b"linearRGB" => GradientInterpolation::LinearRgb,
-            _ => GradientInterpolation::Rgb,
-        };
-
-        let gradient = Gradient {
-            matrix: matrix.into(),
-            spread,
-            interpolation,
-            records,
-        };
-        let style = if &method == b"linear" {
-            FillStyle::LinearGradient(gradient)
-        } else if &method == b"radial" {
-            if let Some(focal_point) = args.get(7) {
-                FillStyle::FocalGradient {
-                    gradient,
-                    focal_point: Fixed8::from_f64(focal_point.coerce_to_f64(activation)?),
-                }
-            } else {
-                FillStyle::RadialGradient(gradient)
-            }
-        } else {
-            avm_warn!(
-                activation,
-                "beginGradientFill() received invalid fill type {:?}",
-                method
-            );
-            return Ok(Value::Undefined);
-        };
+    if args.len() < 5 {
         movie_clip
             .drawing(activation.context.gc_context)
-            .set_fill_style(Some(style));
-    } else {
+            .set_fill_style(None);
+    } else if let Some(style) = gradient_fill_style(activation, args, "beginGradientFill")? {
         movie_clip
             .drawing(activation.context.gc_context)
-            .set_fill_style(None);
+            .set_fill_style(Some(style));
+    }
+    Ok(Value::Undefined)
+}
+
+fn line_gradient_style<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // The gradient replaces the fill of the current line style; without one,
+    // there is nothing to apply it to.
+    let line_style = movie_clip
+        .drawing(activation.context.gc_context)
+        .line_style()
+        .cloned();
+    if let Some(line_style) = line_style {
+        if let Some(style) = gradient_fill_style(activation, args, "lineGradientStyle")? {
+            movie_clip
+                .drawing(activation.context.gc_context)
+                .set_line_style(Some(line_style.with_fill_style(style)));
+        }
     }
     Ok(Value::Undefined)
 }
 
+/// Build a gradient fill style from the arguments shared by
+/// `beginGradientFill` and `lineGradientStyle`:
+/// `(type, colors, alphas, ratios, matrix, spreadMethod, interpolationMethod, focalPointRatio)`.
+///
+/// Yields `None` if the arguments are missing or invalid, in which case the
+/// call is ignored.
+fn gradient_fill_style<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+    method_name: &str,
+) -> Result<Option<FillStyle>, Error<'gc>> {
+    let (method, colors, alphas, ratios, matrix) = match args.get(0..5) {
+        Some([method, colors, alphas, ratios, matrix]) => (method, colors, alphas, ratios, matrix),
+        _ => return Ok(None),
+    };
+
+    let method = method.coerce_to_string(activation)?;
+    let colors_object = colors.coerce_to_object(activation);
+    let colors_length = colors_object.length(activation)?;
+    let alphas_object = alphas.coerce_to_object(activation);
+    let alphas_length = alphas_object.length(activation)?;
+    let ratios_object = ratios.coerce_to_object(activation);
+    let ratios_length = ratios_object.length(activation)?;
+    let matrix_object = matrix.coerce_to_object(activation);
+    if colors_length != alphas_length || colors_length != ratios_length {
+        avm_warn!(
+            activation,
+            "{}() received different sized arrays for colors, alphas and ratios",
+            method_name
+        );
+        return Ok(None);
+    }
+    let records: Result<Vec<_>, Error<'gc>> = (0..colors_length)
+        .map(|i| {
+            let ratio = ratios_object
+                .get_element(activation, i)
+                .coerce_to_f64(activation)?
+                .clamp(0.0, 255.0) as u8;
+            let rgb = colors_object
+                .get_element(activation, i)
+                .coerce_to_u32(activation)?;
+            let alpha = alphas_object
+                .get_element(activation, i)
+                .coerce_to_f64(activation)?
+                .clamp(0.0, 100.0);
+            Ok(GradientRecord {
+                ratio,
+                color: Color::from_rgb(rgb, (alpha / 100.0 * 255.0) as u8),
+            })
+        })
+        .collect();
+    let records = records?;
+    let matrix = gradient_object_to_matrix(matrix_object, activation)?;
+    let spread = match args
+        .get(5)
+        .and_then(|v| v.coerce_to_string(activation).ok())
+        .as_deref()
+    {
+        Some(v) if v == b"reflect" => GradientSpread::Reflect,
+        Some(v) if v == b"repeat" => GradientSpread::Repeat,
+        _ => GradientSpread::Pad,
+    };
+    let interpolation = match args
+        .get(6)
+        .and_then(|v| v.coerce_to_string(activation).ok())
+        .as_deref()
+    {
+        Some(v) if v == b"linearRGB" => GradientInterpolation::LinearRgb,
+        _ => GradientInterpolation::Rgb,
+    };
+
+    let gradient = Gradient {
+        matrix: matrix.into(),
+        spread,
+        interpolation,
+        records,
+    };
+    let style = if &method == b"linear" {
+        FillStyle::LinearGradient(gradient)
+    } else if &method == b"radial" {
+        if let Some(focal_point) = args.get(7) {
+            FillStyle::FocalGradient {
+                gradient,
+                focal_point: Fixed8::from_f64(focal_point.coerce_to_f64(activation)?),
+            }
+        } else {
+            FillStyle::RadialGradient(gradient)
+        }
+    } else {
+        avm_warn!(
+            activation,
+            "{}() received invalid fill type {:?}",
+            method_name,
+            method
+        );
+        return Ok(None);
+    };
+    Ok(Some(style))
+}
+
 fn move_to<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
@@ -931,12 +1043,106 @@ fn get_instance_at_depth<'gc>(
     }
 }
 
+fn get_child_index<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let child = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation)
+        .as_display_object();
+
+    // Objects that aren't direct children of this clip have no index.
+    let index = child
+        .and_then(|child| movie_clip.child_index(child))
+        .map_or(-1, |index| index as i32);
+    Ok(index.into())
+}
+
+fn get_sound_transform<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let transform = movie_clip.sound_transform();
+    let obj = ScriptObject::new(
+        activation.context.gc_context,
+        Some(activation.context.avm1.prototypes().object),
+    );
+    obj.set("volume", transform.volume.into(), activation)?;
+    obj.set("pan", transform.pan().into(), activation)?;
+    // As with `Sound.getTransform`, `lr` means "right-to-left" and `rl` means "left-to-right".
+    obj.set("ll", transform.left_to_left.into(), activation)?;
+    obj.set("lr", transform.right_to_left.into(), activation)?;
+    obj.set("rl", transform.left_to_right.into(), activation)?;
+    obj.set("rr", transform.right_to_right.into(), activation)?;
+    Ok(obj.into())
+}
+
+fn set_sound_transform<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let obj = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation);
+    let mut transform = movie_clip.sound_transform();
+
+    if obj.has_own_property(activation, "volume".into()) {
+        transform.volume = obj.get("volume", activation)?.coerce_to_i32(activation)?;
+    }
+    // The pan is applied first, so that an explicit matrix takes precedence.
+    if obj.has_own_property(activation, "pan".into()) {
+        let pan = obj.get("pan", activation)?.coerce_to_i32(activation)?;
+        transform.set_pan(pan);
+    }
+    if obj.has_own_property(activation, "ll".into()) {
+        transform.left_to_left = obj.get("ll", activation)?.coerce_to_i32(activation)?;
+    }
+    if obj.has_own_property(activation, "rl".into()) {
+        transform.left_to_right = obj.get("rl", activation)?.coerce_to_i32(activation)?;
+    }
+    if obj.has_own_property(activation, "lr".into()) {
+        transform.right_to_left = obj.get("lr", activation)?.coerce_to_i32(activation)?;
+    }
+    if obj.has_own_property(activation, "rr".into()) {
+        transform.right_to_right = obj.get("rr", activation)?.coerce_to_i32(activation)?;
+    }
+
+    movie_clip.set_sound_transform(&mut activation.context, transform);
+    Ok(Value::Undefined)
+}
+
+fn get_depth_list<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Children are listed from the lowest depth to the highest; their depths
+    // are available to scripts through `getDepth`.
+    let children = movie_clip
+        .depth_list()
+        .into_iter()
+        .map(|(_depth, child)| child.object());
+    Ok(ArrayObject::new(
+        activation.context.gc_context,
+        activation.context.avm1.prototypes().array,
+        children,
+    )
+    .into())
+}
+
 fn get_next_highest_depth<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.swf_version() >= 7 {
+        // Timeline depths are negative in AS, so only the dynamic range (0+) is allocated.
         let depth = movie_clip
             .highest_depth()
             .wrapping_sub(AVM_DEPTH_BIAS - 1)
@@ -1184,6 +1390,11 @@ fn local_to_global<'gc>(
             let (out_x, out_y) = movie_clip.local_to_global((x, y));
             point.set("x", out_x.to_pixels().into(), activation)?;
             point.set("y", out_y.to_pixels().into(), activation)?;
+        } else if let Some(matrix) = object_to_matrix_if_valid(*point, activation) {
+            // A matrix is converted as a whole, by concatenating it onto the same
+            // transform that is used for points.
+            let matrix = movie_clip.local_to_global_matrix() * matrix;
+            apply_matrix_to_object(matrix, *point, activation)?;
         } else {
             avm_warn!(
                 activation,
@@ -1200,10 +1411,59 @@ fn local_to_global<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Gets the bounds of a display object in the coordinate space of `target`,
+/// or in global space if there is no target.
+///
+/// This is shared by `getBounds` and `hitTest`, so that their results agree.
+fn bounds_in_space<'gc>(
+    display_object: DisplayObject<'gc>,
+    target: Option<DisplayObject<'gc>>,
+    include_strokes: bool,
+) -> BoundingBox {
+    let bounds = if include_strokes {
+        display_object.bounds()
+    } else {
+        display_object.edge_bounds_with_transform(&Default::default())
+    };
+    match target {
+        // Getting the clips bounds in its own coordinate space; no AABB transform needed.
+        Some(target) if DisplayObject::ptr_eq(display_object, target) => bounds,
+        _ => {
+            // Transform AABB to global space, and then on to target space.
+            // Note that this doesn't produce as tight of an AABB as if we had used `bounds_with_transform` with
+            // the final matrix, but this matches Flash's behavior.
+            let mut bounds_transform = display_object.local_to_global_matrix();
+            if let Some(target) = target {
+                bounds_transform = target.global_to_local_matrix() * bounds_transform;
+            }
+            bounds.transform(&bounds_transform)
+        }
+    }
+}
+
 fn get_bounds<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
     args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    bounds_object(movie_clip, activation, args, true)
+}
+
+fn get_rect<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Like `getBounds`, but ignoring the width of strokes.
+    bounds_object(movie_clip, activation, args, false)
+}
+
+/// Shared implementation of `getBounds` and `getRect`.
+fn bounds_object<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+    include_strokes: bool,
 ) -> Result<Value<'gc>, Error<'gc>> {
     let target = match args.get(0) {
         Some(val) => activation.resolve_target_display_object(movie_clip.into(), *val, false)?,
@@ -1211,20 +1471,7 @@ fn get_bounds<'gc>(
     };
 
     if let Some(target) = target {
-        let bounds = movie_clip.bounds();
-        let out_bounds = if DisplayObject::ptr_eq(movie_clip.into(), target) {
-            // Getting the clips bounds in its own coordinate space; no AABB transform needed.
-            bounds
-        } else {
-            // Transform AABB to target space.
-            // Calculate the matrix to transform into the target coordinate space, and transform the above AABB.
-            // Note that this doesn't produce as tight of an AABB as if we had used `bounds_with_transform` with
-            // the final matrix, but this matches Flash's behavior.
-            let to_global_matrix = movie_clip.local_to_global_matrix();
-            let to_target_matrix = target.global_to_local_matrix();
-            let bounds_transform = to_target_matrix * to_global_matrix;
-            bounds.transform(&bounds_transform)
-        };
+        let out_bounds = bounds_in_space(movie_clip.into(), Some(target), include_strokes);
 
         let out = ScriptObject::new(
             activation.context.gc_context,
@@ -1240,16 +1487,6 @@ fn get_bounds<'gc>(
     }
 }
 
-fn get_rect<'gc>(
-    movie_clip: MovieClip<'gc>,
-    activation: &mut Activation<'_, 'gc, '_>,
-    args: &[Value<'gc>],
-) -> Result<Value<'gc>, Error<'gc>> {
-    // TODO: This should get the bounds ignoring strokes. Always equal to or smaller than getBounds.
-    // Just defer to getBounds for now. Will have to store edge_bounds vs. shape_bounds in Graphic.
-    get_bounds(movie_clip, activation, args)
-}
-
 pub fn get_url<'gc>(
     _movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
@@ -1309,6 +1546,11 @@ fn global_to_local<'gc>(
             let (out_x, out_y) = movie_clip.global_to_local((x, y));
             point.set("x", out_x.to_pixels().into(), activation)?;
             point.set("y", out_y.to_pixels().into(), activation)?;
+        } else if let Some(matrix) = object_to_matrix_if_valid(*point, activation) {
+            // A matrix is converted as a whole, by concatenating it onto the same
+            // transform that is used for points.
+            let matrix = movie_clip.global_to_local_matrix() * matrix;
+            apply_matrix_to_object(matrix, *point, activation)?;
         } else {
             avm_warn!(
                 activation,
@@ -1446,6 +1688,46 @@ fn set_focus_enabled<'gc>(
     Ok(())
 }
 
+fn tab_enabled<'gc>(
+    this: MovieClip<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.tab_enabled().map_or(Value::Undefined, Value::from))
+}
+
+fn set_tab_enabled<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let enabled = match value {
+        Value::Undefined => None,
+        value => Some(value.as_bool(activation.swf_version())),
+    };
+    this.set_tab_enabled(activation.context.gc_context, enabled);
+    Ok(())
+}
+
+fn tab_index<'gc>(
+    this: MovieClip<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.tab_index().map_or(Value::Undefined, Value::from))
+}
+
+fn set_tab_index<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let index = match value {
+        Value::Undefined | Value::Null => None,
+        value => Some(value.coerce_to_i32(activation)?),
+    };
+    this.set_tab_index(activation.context.gc_context, index);
+    Ok(())
+}
+
 fn lock_root<'gc>(
     this: MovieClip<'gc>,
     _activation: &mut Activation<'_, 'gc, '_>,
@@ -171,7 +171,7 @@ fn get_pan<'gc>(
     let transform = this.as_sound_object().map(|sound| {
         sound
             .owner()
-            .map(|owner| owner.base().sound_transform().clone())
+            .map(|owner| owner.sound_transform())
             .unwrap_or_else(|| activation.context.global_sound_transform().clone())
     });
 
@@ -190,7 +190,7 @@ fn get_transform<'gc>(
     let transform = this.as_sound_object().map(|sound| {
         sound
             .owner()
-            .map(|owner| owner.base().sound_transform().clone())
+            .map(|owner| owner.sound_transform())
             .unwrap_or_else(|| activation.context.global_sound_transform().clone())
     });
 
@@ -218,7 +218,7 @@ fn get_volume<'gc>(
     let transform = this.as_sound_object().map(|sound| {
         sound
             .owner()
-            .map(|owner| owner.base().sound_transform().clone())
+            .map(|owner| owner.sound_transform())
             .unwrap_or_else(|| activation.context.global_sound_transform().clone())
     });
 
@@ -299,7 +299,7 @@ fn set_pan<'gc>(
 
     if let Some(sound) = this.as_sound_object() {
         if let Some(owner) = sound.owner() {
-            let mut transform = owner.base().sound_transform().clone();
+            let mut transform = owner.sound_transform();
             transform.set_pan(pan);
             owner.set_sound_transform(&mut activation.context, transform);
         } else {
@@ -324,7 +324,7 @@ fn set_transform<'gc>(
 
     if let Some(sound) = this.as_sound_object() {
         let mut transform = if let Some(owner) = sound.owner() {
-            owner.base().sound_transform().clone()
+            owner.sound_transform()
         } else {
             activation.context.global_sound_transform().clone()
         };
@@ -298,7 +298,7 @@ pub fn text<'gc>(
     this: EditText<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(AvmString::new(activation.context.gc_context, this.text()).into())
+    Ok(AvmString::new(activation.context.gc_context, this.text_with_line_ending()).into())
 }
 
 pub fn set_text<'gc>(
@@ -349,7 +349,7 @@ pub fn set_text_color<'gc>(
 ) -> Result<(), Error<'gc>> {
     let rgb = value.coerce_to_u32(activation)?;
     let text_format = TextFormat {
-        color: Some(swf::Color::from_rgb(rgb, 0)),
+        color: Some(swf::Color::from_rgb(rgb, 255)),
         ..Default::default()
     };
     this.set_text_format(
@@ -6,7 +6,7 @@ use crate::avm1::{Activation, ArrayObject, Error, Object, ScriptObject, TObject,
 use crate::avm_warn;
 use crate::display_object::{AutoSizeMode, EditText, TDisplayObject};
 use crate::ecma_conversions::round_to_even;
-use crate::html::TextFormat;
+use crate::html::{parse_hex_color, TextFormat};
 use crate::string::{AvmString, WStr};
 use gc_arena::{GcCell, MutationContext};
 
@@ -108,11 +108,13 @@ fn set_size<'gc>(
     Ok(())
 }
 
+/// `TextFormat.color` has no alpha: colors are read and written as RGB, and
+/// are always opaque.
 fn color<'gc>(_activation: &mut Activation<'_, 'gc, '_>, text_format: &TextFormat) -> Value<'gc> {
     text_format
         .color
         .as_ref()
-        .map_or(Value::Null, |color| color.to_rgba().into())
+        .map_or(Value::Null, |color| color.to_rgb().into())
 }
 
 fn set_color<'gc>(
@@ -122,7 +124,8 @@ fn set_color<'gc>(
 ) -> Result<(), Error<'gc>> {
     text_format.color = match value {
         Value::Undefined | Value::Null => None,
-        value => Some(swf::Color::from_rgba(value.coerce_to_u32(activation)?)),
+        Value::String(s) if s.starts_with(b'#') => parse_hex_color(s),
+        value => Some(swf::Color::from_rgb(value.coerce_to_u32(activation)?, 255)),
     };
     Ok(())
 }
@@ -3,7 +3,7 @@
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::ExecutionReason;
-use crate::avm1::object::xml_object::XmlObject;
+use crate::avm1::object::xml_object::{XmlObject, XmlStatus};
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Object, TObject, Value};
 use crate::avm_warn;
@@ -185,9 +185,14 @@ fn on_data<'gc>(
 
         this.set("loaded", true.into(), activation)?;
 
+        // A document that failed to parse is reported as an unsuccessful load;
+        // the reason is left in `status`.
+        let success = this
+            .as_xml()
+            .map_or(true, |document| document.status() == XmlStatus::NoError);
         this.call_method(
             "onLoad".into(),
-            &[true.into()],
+            &[success.into()],
             activation,
             ExecutionReason::FunctionCall,
         )?;
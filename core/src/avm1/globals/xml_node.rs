@@ -291,8 +291,7 @@ fn first_child<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(node) = this.as_xml_node() {
         return Ok(node
-            .children()
-            .next()
+            .first_child()
             .map(|mut child| child.script_object(activation).into())
             .unwrap_or_else(|| Value::Null));
     }
@@ -307,8 +306,7 @@ fn last_child<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(node) = this.as_xml_node() {
         return Ok(node
-            .children()
-            .next_back()
+            .last_child()
             .map(|mut child| child.script_object(activation).into())
             .unwrap_or_else(|| Value::Null));
     }
@@ -572,6 +572,9 @@ fn set_width<'gc>(
 ) -> Result<(), Error<'gc>> {
     if let Some(val) = property_coerce_to_number(activation, val)? {
         this.set_width(activation.context.gc_context, val);
+        if let Some(edit_text) = this.as_edit_text() {
+            edit_text.relayout_if_dirty(&mut activation.context);
+        }
     }
     Ok(())
 }
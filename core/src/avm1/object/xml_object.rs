@@ -5,12 +5,12 @@ use crate::avm1::error::Error;
 use crate::avm1::{Object, ScriptObject, TObject};
 use crate::impl_custom_object;
 use crate::string::{AvmString, WStr, WString};
-use crate::xml::{XmlNode, ELEMENT_NODE, TEXT_NODE};
+use crate::xml::{NameInterner, ParseOptions, XmlNode, ELEMENT_NODE, TEXT_NODE};
 use gc_arena::{Collect, GcCell, MutationContext};
-use quick_xml::{events::Event, Reader};
+use quick_xml::events::{BytesStart, Event};
 use std::fmt;
 
-#[derive(Clone, Copy, Collect)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Collect)]
 #[collect(no_drop)]
 pub enum XmlStatus {
     /// No error; parse was completed successfully.
@@ -48,6 +48,19 @@ pub enum XmlStatus {
     MismatchedEnd = -10,
 }
 
+/// An error encountered while parsing an XML document.
+#[derive(Debug, thiserror::Error)]
+pub enum XmlParseError {
+    #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+
+    #[error("XML document exceeds the maximum nesting depth of {0}")]
+    TooDeep(usize),
+
+    #[error("XML document exceeds the maximum node count of {0}")]
+    TooManyNodes(usize),
+}
+
 /// A ScriptObject that is inherently tied to an XML document.
 #[derive(Clone, Copy, Collect)]
 #[collect(no_drop)]
@@ -120,14 +133,53 @@ impl<'gc> XmlObject<'gc> {
         activation: &mut Activation<'_, 'gc, '_>,
         data: &WStr,
         ignore_white: bool,
-    ) -> Result<(), quick_xml::Error> {
+    ) -> Result<(), XmlParseError> {
+        let options = ParseOptions {
+            ignore_white,
+            ..ParseOptions::xml()
+        };
+        self.replace_with_options(activation, data, options)
+    }
+
+    /// Replace the contents of this document with the result of parsing a
+    /// string with the given options.
+    ///
+    /// When the document exceeds `max_depth` or `max_nodes`, the status is set
+    /// to `OutOfMemory` and the nodes parsed up to that point are kept.
+    pub fn replace_with_options(
+        &mut self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        data: &WStr,
+        options: ParseOptions,
+    ) -> Result<(), XmlParseError> {
         let data_utf8 = data.to_utf8_lossy();
-        let mut parser = Reader::from_str(&data_utf8);
+        let mut parser = options.reader(data_utf8.as_bytes());
         let mut buf = Vec::new();
         let mut open_tags = vec![self.as_node()];
+        let mut node_count = 0;
+        let mut names = NameInterner::new();
+
+        // Account for a new node at the given depth, failing if that would
+        // exceed the limits.
+        let document = *self;
+        let gc_context = activation.context.gc_context;
+        let mut check_limits = |depth: usize| {
+            let error = if depth > options.max_depth {
+                XmlParseError::TooDeep(options.max_depth)
+            } else if node_count >= options.max_nodes {
+                XmlParseError::TooManyNodes(options.max_nodes)
+            } else {
+                node_count += 1;
+                return Ok(());
+            };
+            document.0.write(gc_context).status = XmlStatus::OutOfMemory;
+            Err(error)
+        };
 
         self.0.write(activation.context.gc_context).status = XmlStatus::NoError;
 
+        let id_map = self.id_map();
+
         loop {
             let event = parser.read_event(&mut buf).map_err(|error| {
                 self.0.write(activation.context.gc_context).status = match error {
@@ -150,7 +202,8 @@ impl<'gc> XmlObject<'gc> {
 
             match event {
                 Event::Start(bs) => {
-                    let child = XmlNode::from_start_event(activation, bs, self.id_map())?;
+                    check_limits(open_tags.len())?;
+                    let child = element(activation, bs, id_map, options, &mut names)?;
                     open_tags
                         .last_mut()
                         .unwrap()
@@ -158,20 +211,24 @@ impl<'gc> XmlObject<'gc> {
                     open_tags.push(child);
                 }
                 Event::Empty(bs) => {
-                    let child = XmlNode::from_start_event(activation, bs, self.id_map())?;
+                    check_limits(open_tags.len())?;
+                    let child = element(activation, bs, id_map, options, &mut names)?;
                     open_tags
                         .last_mut()
                         .unwrap()
                         .append_child(activation.context.gc_context, child);
                 }
                 Event::End(_) => {
-                    open_tags.pop();
+                    // Stray end tags are only possible in HTML mode; never
+                    // pop the document itself.
+                    if open_tags.len() > 1 {
+                        open_tags.pop();
+                    }
                 }
                 Event::Text(bt) | Event::CData(bt) => {
                     let text = bt.unescaped()?;
-                    let is_whitespace_char = |c: &u8| matches!(*c, b'\t' | b'\n' | b'\r' | b' ');
-                    let is_whitespace_text = text.iter().all(is_whitespace_char);
-                    if !(text.is_empty() || ignore_white && is_whitespace_text) {
+                    if let Some(text) = options.process_white(&text) {
+                        check_limits(open_tags.len())?;
                         let text = AvmString::new_utf8_bytes(activation.context.gc_context, &text);
                         let child =
                             XmlNode::new(activation.context.gc_context, TEXT_NODE, Some(text));
@@ -217,6 +274,38 @@ impl<'gc> XmlObject<'gc> {
     }
 }
 
+/// Build an element from a start tag, lowercasing its name if names are
+/// case-insensitive.
+fn element<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    bs: BytesStart<'_>,
+    id_map: ScriptObject<'gc>,
+    options: ParseOptions,
+    names: &mut NameInterner<'gc>,
+) -> Result<XmlNode<'gc>, quick_xml::Error> {
+    let lowercase_name = if options.case_insensitive {
+        Some(bs.name().to_ascii_lowercase())
+    } else {
+        None
+    };
+
+    let mut names = if options.intern_names {
+        Some(names)
+    } else {
+        None
+    };
+    let node = XmlNode::from_start_event(activation, bs, id_map, names.as_deref_mut())?;
+    if let Some(lowercase_name) = lowercase_name {
+        let gc_context = activation.context.gc_context;
+        let name = match names {
+            Some(names) => names.intern(gc_context, &lowercase_name),
+            None => AvmString::new_utf8_bytes(gc_context, &lowercase_name),
+        };
+        node.set_node_value(gc_context, name);
+    }
+    Ok(node)
+}
+
 impl fmt::Debug for XmlObject<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let this = self.0.read();
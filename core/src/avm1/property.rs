@@ -114,16 +114,21 @@ impl<'gc> Property<'gc> {
         self.attributes = attributes;
     }
 
+    /// Checks if all of the given attributes are set on this property.
+    pub fn has_attribute(&self, attribute: Attribute) -> bool {
+        self.attributes.contains(attribute)
+    }
+
     pub fn is_enumerable(&self) -> bool {
-        !self.attributes.contains(Attribute::DONT_ENUM)
+        !self.has_attribute(Attribute::DONT_ENUM)
     }
 
     pub fn can_delete(&self) -> bool {
-        !self.attributes.contains(Attribute::DONT_DELETE)
+        !self.has_attribute(Attribute::DONT_DELETE)
     }
 
     pub fn is_overwritable(&self) -> bool {
-        !self.attributes.contains(Attribute::READ_ONLY)
+        !self.has_attribute(Attribute::READ_ONLY)
     }
 
     pub fn is_virtual(&self) -> bool {
@@ -151,3 +156,31 @@ impl fmt::Debug for Property<'_> {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attribute_predicates() {
+        for bits in 0..8 {
+            let attributes = Attribute::from_bits_truncate(bits);
+            let property = Property::new_stored(Value::Undefined, attributes);
+
+            assert_eq!(property.attributes(), attributes);
+            assert_eq!(
+                property.is_enumerable(),
+                !property.has_attribute(Attribute::DONT_ENUM)
+            );
+            assert_eq!(
+                property.can_delete(),
+                !property.has_attribute(Attribute::DONT_DELETE)
+            );
+            assert_eq!(
+                property.is_overwritable(),
+                !property.has_attribute(Attribute::READ_ONLY)
+            );
+            assert!(property.has_attribute(Attribute::empty()));
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use crate::avm1::error::Error;
 use crate::avm1::test_utils::with_avm;
-use crate::avm1::TObject;
+use crate::avm1::{Activation, ExecutionReason, Object, TObject, Value};
+use crate::display_object::{DisplayObject, TDisplayObject};
+use crate::player::{Player, PlayerBuilder};
+use crate::string::AvmString;
+use crate::tag_utils::SwfMovie;
+use std::sync::{Arc, Mutex};
+use swf::{Rectangle, Twips};
 
 #[test]
 fn locals_into_form_values() {
@@ -23,3 +29,2240 @@ fn locals_into_form_values() {
         Ok(())
     });
 }
+
+/// Creates an empty movie clip inside `parent` via `createEmptyMovieClip`.
+fn create_clip<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    parent: Object<'gc>,
+    name: &'static str,
+    depth: i32,
+) -> Result<DisplayObject<'gc>, Error<'gc>> {
+    let clip = parent.call_method(
+        "createEmptyMovieClip".into(),
+        &[name.into(), depth.into()],
+        activation,
+        ExecutionReason::Special,
+    )?;
+    Ok(clip
+        .coerce_to_object(activation)
+        .as_display_object()
+        .unwrap())
+}
+
+#[test]
+fn movie_clip_object_identity() {
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let created = this.call_method(
+            "createEmptyMovieClip".into(),
+            &["clip".into(), 3.into()],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        created
+            .coerce_to_object(activation)
+            .set("custom", 42.into(), activation)?;
+
+        // Every way of reaching the clip yields the same script object, so
+        // state stored on it is kept.
+        let by_depth = this.call_method(
+            "getInstanceAtDepth".into(),
+            &[3.into()],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let by_name = this.get("clip", activation)?;
+        let clip = created
+            .coerce_to_object(activation)
+            .as_display_object()
+            .unwrap();
+        for value in [by_depth, by_name, clip.object()] {
+            assert_eq!(value, created);
+            assert_eq!(
+                value
+                    .coerce_to_object(activation)
+                    .get("custom", activation)?,
+                42.into()
+            );
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_depth_list() {
+    use crate::display_object::TDisplayObjectContainer;
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let high = create_clip(activation, this, "high", 10)?;
+        let low = create_clip(activation, this, "low", 2)?;
+        let middle = create_clip(activation, this, "middle", 5)?;
+
+        let root = activation.context.stage.root_clip().as_container().unwrap();
+        let depth_list = root.depth_list();
+        assert_eq!(
+            depth_list
+                .iter()
+                .map(|(depth, _)| *depth)
+                .collect::<Vec<_>>(),
+            vec![16386, 16389, 16394]
+        );
+        assert!(DisplayObject::ptr_eq(depth_list[0].1, low));
+        assert!(DisplayObject::ptr_eq(depth_list[1].1, middle));
+        assert!(DisplayObject::ptr_eq(depth_list[2].1, high));
+
+        assert_eq!(root.child_index(low), Some(0));
+        assert_eq!(root.child_index(middle), Some(1));
+        assert_eq!(root.child_index(high), Some(2));
+
+        // Grandchildren are not children.
+        let middle_object = middle.object().coerce_to_object(activation);
+        let nested = create_clip(activation, middle_object, "nested", 1)?;
+        assert_eq!(root.child_index(nested), None);
+        assert_eq!(root.child_index(root.into()), None);
+
+        // Scripts see the same indices, with -1 for anything that isn't a child.
+        for (child, expected) in [(low, 0), (high, 2), (nested, -1)] {
+            let index = this.call_method(
+                "getChildIndex".into(),
+                &[child.object()],
+                activation,
+                ExecutionReason::Special,
+            )?;
+            assert_eq!(index, expected.into());
+        }
+
+        let list = this
+            .call_method(
+                "getDepthList".into(),
+                &[],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+        assert_eq!(list.length(activation)?, 3);
+        for (i, child) in [low, middle, high].into_iter().enumerate() {
+            let element = list
+                .get_element(activation, i as i32)
+                .coerce_to_object(activation)
+                .as_display_object()
+                .unwrap();
+            assert!(DisplayObject::ptr_eq(element, child));
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_render_order_follows_depth() {
+    use crate::display_object::TDisplayObjectContainer;
+
+    fn call<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        object: Object<'gc>,
+        method: &'static str,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        object.call_method(method.into(), args, activation, ExecutionReason::Special)
+    }
+
+    fn child<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        parent: Object<'gc>,
+        name: &'static str,
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        Ok(parent.get(name, activation)?.coerce_to_object(activation))
+    }
+
+    // Checks that the children are rendered in ascending `getDepth` order,
+    // and returns their names in that order.
+    fn render_order<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Vec<String>, Error<'gc>> {
+        let root = activation.context.stage.root_clip().as_container().unwrap();
+        let mut depths = vec![];
+        let mut names = vec![];
+        for child in root.iter_render_list() {
+            let object = child.object().coerce_to_object(activation);
+            depths.push(call(activation, object, "getDepth", &[])?.coerce_to_f64(activation)?);
+            names.push(child.name().to_string());
+        }
+        assert!(
+            depths.windows(2).all(|pair| pair[0] < pair[1]),
+            "children rendered out of depth order: {:?} at {:?}",
+            names,
+            depths
+        );
+        assert_eq!(
+            root.depth_list()
+                .iter()
+                .map(|(_, child)| child.name().to_string())
+                .collect::<Vec<_>>(),
+            names
+        );
+        Ok(names)
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        create_clip(activation, this, "a", 10)?;
+        create_clip(activation, this, "b", 5)?;
+        create_clip(activation, this, "c", 20)?;
+        call(
+            activation,
+            this,
+            "createTextField",
+            &[
+                "t".into(),
+                15.into(),
+                0.into(),
+                0.into(),
+                10.into(),
+                10.into(),
+            ],
+        )?;
+        assert_eq!(render_order(activation)?, ["b", "a", "t", "c"]);
+
+        // Swapping with another clip exchanges both depths.
+        let a = child(activation, this, "a")?;
+        let c = child(activation, this, "c")?;
+        call(activation, a, "swapDepths", &[c.into()])?;
+        assert_eq!(render_order(activation)?, ["b", "c", "t", "a"]);
+
+        // Swapping to an empty depth moves the clip between its neighbours.
+        let b = child(activation, this, "b")?;
+        call(activation, b, "swapDepths", &[17.into()])?;
+        assert_eq!(render_order(activation)?, ["c", "t", "b", "a"]);
+
+        // Duplicates and new clips are inserted at their depths.
+        call(
+            activation,
+            a,
+            "duplicateMovieClip",
+            &["d".into(), 12.into()],
+        )?;
+        create_clip(activation, this, "e", 1)?;
+        let next = call(activation, this, "getNextHighestDepth", &[])?;
+        call(
+            activation,
+            this,
+            "createEmptyMovieClip",
+            &["f".into(), next],
+        )?;
+        assert_eq!(
+            render_order(activation)?,
+            ["e", "c", "d", "t", "b", "a", "f"]
+        );
+
+        // Removing a clip leaves the others in order.
+        call(activation, b, "removeMovieClip", &[])?;
+        assert_eq!(render_order(activation)?, ["e", "c", "d", "t", "a", "f"]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_remove_movie_clip_depth_guard() {
+    use crate::display_object::TDisplayObjectContainer;
+
+    fn children<'gc>(activation: &mut Activation<'_, 'gc, '_>) -> Vec<String> {
+        let root = activation.context.stage.root_clip().as_container().unwrap();
+        root.depth_list()
+            .iter()
+            .map(|(_, child)| child.name().to_string())
+            .collect()
+    }
+
+    fn remove<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        clip: DisplayObject<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        let object = clip.object().coerce_to_object(activation);
+        object.call_method(
+            "removeMovieClip".into(),
+            &[],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        Ok(())
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        // Timeline instances live at negative depths.
+        let timeline = create_clip(activation, this, "timeline", -16383)?;
+        let dynamic = create_clip(activation, this, "dynamic", 3)?;
+        assert_eq!(children(activation), ["timeline", "dynamic"]);
+
+        remove(activation, dynamic)?;
+        assert_eq!(children(activation), ["timeline"]);
+
+        // Removing a timeline instance is a no-op.
+        remove(activation, timeline)?;
+        assert_eq!(children(activation), ["timeline"]);
+
+        // Unless it is first swapped to a dynamic depth.
+        let object = timeline.object().coerce_to_object(activation);
+        object.call_method(
+            "swapDepths".into(),
+            &[0.into()],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        remove(activation, timeline)?;
+        assert!(children(activation).is_empty());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_init_object_own_properties() {
+    use crate::avm1::function::{Executable, FunctionObject};
+    use crate::avm1::{Attribute, ScriptObject};
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let original = create_clip(activation, this, "original", 1)?
+            .object()
+            .coerce_to_object(activation);
+
+        let proto = ScriptObject::new(mc, Some(activation.context.avm1.prototypes().object));
+        proto.set("inherited", 1.into(), activation)?;
+        let init_object = ScriptObject::new(mc, Some(proto.into()));
+        let function_proto = activation.context.avm1.prototypes().function;
+        let function = FunctionObject::function(
+            mc,
+            Executable::Native(|_, _, _| Ok(Value::Undefined)),
+            function_proto,
+            function_proto,
+        );
+        init_object.set("number", 5.into(), activation)?;
+        init_object.set("string", "text".into(), activation)?;
+        init_object.set("function", function.into(), activation)?;
+        init_object.define_value(mc, "hidden", true.into(), Attribute::DONT_ENUM);
+
+        let copy = original
+            .call_method(
+                "duplicateMovieClip".into(),
+                &["copy".into(), 2.into(), Value::Object(init_object.into())],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+
+        // Only the init object's own enumerable properties are copied.
+        assert_eq!(copy.get("number", activation)?, 5.into());
+        assert_eq!(copy.get("string", activation)?, "text".into());
+        assert_eq!(copy.get("function", activation)?, function.into());
+        assert!(!copy.has_own_property(activation, "hidden".into()));
+        assert!(!copy.has_own_property(activation, "inherited".into()));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_reserved_depths() {
+    fn call<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        object: Object<'gc>,
+        method: &'static str,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        object.call_method(method.into(), args, activation, ExecutionReason::Special)
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        // Timeline instances live at negative depths, which are never handed
+        // out by `getNextHighestDepth`.
+        let timeline = create_clip(activation, this, "timeline", -16383)?
+            .object()
+            .coerce_to_object(activation);
+        assert_eq!(
+            call(activation, timeline, "getDepth", &[])?,
+            (-16383).into()
+        );
+        assert_eq!(
+            call(activation, this, "getNextHighestDepth", &[])?,
+            0.into()
+        );
+
+        let a = create_clip(activation, this, "a", 0)?
+            .object()
+            .coerce_to_object(activation);
+        let b = create_clip(activation, this, "b", 1)?
+            .object()
+            .coerce_to_object(activation);
+        assert_eq!(
+            call(activation, this, "getNextHighestDepth", &[])?,
+            2.into()
+        );
+
+        // Swapping dynamic clips leaves the timeline instance alone.
+        call(activation, a, "swapDepths", &[b.into()])?;
+        assert_eq!(call(activation, a, "getDepth", &[])?, 1.into());
+        assert_eq!(call(activation, b, "getDepth", &[])?, 0.into());
+        assert_eq!(
+            call(activation, timeline, "getDepth", &[])?,
+            (-16383).into()
+        );
+
+        call(activation, a, "swapDepths", &[5.into()])?;
+        assert_eq!(call(activation, a, "getDepth", &[])?, 5.into());
+        assert_eq!(
+            call(activation, this, "getNextHighestDepth", &[])?,
+            6.into()
+        );
+
+        // Scripts may still move a clip into the timeline range explicitly.
+        call(activation, a, "swapDepths", &[(-100).into()])?;
+        assert_eq!(call(activation, a, "getDepth", &[])?, (-100).into());
+        assert_eq!(
+            call(activation, this, "getNextHighestDepth", &[])?,
+            1.into()
+        );
+        assert_eq!(
+            call(activation, timeline, "getDepth", &[])?,
+            (-16383).into()
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_depth_collision() {
+    use crate::display_object::TDisplayObjectContainer;
+    use crate::string::WStr;
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let old = create_clip(activation, this, "old", 5)?;
+        let new = create_clip(activation, this, "new", 5)?;
+
+        // The previous occupant is evicted and fully detached.
+        assert!(old.removed());
+        assert!(old.parent().is_none());
+
+        let root = activation.context.stage.root_clip().as_container().unwrap();
+        assert_eq!(root.child_index(old), None);
+        assert_eq!(root.child_index(new), Some(0));
+        assert!(root.child_by_name(WStr::from_units(b"old"), true).is_none());
+        assert!(DisplayObject::ptr_eq(
+            root.child_by_depth(16389).unwrap(),
+            new
+        ));
+        assert_eq!(root.depth_list().len(), 1);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn resolve_slash_path() {
+    use crate::string::WStr;
+
+    fn resolve<'gc>(start: DisplayObject<'gc>, path: &str) -> Option<DisplayObject<'gc>> {
+        start.resolve_slash_path(WStr::from_units(path.as_bytes()), false)
+    }
+
+    fn resolves_to<'gc>(
+        start: DisplayObject<'gc>,
+        path: &str,
+        expected: DisplayObject<'gc>,
+    ) -> bool {
+        resolve(start, path).map_or(false, |o| DisplayObject::ptr_eq(o, expected))
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let root = activation.context.stage.root_clip();
+        let a = create_clip(activation, this, "a", 1)?;
+        let b = create_clip(activation, this, "b", 2)?;
+        let a_object = a.object().coerce_to_object(activation);
+        let child = create_clip(activation, a_object, "child", 1)?;
+
+        // Absolute paths.
+        assert!(resolves_to(child, "/", root));
+        assert!(resolves_to(child, "/a", a));
+        assert!(resolves_to(b, "/a/child", child));
+
+        // Relative paths.
+        assert!(resolves_to(root, "a/child", child));
+        assert!(resolves_to(a, "child", child));
+        assert!(resolves_to(a, "", a));
+
+        // Parent traversal.
+        assert!(resolves_to(child, "..", a));
+        assert!(resolves_to(child, "../..", root));
+        assert!(resolves_to(child, "../../b", b));
+        assert!(resolves_to(a, "child/../../b/", b));
+
+        // Invalid paths.
+        assert!(resolve(child, "../missing").is_none());
+        assert!(resolve(root, "..").is_none());
+        assert!(resolve(b, "child").is_none());
+
+        Ok(())
+    });
+}
+
+/// Appends `event` to the `log` property of `this`.
+fn log_event<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    event: &'static str,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let log = this.get("log", activation)?.coerce_to_string(activation)?;
+    let log = AvmString::concat(activation.context.gc_context, log, event.into());
+    let log = AvmString::concat(activation.context.gc_context, log, ",".into());
+    this.set("log", log.into(), activation)?;
+    Ok(Value::Undefined)
+}
+
+/// Creates a player running an empty 550x400 movie.
+fn create_player() -> Arc<Mutex<Player>> {
+    let header = swf::Header {
+        compression: swf::Compression::None,
+        version: 8,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(550.0),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(400.0),
+        },
+        frame_rate: swf::Fixed8::ONE,
+        num_frames: 1,
+    };
+    let mut data = Vec::new();
+    swf::write_swf(&header, &[swf::Tag::ShowFrame], &mut data).unwrap();
+    let movie = SwfMovie::from_data(&data, None, None).unwrap();
+    PlayerBuilder::new().with_movie(movie).build()
+}
+
+#[test]
+fn movie_clip_button_handlers() {
+    use crate::avm1::function::{Executable, FunctionObject};
+    use crate::avm1::ActivationIdentifier;
+    use crate::events::{MouseButton, PlayerEvent};
+
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+
+    // Run `f` on the `button` clip, returning and clearing its event log.
+    fn with_button(
+        player: &mut Player,
+        f: for<'gc> fn(&mut Activation<'_, 'gc, '_>, Object<'gc>),
+    ) -> String {
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let mut activation = Activation::from_nothing(
+                context.reborrow(),
+                ActivationIdentifier::root("[Test]"),
+                root,
+            );
+            let root = root.object().coerce_to_object(&mut activation);
+            let button = root
+                .get("button", &mut activation)
+                .unwrap()
+                .coerce_to_object(&mut activation);
+            f(&mut activation, button);
+            let log = button.get("log", &mut activation).unwrap();
+            let log = log.coerce_to_string(&mut activation).unwrap();
+            button.set("log", "".into(), &mut activation).unwrap();
+            log.to_utf8_lossy().into_owned()
+        })
+    }
+
+    fn mouse(player: &mut Player, events: &[(&str, f64, f64)]) {
+        for &(event, x, y) in events {
+            player.handle_event(match event {
+                "move" => PlayerEvent::MouseMove { x, y },
+                "down" => PlayerEvent::MouseDown {
+                    x,
+                    y,
+                    button: MouseButton::Left,
+                },
+                "up" => PlayerEvent::MouseUp {
+                    x,
+                    y,
+                    button: MouseButton::Left,
+                },
+                _ => unreachable!(),
+            });
+        }
+    }
+
+    // Create a 100x100 clip with every button handler logging its name.
+    player.mutate_with_update_context(|context| {
+        let root = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Test]"),
+            root,
+        );
+        let activation = &mut activation;
+        let root = root.object().coerce_to_object(activation);
+        let button = create_clip(activation, root, "button", 1)
+            .unwrap()
+            .object()
+            .coerce_to_object(activation);
+
+        for (method, args) in [
+            ("beginFill", vec![0xFF0000.into()]),
+            ("moveTo", vec![0.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 100.into()]),
+            ("lineTo", vec![0.into(), 100.into()]),
+            ("lineTo", vec![0.into(), 0.into()]),
+            ("endFill", vec![]),
+        ] {
+            button
+                .call_method(method.into(), &args, activation, ExecutionReason::Special)
+                .unwrap();
+        }
+
+        let handlers: [(&str, crate::avm1::function::NativeFunction); 7] = [
+            ("onPress", |activation, this, _| {
+                log_event(activation, this, "press")
+            }),
+            ("onRelease", |activation, this, _| {
+                log_event(activation, this, "release")
+            }),
+            ("onReleaseOutside", |activation, this, _| {
+                log_event(activation, this, "releaseOutside")
+            }),
+            ("onRollOver", |activation, this, _| {
+                log_event(activation, this, "rollOver")
+            }),
+            ("onRollOut", |activation, this, _| {
+                log_event(activation, this, "rollOut")
+            }),
+            ("onDragOver", |activation, this, _| {
+                log_event(activation, this, "dragOver")
+            }),
+            ("onDragOut", |activation, this, _| {
+                log_event(activation, this, "dragOut")
+            }),
+        ];
+        let function_proto = activation.context.avm1.prototypes().function;
+        for (name, handler) in handlers {
+            let handler = FunctionObject::function(
+                activation.context.gc_context,
+                Executable::Native(handler),
+                function_proto,
+                function_proto,
+            );
+            button.set(name, handler.into(), activation).unwrap();
+        }
+        button.set("log", "".into(), activation).unwrap();
+    });
+
+    // Click inside the clip.
+    mouse(
+        &mut player,
+        &[
+            ("move", 50.0, 50.0),
+            ("down", 50.0, 50.0),
+            ("up", 50.0, 50.0),
+        ],
+    );
+    assert_eq!(
+        with_button(&mut player, |_, _| {}),
+        "rollOver,press,release,"
+    );
+
+    // Drag out and back in, then release outside.
+    mouse(
+        &mut player,
+        &[
+            ("down", 50.0, 50.0),
+            ("move", 200.0, 200.0),
+            ("move", 50.0, 50.0),
+            ("move", 200.0, 200.0),
+            ("up", 200.0, 200.0),
+        ],
+    );
+    assert_eq!(
+        with_button(&mut player, |_, _| {}),
+        "press,dragOut,dragOver,dragOut,releaseOutside,"
+    );
+
+    // Pressing and releasing outside of the clip does nothing.
+    mouse(&mut player, &[("down", 300.0, 300.0), ("up", 300.0, 300.0)]);
+    assert_eq!(with_button(&mut player, |_, _| {}), "");
+
+    // Disabled clips do not receive button events.
+    with_button(&mut player, |activation, button| {
+        button.set("enabled", false.into(), activation).unwrap();
+    });
+    mouse(
+        &mut player,
+        &[
+            ("move", 50.0, 50.0),
+            ("down", 50.0, 50.0),
+            ("up", 50.0, 50.0),
+        ],
+    );
+    assert_eq!(with_button(&mut player, |_, _| {}), "");
+
+    // Neither do invisible clips.
+    mouse(&mut player, &[("move", 300.0, 300.0)]);
+    with_button(&mut player, |activation, button| {
+        button.set("enabled", true.into(), activation).unwrap();
+        button.set("_visible", false.into(), activation).unwrap();
+    });
+    mouse(
+        &mut player,
+        &[
+            ("move", 50.0, 50.0),
+            ("down", 50.0, 50.0),
+            ("up", 50.0, 50.0),
+        ],
+    );
+    assert_eq!(with_button(&mut player, |_, _| {}), "");
+}
+
+#[test]
+fn text_format_color() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let constructor = activation
+            .context
+            .avm1
+            .global_object()
+            .get("TextFormat", activation)?
+            .coerce_to_object(activation);
+
+        for color in [Value::from(0xFF0000), "#FF0000".into(), "#ff0000".into()] {
+            let text_format = constructor
+                .construct(activation, &[])?
+                .coerce_to_object(activation);
+            text_format.set("color", color, activation)?;
+            assert_eq!(text_format.get("color", activation)?, 0xFF0000.into());
+        }
+
+        for color in [Value::Undefined, Value::Null] {
+            let text_format = constructor
+                .construct(activation, &[])?
+                .coerce_to_object(activation);
+            text_format.set("color", color, activation)?;
+            assert_eq!(text_format.get("color", activation)?, Value::Null);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn text_format_color_round_trip() {
+    use crate::avm1::object::NativeObject;
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let constructor = activation
+            .context
+            .avm1
+            .global_object()
+            .get("TextFormat", activation)?
+            .coerce_to_object(activation);
+
+        // `TextFormat.color` is RGB only: any alpha byte is ignored, and the
+        // stored color is always opaque.
+        for (color, rgb) in [
+            (Value::from(0x123456), 0x123456),
+            (Value::from(0x80123456u32), 0x123456),
+            ("#123456".into(), 0x123456),
+        ] {
+            let text_format = constructor
+                .construct(activation, &[])?
+                .coerce_to_object(activation);
+            text_format.set("color", color, activation)?;
+            assert_eq!(text_format.get("color", activation)?, rgb.into());
+
+            let stored = match text_format.native() {
+                NativeObject::TextFormat(text_format) => text_format.read().color.clone(),
+                _ => panic!("not a TextFormat"),
+            };
+            assert_eq!(stored, Some(swf::Color::from_rgb(rgb, 255)));
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn text_format_properties_are_virtual() {
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        this.call_method(
+            "createTextField".into(),
+            &[
+                "field".into(),
+                1.into(),
+                0.into(),
+                0.into(),
+                100.into(),
+                100.into(),
+            ],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let field = this.get("field", activation)?.coerce_to_object(activation);
+        field.set("text", "hello".into(), activation)?;
+
+        let constructor = activation
+            .context
+            .avm1
+            .global_object()
+            .get("TextFormat", activation)?
+            .coerce_to_object(activation);
+        let format = constructor
+            .construct(activation, &[])?
+            .coerce_to_object(activation);
+        format.set("bold", true.into(), activation)?;
+        format.set("size", 20.into(), activation)?;
+        format.set("color", 0xFF0000.into(), activation)?;
+        field.call_method(
+            "setTextFormat".into(),
+            &[format.into()],
+            activation,
+            ExecutionReason::Special,
+        )?;
+
+        // The returned format has no properties of its own; every field is
+        // read from the underlying format by the prototype's accessors.
+        let result = field
+            .call_method(
+                "getTextFormat".into(),
+                &[],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+        for name in [
+            "font",
+            "size",
+            "color",
+            "url",
+            "target",
+            "bold",
+            "italic",
+            "underline",
+            "align",
+            "leftMargin",
+            "rightMargin",
+            "indent",
+            "leading",
+            "blockIndent",
+            "tabStops",
+            "bullet",
+            "kerning",
+            "letterSpacing",
+        ] {
+            assert!(result.has_property(activation, name.into()), "{name}");
+            assert!(!result.has_own_property(activation, name.into()), "{name}");
+        }
+        for name in ["bold", "size", "color"] {
+            assert_eq!(
+                result.get(name, activation)?,
+                format.get(name, activation)?,
+                "{name}"
+            );
+        }
+
+        // Writing a field updates the returned format, but not the text.
+        result.set("size", 30.into(), activation)?;
+        assert_eq!(result.get("size", activation)?, 30.into());
+        let current = field
+            .call_method(
+                "getTextFormat".into(),
+                &[],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+        assert_eq!(current.get("size", activation)?, 20.into());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_mouse_position() {
+    use crate::avm1::ActivationIdentifier;
+    use crate::events::PlayerEvent;
+
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+
+    player.mutate_with_update_context(|context| {
+        let root = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Test]"),
+            root,
+        );
+        let activation = &mut activation;
+        let root = root.object().coerce_to_object(activation);
+        let clip = create_clip(activation, root, "clip", 1)
+            .unwrap()
+            .object()
+            .coerce_to_object(activation);
+        clip.set("_x", 100.into(), activation).unwrap();
+        clip.set("_y", 50.into(), activation).unwrap();
+        clip.set("_xscale", 200.into(), activation).unwrap();
+        clip.set("_yscale", 50.into(), activation).unwrap();
+    });
+
+    player.handle_event(PlayerEvent::MouseMove { x: 150.0, y: 70.0 });
+
+    player.mutate_with_update_context(|context| {
+        let root = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Test]"),
+            root,
+        );
+        let activation = &mut activation;
+        let root = root.object().coerce_to_object(activation);
+        let clip = root
+            .get("clip", activation)
+            .unwrap()
+            .coerce_to_object(activation);
+
+        assert_eq!(root.get("_xmouse", activation).unwrap(), 150.into());
+        assert_eq!(root.get("_ymouse", activation).unwrap(), 70.into());
+        assert_eq!(clip.get("_xmouse", activation).unwrap(), 25.into());
+        assert_eq!(clip.get("_ymouse", activation).unwrap(), 40.into());
+
+        // The properties are read-only.
+        clip.set("_xmouse", 0.into(), activation).unwrap();
+        assert_eq!(clip.get("_xmouse", activation).unwrap(), 25.into());
+    });
+}
+
+#[test]
+fn movie_clip_decomposed_transform() {
+    use ruffle_render::matrix::Matrix;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-4,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        let matrix = Matrix::translate(Twips::from_pixels(10.0), Twips::from_pixels(-20.0))
+            * Matrix::rotate(30f32.to_radians())
+            * Matrix::scale(2.0, 0.5);
+        clip.set_matrix(activation.context.gc_context, matrix);
+
+        let transform = clip.decomposed_transform(activation.context.gc_context);
+        assert_close(10.0, transform.tx);
+        assert_close(-20.0, transform.ty);
+        assert_close(200.0, transform.scale_x.percent());
+        assert_close(50.0, transform.scale_y.percent());
+        assert_close(30.0, transform.rotation.into());
+        assert_close(0.0, transform.skew);
+
+        // The components agree with the ActionScript properties.
+        let object = clip.object().coerce_to_object(activation);
+        for (name, value) in [
+            ("_x", transform.tx),
+            ("_y", transform.ty),
+            ("_xscale", transform.scale_x.percent()),
+            ("_yscale", transform.scale_y.percent()),
+            ("_rotation", transform.rotation.into()),
+        ] {
+            let property = object.get(name, activation)?.coerce_to_f64(activation)?;
+            assert_close(value, property);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_hit_test_matches_get_bounds() {
+    fn call<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        object: Object<'gc>,
+        method: &'static str,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        object.call_method(method.into(), args, activation, ExecutionReason::Special)
+    }
+
+    /// Creates a clip with a filled `size` by `size` square.
+    fn create_square<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        parent: Object<'gc>,
+        name: &'static str,
+        depth: i32,
+        size: i32,
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        let clip = create_clip(activation, parent, name, depth)?
+            .object()
+            .coerce_to_object(activation);
+        call(activation, clip, "beginFill", &[0xFF0000.into()])?;
+        call(activation, clip, "moveTo", &[0.into(), 0.into()])?;
+        call(activation, clip, "lineTo", &[size.into(), 0.into()])?;
+        call(activation, clip, "lineTo", &[size.into(), size.into()])?;
+        call(activation, clip, "lineTo", &[0.into(), size.into()])?;
+        call(activation, clip, "lineTo", &[0.into(), 0.into()])?;
+        call(activation, clip, "endFill", &[])?;
+        Ok(clip)
+    }
+
+    /// Gets the global bounds of `clip` as `[xMin, yMin, xMax, yMax]`.
+    fn global_bounds<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        clip: Object<'gc>,
+        root: Object<'gc>,
+    ) -> Result<[f64; 4], Error<'gc>> {
+        let bounds =
+            call(activation, clip, "getBounds", &[root.into()])?.coerce_to_object(activation);
+        let mut out = [0.0; 4];
+        for (value, name) in out.iter_mut().zip(["xMin", "yMin", "xMax", "yMax"]) {
+            *value = bounds.get(name, activation)?.coerce_to_f64(activation)?;
+        }
+        Ok(out)
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let a = create_square(activation, this, "a", 1, 100)?;
+        let b = create_square(activation, this, "b", 2, 50)?;
+        a.set("_x", 100.into(), activation)?;
+        a.set("_y", 100.into(), activation)?;
+        b.set("_rotation", 45.into(), activation)?;
+
+        // Positions around `a`, including ones where only the corners of the
+        // rotated clip's bounding box reach it.
+        for (x, y) in [
+            (150.0, 150.0),
+            (60.0, 60.0),
+            (25.0, 60.0),
+            (210.0, 90.0),
+            (240.0, 150.0),
+            (150.0, 30.0),
+            (150.0, 20.0),
+            (95.0, 215.0),
+        ] {
+            b.set("_x", x.into(), activation)?;
+            b.set("_y", y.into(), activation)?;
+
+            let a_bounds = global_bounds(activation, a, this)?;
+            let b_bounds = global_bounds(activation, b, this)?;
+            let overlaps = a_bounds[0] < b_bounds[2]
+                && b_bounds[0] < a_bounds[2]
+                && a_bounds[1] < b_bounds[3]
+                && b_bounds[1] < a_bounds[3];
+
+            assert_eq!(
+                call(activation, a, "hitTest", &[b.into()])?,
+                overlaps.into(),
+                "a.hitTest(b) with b at ({}, {})",
+                x,
+                y
+            );
+            assert_eq!(
+                call(activation, b, "hitTest", &[a.into()])?,
+                overlaps.into(),
+                "b.hitTest(a) with b at ({}, {})",
+                x,
+                y
+            );
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn xml_on_data_calls_on_load() {
+    use crate::avm1::function::{Executable, FunctionObject};
+    use crate::avm1::object::xml_object::XmlStatus;
+
+    fn on_load<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        this: Object<'gc>,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let success = args
+            .get(0)
+            .map_or(false, |v| v.as_bool(activation.swf_version()));
+        log_event(activation, this, if success { "true" } else { "false" })
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let constructor = activation
+            .context
+            .avm1
+            .global_object()
+            .get("XML", activation)?
+            .coerce_to_object(activation);
+        let function_proto = activation.context.avm1.prototypes().function;
+
+        // The loader passes the response body to `onData`, or `undefined` if
+        // the load failed.
+        for (data, log, status) in [
+            (Value::from("<a><b/></a>"), "true,", XmlStatus::NoError),
+            ("<a><b></a>".into(), "false,", XmlStatus::MismatchedEnd),
+            (Value::Undefined, "false,", XmlStatus::NoError),
+        ] {
+            let xml = constructor
+                .construct(activation, &[])?
+                .coerce_to_object(activation);
+            let handler = FunctionObject::function(
+                activation.context.gc_context,
+                Executable::Native(on_load),
+                function_proto,
+                function_proto,
+            );
+            xml.set("onLoad", handler.into(), activation)?;
+            xml.set("log", "".into(), activation)?;
+
+            xml.call_method(
+                "onData".into(),
+                &[data],
+                activation,
+                ExecutionReason::Special,
+            )?;
+
+            assert_eq!(
+                xml.get("log", activation)?,
+                AvmString::from(log).into(),
+                "log after onData({:?})",
+                data
+            );
+            assert_eq!(xml.get("status", activation)?, (status as i8).into());
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_bitmap_fill() {
+    use crate::avm1::globals::matrix::matrix_to_object;
+    use ruffle_render::bitmap::BitmapSource;
+    use ruffle_render::matrix::Matrix;
+    use swf::FillStyle;
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        let movie_clip = clip.as_movie_clip().unwrap();
+        let object = clip.object().coerce_to_object(activation);
+
+        let mut constructor = Value::Object(activation.context.avm1.global_object());
+        for name in ["flash", "display", "BitmapData"] {
+            constructor = constructor
+                .coerce_to_object(activation)
+                .get(name, activation)?;
+        }
+        let bitmap_data = constructor
+            .coerce_to_object(activation)
+            .construct(activation, &[40.into(), 30.into()])?;
+        let matrix = Matrix::translate(Twips::from_pixels(10.0), Twips::from_pixels(20.0));
+        let matrix_object = matrix_to_object(matrix, activation)?;
+
+        object.call_method(
+            "beginBitmapFill".into(),
+            &[bitmap_data, matrix_object, false.into(), true.into()],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        {
+            let drawing = movie_clip.drawing(activation.context.gc_context);
+            // The matrix is given in pixels, but the fill style is in twips.
+            let twips_matrix = matrix
+                * Matrix::scale(Twips::TWIPS_PER_PIXEL as f32, Twips::TWIPS_PER_PIXEL as f32);
+            assert_eq!(
+                drawing.fill_style(),
+                Some(&FillStyle::Bitmap {
+                    id: 0,
+                    matrix: twips_matrix.into(),
+                    is_smoothed: true,
+                    is_repeating: false,
+                })
+            );
+            let size = drawing.bitmap_size(0).unwrap();
+            assert_eq!((size.width, size.height), (40, 30));
+        }
+
+        // Three sides of a rectangle; `endFill` closes the fourth.
+        for (name, args) in [
+            ("lineTo", vec![100.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 50.into()]),
+            ("lineTo", vec![0.into(), 50.into()]),
+        ] {
+            object.call_method(name.into(), &args, activation, ExecutionReason::Special)?;
+        }
+        let center = (Twips::from_pixels(50.0), Twips::from_pixels(25.0));
+        assert!(!movie_clip
+            .drawing(activation.context.gc_context)
+            .hit_test(center, &Matrix::IDENTITY));
+
+        object.call_method("endFill".into(), &[], activation, ExecutionReason::Special)?;
+        {
+            let drawing = movie_clip.drawing(activation.context.gc_context);
+            assert_eq!(drawing.fill_style(), None);
+            assert!(drawing.hit_test(center, &Matrix::IDENTITY));
+        }
+
+        // Without a bitmap, no bitmap fill is started.
+        object.call_method(
+            "beginBitmapFill".into(),
+            &[Value::Null],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let drawing = movie_clip.drawing(activation.context.gc_context);
+        assert_eq!(drawing.fill_style(), None);
+        assert!(drawing.bitmap_size(1).is_none());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_gradient_fill() {
+    use crate::avm1::object::array_object::ArrayObject;
+    use crate::avm1::ScriptObject;
+    use swf::{Color, FillStyle, GradientRecord, GradientSpread};
+
+    fn array<'gc>(activation: &mut Activation<'_, 'gc, '_>, values: &[i32]) -> Value<'gc> {
+        let array = ArrayObject::new(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().array,
+            values.iter().map(|v| (*v).into()),
+        );
+        Value::Object(array.into())
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        let movie_clip = clip.as_movie_clip().unwrap();
+        let object = clip.object().coerce_to_object(activation);
+
+        let matrix = ScriptObject::new(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes().object),
+        );
+        for (name, value) in [
+            ("a", 1),
+            ("b", 0),
+            ("c", 0),
+            ("d", 1),
+            ("tx", 10),
+            ("ty", 20),
+        ] {
+            matrix.set(name, value.into(), activation)?;
+        }
+        let matrix = Value::Object(matrix.into());
+        let colors = array(activation, &[0xFF0000, 0x0000FF]);
+        let alphas = array(activation, &[100, 50]);
+        let ratios = array(activation, &[0, 255]);
+
+        object.call_method(
+            "beginGradientFill".into(),
+            &["linear".into(), colors, alphas, ratios, matrix],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let expected = match movie_clip
+            .drawing(activation.context.gc_context)
+            .fill_style()
+        {
+            Some(FillStyle::LinearGradient(gradient)) => {
+                assert_eq!(
+                    gradient.records,
+                    vec![
+                        GradientRecord {
+                            ratio: 0,
+                            color: Color::from_rgb(0xFF0000, 255),
+                        },
+                        GradientRecord {
+                            ratio: 255,
+                            color: Color::from_rgb(0x0000FF, 127),
+                        },
+                    ]
+                );
+                assert_eq!(gradient.spread, GradientSpread::Pad);
+                assert_eq!(gradient.matrix.tx, Twips::from_pixels(10.0));
+                assert_eq!(gradient.matrix.ty, Twips::from_pixels(20.0));
+                FillStyle::LinearGradient(gradient.clone())
+            }
+            style => panic!("expected a linear gradient, got {:?}", style),
+        };
+
+        // Mismatched arrays make Flash ignore the call, keeping the old fill.
+        let short_alphas = array(activation, &[100]);
+        object.call_method(
+            "beginGradientFill".into(),
+            &["radial".into(), colors, short_alphas, ratios, matrix],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        assert_eq!(
+            movie_clip
+                .drawing(activation.context.gc_context)
+                .fill_style(),
+            Some(&expected)
+        );
+
+        // `lineGradientStyle` applies the gradient to the current line style.
+        object.call_method(
+            "lineStyle".into(),
+            &[2.into()],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        object.call_method(
+            "lineGradientStyle".into(),
+            &["radial".into(), colors, alphas, ratios, matrix],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let drawing = movie_clip.drawing(activation.context.gc_context);
+        let line_style = drawing.line_style().unwrap();
+        assert_eq!(line_style.width(), Twips::from_pixels(2.0));
+        assert!(matches!(
+            line_style.fill_style(),
+            FillStyle::RadialGradient(_)
+        ));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_bytes_loaded_streaming() {
+    use crate::avm1::ActivationIdentifier;
+    use crate::limits::ExecutionLimit;
+
+    // Five frames, each padded out so that every frame adds to the byte count.
+    let header = swf::Header {
+        compression: swf::Compression::None,
+        version: 8,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(550.0),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(400.0),
+        },
+        frame_rate: swf::Fixed8::ONE,
+        num_frames: 5,
+    };
+    let padding = [0; 100];
+    let mut tags = Vec::new();
+    for _ in 0..5 {
+        tags.push(swf::Tag::DoAction(&padding));
+        tags.push(swf::Tag::ShowFrame);
+    }
+    let mut data = Vec::new();
+    swf::write_swf(&header, &tags, &mut data).unwrap();
+    let movie = SwfMovie::from_data(&data, None, None).unwrap();
+    let player = PlayerBuilder::new().with_movie(movie).build();
+    let mut player = player.lock().unwrap();
+
+    // Returns `(getBytesLoaded(), getBytesTotal(), _framesloaded)` of the root.
+    fn progress(player: &mut Player) -> (f64, f64, f64) {
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let mut activation = Activation::from_nothing(
+                context.reborrow(),
+                ActivationIdentifier::root("[Test]"),
+                root,
+            );
+            let activation = &mut activation;
+            let root = root.object().coerce_to_object(activation);
+            let mut get = |name: &'static str| {
+                root.call_method(name.into(), &[], activation, ExecutionReason::Special)
+                    .unwrap()
+                    .coerce_to_f64(activation)
+                    .unwrap()
+            };
+            let loaded = get("getBytesLoaded");
+            let total = get("getBytesTotal");
+            let frames = root
+                .get("_framesloaded", activation)
+                .unwrap()
+                .coerce_to_f64(activation)
+                .unwrap();
+            (loaded, total, frames)
+        })
+    }
+
+    let (mut last_loaded, total, mut last_frames) = progress(&mut player);
+    assert!(last_loaded < total);
+    assert_eq!(last_frames, 0.0);
+
+    // Stream in one tag at a time; progress only moves on frame boundaries.
+    while !player.preload(&mut ExecutionLimit::exhausted()) {
+        let (loaded, _, frames) = progress(&mut player);
+        assert!(loaded >= last_loaded);
+        assert!(loaded <= total);
+        assert_eq!(loaded > last_loaded, frames > last_frames);
+        last_loaded = loaded;
+        last_frames = frames;
+    }
+
+    let (loaded, total, frames) = progress(&mut player);
+    assert_eq!(loaded, total);
+    assert_eq!(frames, 5.0);
+
+    // A fully downloaded movie reports everything loaded straight away.
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+    assert!(player.preload(&mut ExecutionLimit::none()));
+    let (loaded, total, _) = progress(&mut player);
+    assert_eq!(loaded, total);
+}
+
+#[test]
+fn movie_clip_tab_order() {
+    use crate::avm1::ActivationIdentifier;
+    use crate::events::{KeyCode, PlayerEvent};
+
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+
+    // Set `property` on each named clip of the root.
+    fn set_all(player: &mut Player, property: &'static str, values: &[(&'static str, i32)]) {
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let mut activation = Activation::from_nothing(
+                context.reborrow(),
+                ActivationIdentifier::root("[Test]"),
+                root,
+            );
+            let activation = &mut activation;
+            let root = root.object().coerce_to_object(activation);
+            for &(name, value) in values {
+                let clip = root
+                    .get(name, activation)
+                    .unwrap()
+                    .coerce_to_object(activation);
+                let value = if property == "tabEnabled" || property == "focusEnabled" {
+                    (value != 0).into()
+                } else {
+                    value.into()
+                };
+                clip.set(property, value, activation).unwrap();
+            }
+        });
+    }
+
+    // Press Tab, returning the name of the newly focused clip.
+    fn tab(player: &mut Player) -> String {
+        player.handle_event(PlayerEvent::KeyDown {
+            key_code: KeyCode::Tab,
+            key_char: None,
+        });
+        player.handle_event(PlayerEvent::KeyUp {
+            key_code: KeyCode::Tab,
+            key_char: None,
+        });
+        player.mutate_with_update_context(|context| {
+            context
+                .focus_tracker
+                .get()
+                .map(|focus| focus.name().to_utf8_lossy().into_owned())
+                .unwrap_or_default()
+        })
+    }
+
+    player.mutate_with_update_context(|context| {
+        let root = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Test]"),
+            root,
+        );
+        let activation = &mut activation;
+        let root = root.object().coerce_to_object(activation);
+        for (depth, name) in ["a", "b", "c"].into_iter().enumerate() {
+            create_clip(activation, root, name, depth as i32 + 1).unwrap();
+        }
+    });
+
+    // Without indices, focusable clips are visited in display order.
+    set_all(&mut player, "focusEnabled", &[("a", 1), ("b", 1), ("c", 1)]);
+    assert_eq!(tab(&mut player), "a");
+    assert_eq!(tab(&mut player), "b");
+    assert_eq!(tab(&mut player), "c");
+    assert_eq!(tab(&mut player), "a");
+
+    // Indices take precedence over display order.
+    set_all(&mut player, "tabIndex", &[("c", 1), ("a", 2), ("b", 3)]);
+    assert_eq!(tab(&mut player), "b");
+    assert_eq!(tab(&mut player), "c");
+    assert_eq!(tab(&mut player), "a");
+    assert_eq!(tab(&mut player), "b");
+
+    // Disabled clips are skipped.
+    set_all(&mut player, "tabEnabled", &[("a", 0)]);
+    assert_eq!(tab(&mut player), "c");
+    assert_eq!(tab(&mut player), "b");
+    assert_eq!(tab(&mut player), "c");
+}
+
+#[test]
+fn movie_clip_drop_target() {
+    use crate::avm1::ActivationIdentifier;
+    use crate::events::PlayerEvent;
+
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+
+    // Run `f` with the root clip's object.
+    fn with_root<R>(
+        player: &mut Player,
+        f: for<'gc> fn(&mut Activation<'_, 'gc, '_>, Object<'gc>) -> R,
+    ) -> R {
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let mut activation = Activation::from_nothing(
+                context.reborrow(),
+                ActivationIdentifier::root("[Test]"),
+                root,
+            );
+            let root = root.object().coerce_to_object(&mut activation);
+            f(&mut activation, root)
+        })
+    }
+
+    // Fill a square of the given size at the origin of `clip`.
+    fn fill_square<'gc>(activation: &mut Activation<'_, 'gc, '_>, clip: Object<'gc>, size: i32) {
+        let mut call = |name: &'static str, args: &[Value<'gc>]| {
+            clip.call_method(name.into(), args, activation, ExecutionReason::Special)
+                .unwrap();
+        };
+        call("beginFill", &[0xFF0000.into()]);
+        call("moveTo", &[0.into(), 0.into()]);
+        call("lineTo", &[size.into(), 0.into()]);
+        call("lineTo", &[size.into(), size.into()]);
+        call("lineTo", &[0.into(), size.into()]);
+        call("lineTo", &[0.into(), 0.into()]);
+        call("endFill", &[]);
+    }
+
+    fn drop_target<'gc>(activation: &mut Activation<'_, 'gc, '_>, root: Object<'gc>) -> String {
+        let dragged = root
+            .get("dragged", activation)
+            .unwrap()
+            .coerce_to_object(activation);
+        dragged
+            .get("_droptarget", activation)
+            .unwrap()
+            .coerce_to_string(activation)
+            .unwrap()
+            .to_utf8_lossy()
+            .into_owned()
+    }
+
+    // A 100x100 drop zone at (100, 100), and a small clip dragged by its center.
+    with_root(&mut player, |activation, root| {
+        let zone = create_clip(activation, root, "zone", 1)
+            .unwrap()
+            .object()
+            .coerce_to_object(activation);
+        fill_square(activation, zone, 100);
+        zone.set("_x", 100.into(), activation).unwrap();
+        zone.set("_y", 100.into(), activation).unwrap();
+
+        let dragged = create_clip(activation, root, "dragged", 2)
+            .unwrap()
+            .object()
+            .coerce_to_object(activation);
+        fill_square(activation, dragged, 10);
+        dragged
+            .call_method(
+                "startDrag".into(),
+                &[true.into()],
+                activation,
+                ExecutionReason::Special,
+            )
+            .unwrap();
+    });
+
+    player.handle_event(PlayerEvent::MouseMove { x: 150.0, y: 150.0 });
+    assert_eq!(with_root(&mut player, drop_target), "/zone");
+
+    player.handle_event(PlayerEvent::MouseMove { x: 400.0, y: 300.0 });
+    assert_eq!(with_root(&mut player, drop_target), "");
+
+    // The drop target is kept once the drag stops.
+    player.handle_event(PlayerEvent::MouseMove { x: 120.0, y: 180.0 });
+    with_root(&mut player, |activation, root| {
+        root.call_method("stopDrag".into(), &[], activation, ExecutionReason::Special)
+            .unwrap();
+    });
+    assert_eq!(with_root(&mut player, drop_target), "/zone");
+}
+
+#[test]
+fn movie_clip_get_rect_excludes_strokes() {
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?
+            .object()
+            .coerce_to_object(activation);
+
+        // A 100x100 filled square with a 10px stroke.
+        for (name, args) in [
+            ("lineStyle", vec![10.into(), 0x000000.into()]),
+            ("beginFill", vec![0xFF0000.into()]),
+            ("moveTo", vec![0.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 100.into()]),
+            ("lineTo", vec![0.into(), 100.into()]),
+            ("lineTo", vec![0.into(), 0.into()]),
+            ("endFill", vec![]),
+        ] {
+            clip.call_method(name.into(), &args, activation, ExecutionReason::Special)?;
+        }
+
+        let bounds = clip
+            .call_method(
+                "getBounds".into(),
+                &[],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+        let rect = clip
+            .call_method("getRect".into(), &[], activation, ExecutionReason::Special)?
+            .coerce_to_object(activation);
+
+        // The stroke extends half its width past each edge.
+        for (name, expected_bounds, expected_rect) in [
+            ("xMin", -5.0, 0.0),
+            ("yMin", -5.0, 0.0),
+            ("xMax", 105.0, 100.0),
+            ("yMax", 105.0, 100.0),
+        ] {
+            assert_eq!(
+                bounds.get(name, activation)?.coerce_to_f64(activation)?,
+                expected_bounds,
+                "getBounds().{name}"
+            );
+            assert_eq!(
+                rect.get(name, activation)?.coerce_to_f64(activation)?,
+                expected_rect,
+                "getRect().{name}"
+            );
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_use_hand_cursor() {
+    use crate::avm1::function::{Executable, FunctionObject};
+    use crate::avm1::ActivationIdentifier;
+    use crate::backend::ui::MouseCursor;
+    use crate::events::PlayerEvent;
+
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+
+    // Two 100x100 clips side by side, each with an `onPress` handler; only
+    // the second one opts out of the hand cursor.
+    player.mutate_with_update_context(|context| {
+        let root = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Test]"),
+            root,
+        );
+        let activation = &mut activation;
+        let root = root.object().coerce_to_object(activation);
+        let function_proto = activation.context.avm1.prototypes().function;
+
+        for (name, depth, x, use_hand_cursor) in [("hand", 1, 0, true), ("arrow", 2, 200, false)] {
+            let clip = create_clip(activation, root, name, depth)
+                .unwrap()
+                .object()
+                .coerce_to_object(activation);
+            for (method, args) in [
+                ("beginFill", vec![0xFF0000.into()]),
+                ("moveTo", vec![0.into(), 0.into()]),
+                ("lineTo", vec![100.into(), 0.into()]),
+                ("lineTo", vec![100.into(), 100.into()]),
+                ("lineTo", vec![0.into(), 100.into()]),
+                ("lineTo", vec![0.into(), 0.into()]),
+                ("endFill", vec![]),
+            ] {
+                clip.call_method(method.into(), &args, activation, ExecutionReason::Special)
+                    .unwrap();
+            }
+            clip.set("_x", x.into(), activation).unwrap();
+
+            let on_press = FunctionObject::function(
+                activation.context.gc_context,
+                Executable::Native(|_, _, _| Ok(Value::Undefined)),
+                function_proto,
+                function_proto,
+            );
+            clip.set("onPress", on_press.into(), activation).unwrap();
+
+            assert_eq!(
+                clip.get("useHandCursor", activation).unwrap(),
+                Value::Bool(true)
+            );
+            clip.set("useHandCursor", use_hand_cursor.into(), activation)
+                .unwrap();
+        }
+    });
+
+    assert_eq!(player.mouse_cursor(), MouseCursor::Arrow);
+
+    player.handle_event(PlayerEvent::MouseMove { x: 50.0, y: 50.0 });
+    assert_eq!(player.mouse_cursor(), MouseCursor::Hand);
+
+    player.handle_event(PlayerEvent::MouseMove { x: 150.0, y: 50.0 });
+    assert_eq!(player.mouse_cursor(), MouseCursor::Arrow);
+
+    player.handle_event(PlayerEvent::MouseMove { x: 250.0, y: 50.0 });
+    assert_eq!(player.mouse_cursor(), MouseCursor::Arrow);
+}
+
+#[test]
+fn movie_clip_scroll_rect() {
+    use crate::avm1::ActivationIdentifier;
+
+    let player = create_player();
+    let mut player = player.lock().unwrap();
+
+    // Run `f` with the `scroller` clip's object, creating it on first use.
+    fn with_scroller<R>(
+        player: &mut Player,
+        f: for<'gc> fn(&mut Activation<'_, 'gc, '_>, Object<'gc>) -> R,
+    ) -> R {
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let mut activation = Activation::from_nothing(
+                context.reborrow(),
+                ActivationIdentifier::root("[Test]"),
+                root,
+            );
+            let root = root.object().coerce_to_object(&mut activation);
+            let scroller = root
+                .get("scroller", &mut activation)
+                .unwrap()
+                .coerce_to_object(&mut activation);
+            f(&mut activation, scroller)
+        })
+    }
+
+    fn get<'gc>(activation: &mut Activation<'_, 'gc, '_>, object: Object<'gc>, path: &str) -> f64 {
+        let mut value = Value::Object(object);
+        for name in path.split('.') {
+            let object = value.coerce_to_object(activation);
+            value = object
+                .get(
+                    AvmString::new_utf8(activation.context.gc_context, name),
+                    activation,
+                )
+                .unwrap();
+        }
+        value.coerce_to_f64(activation).unwrap()
+    }
+
+    // The global position of the content's origin.
+    fn content_origin<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        scroller: Object<'gc>,
+    ) -> (f64, f64) {
+        let content = scroller
+            .get("content", activation)
+            .unwrap()
+            .coerce_to_object(activation)
+            .as_display_object()
+            .unwrap();
+        let matrix = content.local_to_global_matrix();
+        (matrix.tx.to_pixels(), matrix.ty.to_pixels())
+    }
+
+    // A 200x200 square inside a clip at (100, 100).
+    player.mutate_with_update_context(|context| {
+        let root = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Test]"),
+            root,
+        );
+        let activation = &mut activation;
+        let root = root.object().coerce_to_object(activation);
+        let scroller = create_clip(activation, root, "scroller", 1)
+            .unwrap()
+            .object()
+            .coerce_to_object(activation);
+        scroller.set("_x", 100.into(), activation).unwrap();
+        scroller.set("_y", 100.into(), activation).unwrap();
+        let content = create_clip(activation, scroller, "content", 1)
+            .unwrap()
+            .object()
+            .coerce_to_object(activation);
+        for (method, args) in [
+            ("beginFill", vec![0xFF0000.into()]),
+            ("moveTo", vec![0.into(), 0.into()]),
+            ("lineTo", vec![200.into(), 0.into()]),
+            ("lineTo", vec![200.into(), 200.into()]),
+            ("lineTo", vec![0.into(), 200.into()]),
+            ("lineTo", vec![0.into(), 0.into()]),
+            ("endFill", vec![]),
+        ] {
+            content
+                .call_method(method.into(), &args, activation, ExecutionReason::Special)
+                .unwrap();
+        }
+    });
+
+    with_scroller(&mut player, |activation, scroller| {
+        assert_eq!(
+            scroller.get("scrollRect", activation).unwrap(),
+            Value::Undefined
+        );
+
+        let mut rectangle = activation.context.avm1.global_object();
+        for name in ["flash", "geom", "Rectangle"] {
+            rectangle = rectangle
+                .get(name, activation)
+                .unwrap()
+                .coerce_to_object(activation);
+        }
+        let rectangle = rectangle
+            .construct(activation, &[50.into(), 60.into(), 100.into(), 80.into()])
+            .unwrap();
+        scroller.set("scrollRect", rectangle, activation).unwrap();
+
+        // The getter reflects the new rectangle right away.
+        assert_eq!(get(activation, scroller, "scrollRect.x"), 50.0);
+        assert_eq!(get(activation, scroller, "scrollRect.y"), 60.0);
+        assert_eq!(get(activation, scroller, "scrollRect.width"), 100.0);
+        assert_eq!(get(activation, scroller, "scrollRect.height"), 80.0);
+    });
+
+    // The scroll rect takes effect when the clip is next rendered.
+    player.render();
+    with_scroller(&mut player, |activation, scroller| {
+        // The content is clipped to the size of the rectangle...
+        assert_eq!(get(activation, scroller, "_width"), 100.0);
+        assert_eq!(get(activation, scroller, "_height"), 80.0);
+
+        // ...and scrolled by its origin.
+        assert_eq!(content_origin(activation, scroller), (50.0, 40.0));
+
+        scroller.set("scrollRect", Value::Null, activation).unwrap();
+    });
+
+    player.render();
+    with_scroller(&mut player, |activation, scroller| {
+        assert_eq!(
+            scroller.get("scrollRect", activation).unwrap(),
+            Value::Undefined
+        );
+        assert_eq!(get(activation, scroller, "_width"), 200.0);
+        assert_eq!(content_origin(activation, scroller), (100.0, 100.0));
+    });
+}
+
+#[test]
+fn movie_clip_convert_matrix() {
+    use crate::avm1::globals::matrix::{matrix_to_object, object_to_matrix};
+    use crate::avm1::ScriptObject;
+    use ruffle_render::matrix::Matrix;
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        clip.set_matrix(
+            activation.context.gc_context,
+            Matrix::translate(Twips::from_pixels(10.0), Twips::from_pixels(20.0))
+                * Matrix::scale(2.0, 2.0),
+        );
+        let object = clip.object().coerce_to_object(activation);
+
+        // A local matrix is concatenated onto the clip's world matrix.
+        let local = Matrix::translate(Twips::from_pixels(5.0), Twips::from_pixels(5.0))
+            * Matrix::scale(0.5, 1.0);
+        let matrix = matrix_to_object(local, activation)?.coerce_to_object(activation);
+        object.call_method(
+            "localToGlobal".into(),
+            &[Value::Object(matrix)],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let global = object_to_matrix(matrix, activation)?;
+        assert_eq!(global, clip.local_to_global_matrix() * local);
+        assert_eq!(
+            global,
+            Matrix::translate(Twips::from_pixels(20.0), Twips::from_pixels(30.0))
+                * Matrix::scale(1.0, 2.0)
+        );
+
+        // Converting back restores the original matrix.
+        object.call_method(
+            "globalToLocal".into(),
+            &[Value::Object(matrix)],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        assert_eq!(object_to_matrix(matrix, activation)?, local);
+
+        // An object without numeric matrix properties is left unmodified.
+        let invalid = ScriptObject::new(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes().object),
+        );
+        invalid.set("a", "2".into(), activation)?;
+        invalid.set("d", 1.into(), activation)?;
+        object.call_method(
+            "localToGlobal".into(),
+            &[Value::Object(invalid.into())],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        assert_eq!(invalid.get("a", activation)?, "2".into());
+        assert_eq!(invalid.get("d", activation)?, 1.into());
+        assert!(!invalid.has_property(activation, "tx".into()));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_opaque_background() {
+    use crate::context::RenderContext;
+    use crate::display_object::render_base;
+    use ruffle_render::backend::ShapeHandle;
+    use ruffle_render::bitmap::BitmapHandle;
+    use ruffle_render::commands::{CommandHandler, CommandList};
+    use ruffle_render::matrix::Matrix;
+    use ruffle_render::transform::{Transform, TransformStack};
+    use swf::{BlendMode, Color};
+
+    /// Records the rectangles drawn by a command list.
+    #[derive(Default)]
+    struct DrawnRects(Vec<(Color, Matrix)>);
+
+    impl<'a> CommandHandler<'a> for DrawnRects {
+        fn render_bitmap(&mut self, _: &'a BitmapHandle, _: &Transform, _: bool) {}
+        fn render_shape(&mut self, _: ShapeHandle, _: &Transform) {}
+        fn draw_rect(&mut self, color: Color, matrix: &Matrix) {
+            self.0.push((color, *matrix));
+        }
+        fn push_mask(&mut self) {}
+        fn activate_mask(&mut self) {}
+        fn deactivate_mask(&mut self) {}
+        fn pop_mask(&mut self) {}
+        fn push_blend_mode(&mut self, _: BlendMode) {}
+        fn pop_blend_mode(&mut self) {}
+    }
+
+    fn drawn_rects<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        clip: DisplayObject<'gc>,
+    ) -> Vec<(Color, Matrix)> {
+        let context = &mut activation.context;
+        let mut commands = CommandList::new();
+        let mut transform_stack = TransformStack::new();
+        let mut render_context = RenderContext {
+            renderer: context.renderer,
+            commands: &mut commands,
+            gc_context: context.gc_context,
+            ui: context.ui,
+            library: &context.library,
+            transform_stack: &mut transform_stack,
+            is_offscreen: true,
+            stage: context.stage,
+            clip_depth_stack: vec![],
+            allow_mask: true,
+        };
+        render_base(clip, &mut render_context);
+
+        let mut rects = DrawnRects::default();
+        commands.execute(&mut rects);
+        rects.0
+    }
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        let object = clip.object().coerce_to_object(activation);
+        object.set("_x", 10.into(), activation)?;
+        object.set("_y", 20.into(), activation)?;
+        for (name, args) in [
+            ("beginFill", vec![0xFF0000.into()]),
+            ("moveTo", vec![0.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 0.into()]),
+            ("lineTo", vec![100.into(), 50.into()]),
+            ("lineTo", vec![0.into(), 50.into()]),
+            ("lineTo", vec![0.into(), 0.into()]),
+            ("endFill", vec![]),
+        ] {
+            object.call_method(name.into(), &args, activation, ExecutionReason::Special)?;
+        }
+        assert_eq!(object.get("opaqueBackground", activation)?, Value::Null);
+        assert!(drawn_rects(activation, clip).is_empty());
+
+        // The background fills the clip's bounds, behind its content.
+        object.set("opaqueBackground", 0x00FF00.into(), activation)?;
+        assert_eq!(object.get("opaqueBackground", activation)?, 0x00FF00.into());
+        assert_eq!(
+            drawn_rects(activation, clip),
+            vec![(
+                Color::from_rgb(0x00FF00, 255),
+                Matrix::create_box(
+                    100.0,
+                    50.0,
+                    0.0,
+                    Twips::from_pixels(10.0),
+                    Twips::from_pixels(20.0)
+                )
+            )]
+        );
+
+        // `null` clears the background.
+        object.set("opaqueBackground", Value::Null, activation)?;
+        assert_eq!(object.get("opaqueBackground", activation)?, Value::Null);
+        assert!(drawn_rects(activation, clip).is_empty());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_filters() {
+    use crate::avm1::ArrayObject;
+    use swf::{BlurFilter, Filter, Fixed16};
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        let object = clip.object().coerce_to_object(activation);
+        assert!(clip.bitmap_filters().is_empty());
+
+        let blur_filter = activation
+            .context
+            .avm1
+            .prototypes()
+            .blur_filter_constructor
+            .construct(activation, &[5.into(), 7.into()])?;
+        let filters = ArrayObject::new(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().array,
+            [blur_filter],
+        );
+        object.set("filters", filters.into(), activation)?;
+        assert_eq!(
+            clip.bitmap_filters(),
+            vec![Filter::BlurFilter(Box::new(BlurFilter {
+                blur_x: Fixed16::from_f64(5.0),
+                blur_y: Fixed16::from_f64(7.0),
+                num_passes: 1,
+            }))]
+        );
+
+        // Reading the filters back yields a copy of each filter.
+        let filters = object
+            .get("filters", activation)?
+            .coerce_to_object(activation);
+        assert_eq!(filters.length(activation)?, 1);
+        let filter = filters
+            .get_element(activation, 0)
+            .coerce_to_object(activation);
+        assert_ne!(Value::Object(filter), blur_filter);
+        assert_eq!(filter.get("blurX", activation)?, 5.into());
+        assert_eq!(filter.get("blurY", activation)?, 7.into());
+        assert_eq!(filter.get("quality", activation)?, 1.into());
+
+        // Filters the renderer doesn't support are kept, and read back as well.
+        let glow_filter = activation
+            .context
+            .avm1
+            .prototypes()
+            .glow_filter_constructor
+            .construct(activation, &[0x00FF00.into(), 1.into(), 2.into(), 3.into()])?;
+        glow_filter
+            .coerce_to_object(activation)
+            .set("inner", true.into(), activation)?;
+        let filters = ArrayObject::new(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().array,
+            [glow_filter],
+        );
+        object.set("filters", filters.into(), activation)?;
+        assert!(matches!(
+            &clip.bitmap_filters()[..],
+            [Filter::GlowFilter(_)]
+        ));
+        let filter = object
+            .get("filters", activation)?
+            .coerce_to_object(activation)
+            .get_element(activation, 0)
+            .coerce_to_object(activation);
+        assert_eq!(filter.get("color", activation)?, 0x00FF00.into());
+        assert_eq!(filter.get("blurX", activation)?, 2.into());
+        assert_eq!(filter.get("inner", activation)?, true.into());
+
+        // Strengths of 128 and over survive the conversion.
+        let bevel_filter = activation
+            .context
+            .avm1
+            .prototypes()
+            .bevel_filter_constructor
+            .construct(
+                activation,
+                &[
+                    4.into(),
+                    45.into(),
+                    0xFFFFFF.into(),
+                    1.into(),
+                    0.into(),
+                    1.into(),
+                    4.into(),
+                    4.into(),
+                    200.into(),
+                ],
+            )?;
+        let filters = ArrayObject::new(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().array,
+            [bevel_filter],
+        );
+        object.set("filters", filters.into(), activation)?;
+        let filter = object
+            .get("filters", activation)?
+            .coerce_to_object(activation)
+            .get_element(activation, 0)
+            .coerce_to_object(activation);
+        assert_eq!(filter.get("strength", activation)?, 200.into());
+
+        // A quality too high to store is clamped.
+        let blur_filter = activation
+            .context
+            .avm1
+            .prototypes()
+            .blur_filter_constructor
+            .construct(activation, &[5.into(), 7.into(), 100.into()])?;
+        let filters = ArrayObject::new(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().array,
+            [blur_filter],
+        );
+        object.set("filters", filters.into(), activation)?;
+        assert!(matches!(
+            &clip.bitmap_filters()[..],
+            [Filter::BlurFilter(filter)] if filter.num_passes == 15
+        ));
+
+        // An empty array clears the filters.
+        let filters = ArrayObject::empty(activation);
+        object.set("filters", filters.into(), activation)?;
+        assert!(clip.bitmap_filters().is_empty());
+        let filters = object
+            .get("filters", activation)?
+            .coerce_to_object(activation);
+        assert_eq!(filters.length(activation)?, 0);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn create_text_field_default_format() {
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        this.call_method(
+            "createTextField".into(),
+            &[
+                "field".into(),
+                1.into(),
+                0.into(),
+                0.into(),
+                100.into(),
+                100.into(),
+            ],
+            activation,
+            ExecutionReason::Special,
+        )?;
+        let field = this.get("field", activation)?.coerce_to_object(activation);
+        let edit_text = field
+            .as_display_object()
+            .and_then(|field| field.as_edit_text())
+            .expect("text field");
+
+        // New text is 12px opaque black Times New Roman.
+        let default_format = edit_text.new_text_format();
+        assert_eq!(
+            default_format.font,
+            Some(crate::string::WString::from_utf8("Times New Roman"))
+        );
+        assert_eq!(default_format.size, Some(12.0));
+        assert_eq!(default_format.color, Some(swf::Color::BLACK));
+
+        // Text set afterwards takes on that format, so it's visible.
+        field.set("text", "hello".into(), activation)?;
+        let text_format = edit_text.text_format(0, 5);
+        assert_eq!(text_format.size, Some(12.0));
+        assert_eq!(text_format.color, Some(swf::Color::BLACK));
+
+        let new_text_format = field
+            .call_method(
+                "getNewTextFormat".into(),
+                &[],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+        assert_eq!(new_text_format.get("color", activation)?, 0.into());
+        assert_eq!(new_text_format.get("size", activation)?, 12.into());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn movie_clip_sound_transform() {
+    use crate::avm1::ScriptObject;
+
+    with_avm(8, |activation, this| -> Result<(), Error> {
+        let clip = create_clip(activation, this, "clip", 1)?;
+        let clip_object = clip.object().coerce_to_object(activation);
+
+        let transform = ScriptObject::new(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes().object),
+        );
+        transform.set("volume", 50.into(), activation)?;
+        transform.set("rr", 25.into(), activation)?;
+        clip_object.call_method(
+            "setSoundTransform".into(),
+            &[Value::Object(transform.into())],
+            activation,
+            ExecutionReason::Special,
+        )?;
+
+        // Properties missing from the object are left untouched.
+        let sound_transform = clip.sound_transform();
+        assert_eq!(sound_transform.volume, 50);
+        assert_eq!(sound_transform.left_to_left, 100);
+        assert_eq!(sound_transform.right_to_right, 25);
+
+        let result = clip_object
+            .call_method(
+                "getSoundTransform".into(),
+                &[],
+                activation,
+                ExecutionReason::Special,
+            )?
+            .coerce_to_object(activation);
+        assert_eq!(result.get("volume", activation)?, 50.into());
+        assert_eq!(result.get("ll", activation)?, 100.into());
+        assert_eq!(result.get("rr", activation)?, 25.into());
+        assert_eq!(result.get("pan", activation)?, (-75).into());
+
+        Ok(())
+    });
+}
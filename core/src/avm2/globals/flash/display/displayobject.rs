@@ -202,6 +202,9 @@ pub fn set_width<'gc>(
 
         if new_width >= 0.0 {
             dobj.set_width(activation.context.gc_context, new_width);
+            if let Some(edit_text) = dobj.as_edit_text() {
+                edit_text.relayout_if_dirty(&mut activation.context);
+            }
         }
     }
 
@@ -319,12 +319,8 @@ pub fn get_child_index<'gc>(
                 .as_object()
                 .and_then(|o| o.as_display_object());
 
-            if let Some(target_child) = target_child {
-                for (i, child) in ctr.iter_render_list().enumerate() {
-                    if DisplayObject::ptr_eq(child, target_child) {
-                        return Ok(i.into());
-                    }
-                }
+            if let Some(index) = target_child.and_then(|child| ctr.child_index(child)) {
+                return Ok(index.into());
             }
         }
     }
@@ -477,7 +477,9 @@ pub fn text<'gc>(
         .and_then(|this| this.as_display_object())
         .and_then(|this| this.as_edit_text())
     {
-        return Ok(AvmString::new(activation.context.gc_context, this.text()).into());
+        return Ok(
+            AvmString::new(activation.context.gc_context, this.text_with_line_ending()).into(),
+        );
     }
 
     Ok(Value::Undefined)
@@ -1069,7 +1071,7 @@ pub fn set_sharpness<'gc>(
 }
 
 pub fn num_lines<'gc>(
-    _activation: &mut Activation<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc, '_>,
     this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
@@ -1077,7 +1079,7 @@ pub fn num_lines<'gc>(
         .and_then(|this| this.as_display_object())
         .and_then(|this| this.as_edit_text())
     {
-        return Ok(this.layout_lines().into());
+        return Ok(this.num_lines(&mut activation.context).into());
     }
 
     Ok(Value::Undefined)
@@ -1122,6 +1124,73 @@ pub fn get_line_metrics<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn get_line_text<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|this| this.as_edit_text())
+    {
+        let line_num = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_i32(activation)?;
+        let text = usize::try_from(line_num)
+            .ok()
+            .and_then(|line_num| this.line_text(&mut activation.context, line_num));
+
+        if let Some(text) = text {
+            return Ok(AvmString::new(activation.context.gc_context, text).into());
+        } else {
+            return Err("RangeError: The supplied index is out of bounds.".into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn get_char_boundaries<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|this| this.as_edit_text())
+    {
+        let char_index = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_i32(activation)?;
+        let bounds = usize::try_from(char_index)
+            .ok()
+            .and_then(|char_index| this.char_boundaries(&mut activation.context, char_index));
+
+        if let Some(bounds) = bounds {
+            let rectangle_class = activation.avm2().classes().rectangle;
+            return Ok(rectangle_class
+                .construct(
+                    activation,
+                    &[
+                        bounds.x_min.to_pixels().into(),
+                        bounds.y_min.to_pixels().into(),
+                        (bounds.x_max - bounds.x_min).to_pixels().into(),
+                        (bounds.y_max - bounds.y_min).to_pixels().into(),
+                    ],
+                )?
+                .into());
+        } else {
+            return Ok(Value::Null);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn bottom_scroll_v<'gc>(
     _activation: &mut Activation<'_, 'gc, '_>,
     this: Option<Object<'gc>>,
@@ -1318,6 +1387,8 @@ pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>
         ("setSelection", set_selection),
         ("setTextFormat", set_text_format),
         ("getLineMetrics", get_line_metrics),
+        ("getLineText", get_line_text),
+        ("getCharBoundaries", get_char_boundaries),
     ];
     write.define_public_builtin_instance_methods(mc, PUBLIC_INSTANCE_METHODS);
 
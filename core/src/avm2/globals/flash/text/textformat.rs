@@ -251,7 +251,7 @@ fn color<'gc>(
     Ok(text_format
         .color
         .as_ref()
-        .map_or(Value::Null, |color| (color.to_rgba() as i32).into()))
+        .map_or(Value::Null, |color| (color.to_rgb() as i32).into()))
 }
 
 fn set_color<'gc>(
@@ -261,7 +261,7 @@ fn set_color<'gc>(
 ) -> Result<(), Error<'gc>> {
     text_format.color = match value {
         Value::Undefined | Value::Null => None,
-        value => Some(swf::Color::from_rgba(value.coerce_to_u32(activation)?)),
+        value => Some(swf::Color::from_rgb(value.coerce_to_u32(activation)?, 255)),
     };
     Ok(())
 }
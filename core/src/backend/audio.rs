@@ -707,3 +707,232 @@ impl Default for SoundTransform {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{Player, PlayerBuilder};
+    use crate::tag_utils::SwfMovie;
+
+    /// Audio backend that records the calls made to it by the audio manager.
+    #[derive(Default)]
+    struct RecordingAudioBackend {
+        playing: Arena<()>,
+        transforms: Vec<(SoundInstanceHandle, SoundTransform)>,
+        stop_all_count: usize,
+    }
+
+    impl AudioBackend for RecordingAudioBackend {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn register_sound(&mut self, _sound: &swf::Sound) -> Result<SoundHandle, RegisterError> {
+            Ok(SoundHandle::from_raw_parts(0, 0))
+        }
+        fn register_mp3(&mut self, _data: &[u8]) -> Result<SoundHandle, DecodeError> {
+            Ok(SoundHandle::from_raw_parts(0, 0))
+        }
+        fn start_sound(
+            &mut self,
+            _sound: SoundHandle,
+            _settings: &swf::SoundInfo,
+        ) -> Result<SoundInstanceHandle, DecodeError> {
+            Ok(self.playing.insert(()))
+        }
+        fn start_stream(
+            &mut self,
+            _stream_handle: Option<SoundHandle>,
+            _clip_frame: u16,
+            _clip_data: crate::tag_utils::SwfSlice,
+            _handle: &swf::SoundStreamHead,
+        ) -> Result<SoundInstanceHandle, DecodeError> {
+            Ok(self.playing.insert(()))
+        }
+        fn stop_sound(&mut self, sound: SoundInstanceHandle) {
+            self.playing.remove(sound);
+        }
+        fn stop_all_sounds(&mut self) {
+            self.playing.clear();
+            self.stop_all_count += 1;
+        }
+        fn get_sound_position(&self, instance: SoundInstanceHandle) -> Option<f64> {
+            self.playing.get(instance).map(|_| 0.0)
+        }
+        fn get_sound_duration(&self, _sound: SoundHandle) -> Option<f64> {
+            None
+        }
+        fn get_sound_size(&self, _sound: SoundHandle) -> Option<u32> {
+            None
+        }
+        fn get_sound_format(&self, _sound: SoundHandle) -> Option<&swf::SoundFormat> {
+            None
+        }
+        fn set_sound_transform(
+            &mut self,
+            instance: SoundInstanceHandle,
+            transform: SoundTransform,
+        ) {
+            self.transforms.push((instance, transform));
+        }
+        fn get_sound_peak(&mut self, _instance: SoundInstanceHandle) -> Option<[f32; 2]> {
+            None
+        }
+        fn volume(&self) -> f32 {
+            1.0
+        }
+        fn set_volume(&mut self, _volume: f32) {}
+        fn get_sample_history(&self) -> [[f32; 2]; 1024] {
+            [[0.0; 2]; 1024]
+        }
+    }
+
+    fn recording_player() -> std::sync::Arc<std::sync::Mutex<Player>> {
+        PlayerBuilder::new()
+            .with_audio(RecordingAudioBackend::default())
+            .with_movie(SwfMovie::empty(8))
+            .build()
+    }
+
+    fn recording_backend(player: &mut Player) -> &mut RecordingAudioBackend {
+        player
+            .audio_mut()
+            .downcast_mut::<RecordingAudioBackend>()
+            .unwrap()
+    }
+
+    fn sound_info() -> swf::SoundInfo {
+        swf::SoundInfo {
+            event: swf::SoundEvent::Event,
+            in_sample: None,
+            out_sample: None,
+            num_loops: 1,
+            envelope: None,
+        }
+    }
+
+    #[test]
+    fn display_object_sound_transform_updates_backend() {
+        let player = recording_player();
+        let mut player = player.lock().unwrap();
+
+        let instance = player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let sound = context.audio.register_mp3(&[]).unwrap();
+            context
+                .start_sound(sound, &sound_info(), Some(root), None)
+                .unwrap()
+        });
+        recording_backend(&mut player).transforms.clear();
+
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let sound_transform = display_object::SoundTransform {
+                volume: 50,
+                left_to_right: 100,
+                ..Default::default()
+            };
+            root.set_sound_transform(context, sound_transform.clone());
+            assert_eq!(root.sound_transform(), sound_transform);
+
+            context.update_sounds();
+        });
+
+        assert_eq!(
+            recording_backend(&mut player).transforms,
+            vec![(
+                instance,
+                SoundTransform {
+                    left_to_left: 0.5,
+                    left_to_right: 0.5,
+                    right_to_left: 0.0,
+                    right_to_right: 0.5,
+                }
+            )]
+        );
+
+        // Transforms are only sent to the backend when they change.
+        recording_backend(&mut player).transforms.clear();
+        player.mutate_with_update_context(|context| context.update_sounds());
+        assert!(recording_backend(&mut player).transforms.is_empty());
+    }
+
+    #[test]
+    fn stop_all_sounds_stops_backend() {
+        let player = recording_player();
+        let mut player = player.lock().unwrap();
+
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let sound = context.audio.register_mp3(&[]).unwrap();
+            let first = context
+                .start_sound(sound, &sound_info(), Some(root), None)
+                .unwrap();
+            let second = context
+                .start_sound(sound, &sound_info(), None, None)
+                .unwrap();
+            assert!(context.is_sound_playing(first));
+            assert!(context.is_sound_playing(second));
+
+            context.stop_all_sounds();
+
+            assert!(!context.is_sound_playing(first));
+            assert!(!context.is_sound_playing(second));
+        });
+
+        let backend = recording_backend(&mut player);
+        assert_eq!(backend.stop_all_count, 1);
+        assert!(backend.playing.is_empty());
+    }
+
+    #[test]
+    fn avm1_sound_stop_stops_all_sounds() {
+        use crate::avm1::{Activation, ActivationIdentifier, ExecutionReason, TObject};
+
+        let player = recording_player();
+        let mut player = player.lock().unwrap();
+
+        player.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip();
+            let sound = context.audio.register_mp3(&[]).unwrap();
+            let first = context
+                .start_sound(sound, &sound_info(), Some(root), None)
+                .unwrap();
+            let second = context
+                .start_sound(sound, &sound_info(), None, None)
+                .unwrap();
+
+            let mut activation = Activation::from_nothing(
+                context.reborrow(),
+                ActivationIdentifier::root("[Test]"),
+                root,
+            );
+
+            // `Sound.stop` on a sound without an owner acts like `stopAllSounds()`.
+            let constructor = activation
+                .context
+                .avm1
+                .global_object()
+                .get("Sound", &mut activation)
+                .unwrap()
+                .coerce_to_object(&mut activation);
+            let sound_object = constructor
+                .construct(&mut activation, &[])
+                .unwrap()
+                .coerce_to_object(&mut activation);
+            sound_object
+                .call_method(
+                    "stop".into(),
+                    &[],
+                    &mut activation,
+                    ExecutionReason::Special,
+                )
+                .unwrap();
+
+            assert!(!activation.context.is_sound_playing(first));
+            assert!(!activation.context.is_sound_playing(second));
+        });
+
+        let backend = recording_backend(&mut player);
+        assert_eq!(backend.stop_all_count, 1);
+        assert!(backend.playing.is_empty());
+    }
+}
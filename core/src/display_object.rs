@@ -8,7 +8,7 @@ use crate::context::{RenderContext, UpdateContext};
 use crate::drawing::Drawing;
 use crate::player::NEWEST_PLAYER_VERSION;
 use crate::prelude::*;
-use crate::string::{AvmString, WString};
+use crate::string::{AvmString, WStr, WString};
 use crate::tag_utils::SwfMovie;
 use crate::types::{Degrees, Percent};
 use crate::vminterface::Instantiator;
@@ -19,7 +19,7 @@ use ruffle_render::transform::Transform;
 use std::cell::{Ref, RefMut};
 use std::fmt::Debug;
 use std::sync::Arc;
-use swf::{BlendMode, Fixed8, Rectangle};
+use swf::{BlendMode, Filter, Fixed8, Rectangle};
 
 mod avm1_button;
 mod avm2_button;
@@ -51,6 +51,67 @@ pub use stage::{Stage, StageAlign, StageDisplayState, StageQuality, StageScaleMo
 pub use text::Text;
 pub use video::Video;
 
+/// A transform matrix decomposed into the components exposed to ActionScript.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecomposedTransform {
+    /// The X translation in pixels, as returned by `_x`.
+    pub tx: f64,
+
+    /// The Y translation in pixels, as returned by `_y`.
+    pub ty: f64,
+
+    /// The length of the transformed X axis, as returned by `_xscale`.
+    pub scale_x: Percent,
+
+    /// The length of the transformed Y axis, as returned by `_yscale`.
+    pub scale_y: Percent,
+
+    /// The rotation of the transformed X axis, as returned by `_rotation`.
+    pub rotation: Degrees,
+
+    /// The rotation of the transformed Y axis relative to the X axis, in
+    /// radians. This is not exposed by ActionScript.
+    pub skew: f64,
+}
+
+impl DecomposedTransform {
+    /// Decompose a transform matrix.
+    pub fn from_matrix(matrix: &Matrix) -> Self {
+        let (a, b, c, d) = (
+            f64::from(matrix.a),
+            f64::from(matrix.b),
+            f64::from(matrix.c),
+            f64::from(matrix.d),
+        );
+        // If this object's transform matrix is:
+        // [[a c tx]
+        //  [b d ty]]
+        // After transformation, the X-axis and Y-axis will turn into the column vectors x' = <a, b> and y' = <c, d>.
+        // We derive the scale, rotation, and skew values from these transformed axes.
+        // The skew value is not exposed by ActionScript, but is remembered internally.
+        // xscale = len(x')
+        // yscale = len(y')
+        // rotation = atan2(b, a)  (the rotation of x' from the normal x-axis).
+        // skew = atan2(-c, d) - atan2(b, a)  (the signed difference between y' and x' rotation)
+
+        // This can produce some surprising results due to the overlap between flipping/rotation/skewing.
+        // For example, in Flash, using Modify->Transform->Flip Horizontal and then tracing _xscale, _yscale, and _rotation
+        // will output 100, 100, and 180. (a horizontal flip could also be a 180 degree skew followed by 180 degree rotation!)
+        let rotation_x = f64::atan2(b, a);
+        let rotation_y = f64::atan2(-c, d);
+        let scale_x = f64::sqrt(a * a + b * b);
+        let scale_y = f64::sqrt(c * c + d * d);
+        Self {
+            tx: matrix.tx.to_pixels(),
+            ty: matrix.ty.to_pixels(),
+            scale_x: Percent::from_unit(scale_x),
+            scale_y: Percent::from_unit(scale_y),
+            rotation: Degrees::from_radians(rotation_x),
+            skew: rotation_y - rotation_x,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Collect)]
 #[collect(no_drop)]
 pub struct DisplayObjectBase<'gc> {
@@ -101,6 +162,11 @@ pub struct DisplayObjectBase<'gc> {
     #[collect(require_static)]
     opaque_background: Option<Color>,
 
+    /// The bitmap filters of this display object, such as blurs and drop shadows.
+    /// This is set by AVM1 `filters` and PlaceObject tags, but isn't rendered yet.
+    #[collect(require_static)]
+    bitmap_filters: Vec<Filter>,
+
     /// Bit flags for various display object properties.
     flags: DisplayObjectFlags,
 
@@ -114,6 +180,12 @@ pub struct DisplayObjectBase<'gc> {
     /// changes immediately (without needing wait for a render)
     #[collect(require_static)]
     next_scroll_rect: Rectangle<Twips>,
+
+    /// The position of this display object in the keyboard tab order, if set.
+    tab_index: Option<i32>,
+
+    /// Whether this display object is a keyboard tab stop, if set.
+    tab_enabled: Option<bool>,
 }
 
 impl<'gc> Default for DisplayObjectBase<'gc> {
@@ -136,9 +208,12 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             sound_transform: Default::default(),
             blend_mode: Default::default(),
             opaque_background: Default::default(),
+            bitmap_filters: Vec::new(),
             flags: DisplayObjectFlags::VISIBLE,
             scroll_rect: None,
             next_scroll_rect: Default::default(),
+            tab_index: None,
+            tab_enabled: None,
         }
     }
 }
@@ -218,34 +293,23 @@ impl<'gc> DisplayObjectBase<'gc> {
     /// `_rotation` is accessed.
     fn cache_scale_rotation(&mut self) {
         if !self.scale_rotation_cached() {
-            let (a, b, c, d) = (
-                f64::from(self.transform.matrix.a),
-                f64::from(self.transform.matrix.b),
-                f64::from(self.transform.matrix.c),
-                f64::from(self.transform.matrix.d),
-            );
-            // If this object's transform matrix is:
-            // [[a c tx]
-            //  [b d ty]]
-            // After transformation, the X-axis and Y-axis will turn into the column vectors x' = <a, b> and y' = <c, d>.
-            // We derive the scale, rotation, and skew values from these transformed axes.
-            // The skew value is not exposed by ActionScript, but is remembered internally.
-            // xscale = len(x')
-            // yscale = len(y')
-            // rotation = atan2(b, a)  (the rotation of x' from the normal x-axis).
-            // skew = atan2(-c, d) - atan2(b, a)  (the signed difference between y' and x' rotation)
-
-            // This can produce some surprising results due to the overlap between flipping/rotation/skewing.
-            // For example, in Flash, using Modify->Transform->Flip Horizontal and then tracing _xscale, _yscale, and _rotation
-            // will output 100, 100, and 180. (a horizontal flip could also be a 180 degree skew followed by 180 degree rotation!)
-            let rotation_x = f64::atan2(b, a);
-            let rotation_y = f64::atan2(-c, d);
-            let scale_x = f64::sqrt(a * a + b * b);
-            let scale_y = f64::sqrt(c * c + d * d);
-            self.rotation = Degrees::from_radians(rotation_x);
-            self.scale_x = Percent::from_unit(scale_x);
-            self.scale_y = Percent::from_unit(scale_y);
-            self.skew = rotation_y - rotation_x;
+            let decomposed = DecomposedTransform::from_matrix(&self.transform.matrix);
+            self.rotation = decomposed.rotation;
+            self.scale_x = decomposed.scale_x;
+            self.scale_y = decomposed.scale_y;
+            self.skew = decomposed.skew;
+        }
+    }
+
+    fn decomposed_transform(&mut self) -> DecomposedTransform {
+        self.cache_scale_rotation();
+        DecomposedTransform {
+            tx: self.x(),
+            ty: self.y(),
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            rotation: self.rotation,
+            skew: self.skew,
         }
     }
 
@@ -418,6 +482,30 @@ impl<'gc> DisplayObjectBase<'gc> {
         });
     }
 
+    fn bitmap_filters(&self) -> &[Filter] {
+        &self.bitmap_filters
+    }
+
+    fn set_bitmap_filters(&mut self, filters: Vec<Filter>) {
+        self.bitmap_filters = filters;
+    }
+
+    fn tab_index(&self) -> Option<i32> {
+        self.tab_index
+    }
+
+    fn set_tab_index(&mut self, value: Option<i32>) {
+        self.tab_index = value;
+    }
+
+    fn tab_enabled(&self) -> Option<bool> {
+        self.tab_enabled
+    }
+
+    fn set_tab_enabled(&mut self, value: Option<bool>) {
+        self.tab_enabled = value;
+    }
+
     fn is_root(&self) -> bool {
         self.flags.contains(DisplayObjectFlags::IS_ROOT)
     }
@@ -560,6 +648,24 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.commands.activate_mask();
     }
 
+    if let Some(color) = this.opaque_background() {
+        // The background fills the bounding box of the object, behind all of its content.
+        let bounds = this.bounds();
+        if bounds.valid {
+            let matrix = context.transform_stack.transform().matrix
+                * Matrix::create_box(
+                    bounds.width().to_pixels() as f32,
+                    bounds.height().to_pixels() as f32,
+                    0.0,
+                    bounds.x_min,
+                    bounds.y_min,
+                );
+            context.commands.draw_rect(color, &matrix);
+        }
+    }
+
+    // Filters are only stored for scripts to read back; the renderer has no filter
+    // support, so `this.bitmap_filters()` isn't applied here.
     this.render_self(context);
 
     if let Some(rect_mat) = scroll_rect_matrix {
@@ -640,6 +746,14 @@ pub trait TDisplayObject<'gc>:
     /// Composite DisplayObjects that only contain children should return `&Default::default()`
     fn self_bounds(&self) -> BoundingBox;
 
+    /// The untransformed inherent bounding box of this object, excluding the
+    /// width of any strokes.
+    ///
+    /// Only objects that can have strokes need to override this.
+    fn self_edge_bounds(&self) -> BoundingBox {
+        self.self_bounds()
+    }
+
     /// The untransformed bounding box of this object including children.
     fn bounds(&self) -> BoundingBox {
         self.bounds_with_transform(&Matrix::default())
@@ -685,6 +799,25 @@ pub trait TDisplayObject<'gc>:
         bounds
     }
 
+    /// Like `bounds_with_transform`, but excluding the width of any strokes.
+    fn edge_bounds_with_transform(&self, matrix: &Matrix) -> BoundingBox {
+        // A scroll rect overrides the bounds of strokes and fills alike.
+        if self.scroll_rect().is_some() {
+            return self.bounds_with_transform(matrix);
+        }
+
+        let mut bounds = self.self_edge_bounds().transform(matrix);
+
+        if let Some(ctr) = self.as_container() {
+            for child in ctr.iter_render_list() {
+                let matrix = *matrix * *child.base().matrix();
+                bounds.union(&child.edge_bounds_with_transform(&matrix));
+            }
+        }
+
+        bounds
+    }
+
     fn place_frame(&self) -> u16 {
         self.base().place_frame()
     }
@@ -823,6 +956,17 @@ pub trait TDisplayObject<'gc>:
         self.set_scale_rotation_cached(gc_context);
     }
 
+    /// The transform of this display object in local space, decomposed into
+    /// translation, scale, rotation and skew.
+    ///
+    /// The components match those returned by the `_x`, `_y`, `_xscale`,
+    /// `_yscale` and `_rotation` ActionScript properties.
+    fn decomposed_transform(&self, gc_context: MutationContext<'gc, '_>) -> DecomposedTransform {
+        let transform = self.base_mut(gc_context).decomposed_transform();
+        self.set_scale_rotation_cached(gc_context);
+        transform
+    }
+
     /// Gets the pixel width of the AABB containing this display object in local space.
     /// Returned by the ActionScript `_width`/`width` properties.
     fn width(&self) -> f64 {
@@ -988,6 +1132,34 @@ pub trait TDisplayObject<'gc>:
         }
     }
 
+    /// Resolves a Flash 4 slash-syntax path relative to this display object,
+    /// e.g. `../sibling/child`. This is the inverse of `slash_path`.
+    ///
+    /// A leading `/` starts from the AVM1 root, `..` steps to the parent, and
+    /// any other segment names a child. Returns `None` if any segment of the
+    /// path does not exist.
+    fn resolve_slash_path(&self, path: &WStr, case_sensitive: bool) -> Option<DisplayObject<'gc>> {
+        let mut object: DisplayObject<'gc> = if path.starts_with(b'/') {
+            self.avm1_root()
+        } else {
+            (*self).into()
+        };
+
+        for segment in path.split(b'/') {
+            object = if segment.is_empty() {
+                continue;
+            } else if segment == b".." {
+                object.avm1_parent()?
+            } else {
+                object
+                    .as_container()?
+                    .child_by_name(segment, case_sensitive)?
+            };
+        }
+
+        Some(object)
+    }
+
     fn clip_depth(&self) -> Depth {
         self.base().clip_depth()
     }
@@ -1130,6 +1302,47 @@ pub trait TDisplayObject<'gc>:
         self.base_mut(gc_context).set_opaque_background(value);
     }
 
+    /// The bitmap filters of this display object. These are stored, but not rendered.
+    fn bitmap_filters(&self) -> Vec<Filter> {
+        self.base().bitmap_filters().to_vec()
+    }
+
+    /// Sets the bitmap filters of this display object.
+    /// An empty list clears all filters.
+    fn set_bitmap_filters(&self, gc_context: MutationContext<'gc, '_>, filters: Vec<Filter>) {
+        self.base_mut(gc_context).set_bitmap_filters(filters);
+    }
+
+    /// The position of this display object in the keyboard tab order.
+    ///
+    /// `None` if no index has been set, in which case the object only takes
+    /// part in the automatic tab order.
+    fn tab_index(&self) -> Option<i32> {
+        self.base().tab_index()
+    }
+
+    /// Sets the position of this display object in the keyboard tab order.
+    fn set_tab_index(&self, gc_context: MutationContext<'gc, '_>, value: Option<i32>) {
+        self.base_mut(gc_context).set_tab_index(value);
+    }
+
+    /// Whether this display object was explicitly included in or excluded
+    /// from the keyboard tab order.
+    fn tab_enabled(&self) -> Option<bool> {
+        self.base().tab_enabled()
+    }
+
+    /// Sets whether this display object is included in the keyboard tab order.
+    /// `None` restores the default, which includes all focusable objects.
+    fn set_tab_enabled(&self, gc_context: MutationContext<'gc, '_>, value: Option<bool>) {
+        self.base_mut(gc_context).set_tab_enabled(value);
+    }
+
+    /// Whether Tab may move the focus to this display object.
+    fn is_tab_stop(&self) -> bool {
+        self.visible() && self.tab_enabled().unwrap_or_else(|| self.is_focusable())
+    }
+
     /// Whether this display object represents the root of loaded content.
     fn is_root(&self) -> bool {
         self.base().is_root()
@@ -1140,6 +1353,11 @@ pub trait TDisplayObject<'gc>:
         self.base_mut(gc_context).set_is_root(value);
     }
 
+    /// The sound transform for sounds played inside this display object.
+    fn sound_transform(&self) -> SoundTransform {
+        self.base().sound_transform().clone()
+    }
+
     /// The sound transform for sounds played inside this display object.
     fn set_sound_transform(
         &self,
@@ -1468,11 +1686,13 @@ pub trait TDisplayObject<'gc>:
                     self.set_opaque_background(context.gc_context, color);
                 }
             }
+            if let Some(filters) = &place_object.filters {
+                self.set_bitmap_filters(context.gc_context, filters.clone());
+            }
             // Purposely omitted properties:
             // name, clip_depth, clip_actions
             // These properties are only set on initial placement in `MovieClip::instantiate_child`
             // and can not be modified by subsequent PlaceObject tags.
-            // TODO: Filters need to be applied here.
         }
     }
 
@@ -170,6 +170,24 @@ pub trait TDisplayObjectContainer<'gc>:
         self.raw_container().get_name(name, case_sensitive)
     }
 
+    /// Get the position of a child display object in the render list.
+    ///
+    /// Yields `None` if the given display object is not a child of this
+    /// container.
+    fn child_index(self, child: DisplayObject<'gc>) -> Option<usize> {
+        self.raw_container()
+            .iter_render_list()
+            .position(|other| DisplayObject::ptr_eq(other, child))
+    }
+
+    /// Returns all children on the depth list, paired with their depths, in
+    /// ascending depth order.
+    ///
+    /// Children not on the depth list will not be returned.
+    fn depth_list(self) -> Vec<(Depth, DisplayObject<'gc>)> {
+        self.raw_container().iter_depth_list().collect()
+    }
+
     /// Returns the number of children on the render list.
     fn num_children(self) -> usize {
         self.raw_container().num_children()
@@ -781,6 +799,13 @@ impl<'gc> ChildContainer<'gc> {
     fn iter_render_list<'a>(&'a self) -> impl 'a + Iterator<Item = DisplayObject<'gc>> {
         self.render_list.iter().copied()
     }
+
+    /// Yield children on the depth list, in ascending depth order.
+    fn iter_depth_list<'a>(&'a self) -> impl 'a + Iterator<Item = (Depth, DisplayObject<'gc>)> {
+        self.depth_list
+            .iter()
+            .map(|(depth, child)| (*depth, *child))
+    }
 }
 
 pub struct RenderIter<'gc> {
@@ -30,7 +30,7 @@ use ruffle_render::commands::CommandHandler;
 use ruffle_render::shape_utils::DrawCommand;
 use ruffle_render::transform::Transform;
 use std::{cell::Ref, cell::RefMut, sync::Arc};
-use swf::{Color, Twips};
+use swf::{Color, Rectangle, Twips};
 
 /// The kind of autosizing behavior an `EditText` should have, if any
 #[derive(Copy, Clone, Debug, Collect, PartialEq, Eq)]
@@ -188,11 +188,12 @@ impl<'gc> EditText<'gc> {
         let encoding = swf_movie.encoding();
 
         let text = WString::from_utf8(&text.to_str_lossy(encoding));
-        let mut text_spans = if swf_tag.is_html() {
-            FormatSpans::from_html(&text, default_format, swf_tag.is_multiline())
-        } else {
-            FormatSpans::from_text(text, default_format)
-        };
+        let mut text_spans = FormatSpans::from_source(
+            &text,
+            default_format,
+            swf_tag.is_html(),
+            swf_tag.is_multiline(),
+        );
 
         if swf_tag.is_password() {
             text_spans.hide_text();
@@ -206,14 +207,11 @@ impl<'gc> EditText<'gc> {
 
         let bounds: BoundingBox = swf_tag.bounds().into();
 
-        let (layout, intrinsic_bounds) = LayoutBox::lower_from_text_spans(
-            &text_spans,
-            context,
-            swf_movie.clone(),
-            bounds.width() - Twips::from_pixels(Self::INTERNAL_PADDING * 2.0),
-            swf_tag.is_word_wrap(),
-            !swf_tag.use_outlines(),
-        );
+        let width = bounds.width() - Twips::from_pixels(Self::INTERNAL_PADDING * 2.0);
+        let is_device_font = !swf_tag.use_outlines();
+        text_spans.set_wrap_width(swf_tag.is_word_wrap().then_some(width));
+        let (layout, intrinsic_bounds) =
+            text_spans.layout(context, swf_movie.clone(), width, is_device_font);
         let line_data = get_line_data(&layout);
 
         let mut base = InteractiveObjectBase::default();
@@ -319,6 +317,12 @@ impl<'gc> EditText<'gc> {
         self.0.read().text_spans.text().into()
     }
 
+    /// The text as given to scripts, with its line breaks in the convention
+    /// it was set in.
+    pub fn text_with_line_ending(self) -> WString {
+        self.0.read().text_spans.text_with_line_ending()
+    }
+
     pub fn set_text(self, text: &WStr, context: &mut UpdateContext<'_, 'gc, '_>) {
         let mut edit_text = self.0.write(context.gc_context);
         let default_format = edit_text.text_spans.default_format().clone();
@@ -338,20 +342,18 @@ impl<'gc> EditText<'gc> {
     }
 
     pub fn set_html_text(self, text: &WStr, context: &mut UpdateContext<'_, 'gc, '_>) {
-        if self.is_html() {
-            let mut write = self.0.write(context.gc_context);
-            let default_format = write.text_spans.default_format().clone();
-            write.text_spans = FormatSpans::from_html(
-                text,
-                default_format,
-                write.flags.contains(EditTextFlag::MULTILINE),
-            );
-            drop(write);
+        // Non-HTML text fields take the text literally.
+        let mut write = self.0.write(context.gc_context);
+        let default_format = write.text_spans.default_format().clone();
+        write.text_spans = FormatSpans::from_source(
+            text,
+            default_format,
+            write.flags.contains(EditTextFlag::HTML),
+            write.flags.contains(EditTextFlag::MULTILINE),
+        );
+        drop(write);
 
-            self.relayout(context);
-        } else {
-            self.set_text(text, context);
-        }
+        self.relayout(context);
     }
 
     pub fn text_length(self) -> usize {
@@ -529,18 +531,39 @@ impl<'gc> EditText<'gc> {
             .set(EditTextFlag::HTML, is_html);
     }
 
+    /// Replace the text in the range [from, to) and relayout the field.
+    ///
+    /// Returns the position immediately after the inserted text.
     pub fn replace_text(
         self,
         from: usize,
         to: usize,
         text: &WStr,
         context: &mut UpdateContext<'_, 'gc, '_>,
-    ) {
-        self.0
+    ) -> usize {
+        self.replace_text_with_format(from, to, text, None, context)
+    }
+
+    /// Replace the text in the range [from, to) with text of the given
+    /// format, and relayout the field.
+    ///
+    /// Without a format, the inserted text inherits one as described in
+    /// `FormatSpans::replace_text`.
+    fn replace_text_with_format(
+        self,
+        from: usize,
+        to: usize,
+        text: &WStr,
+        format: Option<&TextFormat>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> usize {
+        let caret = self
+            .0
             .write(context.gc_context)
             .text_spans
-            .replace_text(from, to, text, None);
+            .replace_text(from, to, text, format);
         self.relayout(context);
+        caret
     }
 
     /// Construct a base text transform for a particular `EditText` span.
@@ -699,18 +722,22 @@ impl<'gc> EditText<'gc> {
             edit_text.text_spans.clear_displayed_text();
         }
 
-        let (new_layout, intrinsic_bounds) = LayoutBox::lower_from_text_spans(
-            &edit_text.text_spans,
-            context,
-            movie,
-            edit_text.bounds.width() - padding,
-            is_word_wrap,
-            !edit_text.flags.contains(EditTextFlag::USE_OUTLINES),
-        );
+        let width = edit_text.bounds.width() - padding;
+        let is_device_font = !edit_text.flags.contains(EditTextFlag::USE_OUTLINES);
+        edit_text
+            .text_spans
+            .set_wrap_width(is_word_wrap.then_some(width));
 
-        edit_text.line_data = get_line_data(&new_layout);
-        edit_text.layout = new_layout;
-        edit_text.intrinsic_bounds = intrinsic_bounds;
+        if let Some((new_layout, intrinsic_bounds)) =
+            edit_text
+                .text_spans
+                .layout_if_changed(context, movie, width, is_device_font)
+        {
+            edit_text.line_data = get_line_data(&new_layout);
+            edit_text.layout = new_layout;
+            edit_text.intrinsic_bounds = intrinsic_bounds;
+        }
+        let intrinsic_bounds = edit_text.intrinsic_bounds;
         // reset scroll
         edit_text.hscroll = 0.0;
         edit_text.scroll = 1;
@@ -740,6 +767,14 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    /// Lay out the text again if resizing the text field changed the width
+    /// its text wraps at.
+    pub fn relayout_if_dirty(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        if self.0.read().text_spans.is_layout_dirty() {
+            self.relayout(context);
+        }
+    }
+
     /// Measure the width and height of the `EditText`'s current text load.
     ///
     /// The returned tuple should be interpreted as width, then height.
@@ -1211,13 +1246,15 @@ impl<'gc> EditText<'gc> {
                     }
                 }
                 code if !(code as char).is_control() => {
-                    self.replace_text(
+                    // Typed text takes on the format of the character before it.
+                    let format = self.0.read().text_spans.format_before(selection.start());
+                    let new_start = self.replace_text_with_format(
                         selection.start(),
                         selection.end(),
                         &WString::from_char(character),
+                        Some(&format),
                         context,
                     );
-                    let new_start = selection.start() + character.len_utf8();
                     self.set_selection(
                         Some(TextSelection::for_position(new_start)),
                         context.gc_context,
@@ -1382,9 +1419,67 @@ impl<'gc> EditText<'gc> {
         }
     }
 
-    /// Count the number of lines in the text box's layout.
-    pub fn layout_lines(self) -> usize {
-        self.0.read().line_data.len()
+    /// Retrieve the text of a line in the text box's layout.
+    ///
+    /// Yields `None` if the line does not exist.
+    pub fn line_text(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        line: usize,
+    ) -> Option<WString> {
+        let edit_text = self.0.read();
+        let movie = edit_text.static_data.swf.clone();
+        let is_device_font = !edit_text.flags.contains(EditTextFlag::USE_OUTLINES);
+
+        edit_text
+            .text_spans
+            .line_text(context, movie, is_device_font, line)
+    }
+
+    /// Retrieve the bounds of the character at `index`, relative to the
+    /// origin of the text field.
+    ///
+    /// Yields `None` if the character doesn't exist or isn't rendered.
+    pub fn char_boundaries(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: usize,
+    ) -> Option<Rectangle<Twips>> {
+        let edit_text = self.0.read();
+        let movie = edit_text.static_data.swf.clone();
+        let is_word_wrap = edit_text.flags.contains(EditTextFlag::WORD_WRAP);
+        let is_device_font = !edit_text.flags.contains(EditTextFlag::USE_OUTLINES);
+        let padding = Twips::from_pixels(Self::INTERNAL_PADDING);
+        let width = edit_text.bounds.width() - padding * 2;
+
+        let bounds = edit_text.text_spans.char_boundaries(
+            context,
+            movie,
+            width,
+            is_word_wrap,
+            is_device_font,
+            index,
+        )?;
+
+        // The text is laid out inside the field's padding.
+        Some(Rectangle {
+            x_min: bounds.x_min + padding,
+            x_max: bounds.x_max + padding,
+            y_min: bounds.y_min + padding,
+            y_max: bounds.y_max + padding,
+        })
+    }
+
+    /// Count the number of lines in the text box's layout, as indexed by
+    /// `line_text`.
+    pub fn num_lines(self, context: &mut UpdateContext<'_, 'gc, '_>) -> usize {
+        let edit_text = self.0.read();
+        let movie = edit_text.static_data.swf.clone();
+        let is_device_font = !edit_text.flags.contains(EditTextFlag::USE_OUTLINES);
+
+        edit_text
+            .text_spans
+            .num_lines(context, movie, is_device_font)
     }
 
     /// Calculate the layout metrics for a given line.
@@ -1590,6 +1685,11 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
 
         write.bounds.set_width(Twips::from_pixels(value));
         write.base.base.set_transformed_by_script(true);
+        if write.flags.contains(EditTextFlag::WORD_WRAP) {
+            let padding = Twips::from_pixels(EditText::INTERNAL_PADDING) * 2;
+            let wrap_width = write.bounds.width() - padding;
+            write.text_spans.set_wrap_width(Some(wrap_width));
+        }
 
         drop(write);
         self.redraw_border(gc_context);
@@ -38,6 +38,7 @@ impl<'gc> Graphic<'gc> {
         let static_data = GraphicStatic {
             id: swf_shape.id,
             bounds: (&swf_shape.shape_bounds).into(),
+            edge_bounds: (&swf_shape.edge_bounds).into(),
             render_handle: Some(
                 context
                     .renderer
@@ -66,6 +67,7 @@ impl<'gc> Graphic<'gc> {
         let static_data = GraphicStatic {
             id: 0,
             bounds: Default::default(),
+            edge_bounds: Default::default(),
             render_handle: None,
             shape: swf::Shape {
                 version: 32,
@@ -130,6 +132,14 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
         }
     }
 
+    fn self_edge_bounds(&self) -> BoundingBox {
+        if let Some(drawing) = &self.0.read().drawing {
+            drawing.self_edge_bounds()
+        } else {
+            self.0.read().static_data.edge_bounds.clone()
+        }
+    }
+
     fn construct_frame(&self, context: &mut UpdateContext<'_, 'gc, '_>) {
         if context.is_action_script_3() && matches!(self.object2(), Avm2Value::Undefined) {
             let shape_constr = context.avm2.classes().shape;
@@ -254,5 +264,6 @@ struct GraphicStatic {
     shape: swf::Shape,
     render_handle: Option<ShapeHandle>,
     bounds: BoundingBox,
+    edge_bounds: BoundingBox,
     movie: Option<Arc<SwfMovie>>,
 }
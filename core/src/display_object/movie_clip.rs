@@ -379,11 +379,18 @@ impl<'gc> MovieClip<'gc> {
         // Should be able to hoist this up somewhere, or use MaybeUninit.
         let mut static_data = (&*self.0.read().static_data).clone();
         let data = self.0.read().static_data.swf.clone();
-        let (mut cur_frame, mut start_pos, next_preload_chunk, preload_symbol) = {
+        let (
+            mut cur_frame,
+            mut start_pos,
+            mut loaded_frames_end,
+            next_preload_chunk,
+            preload_symbol,
+        ) = {
             let read = static_data.preload_progress.read();
             (
                 read.cur_preload_frame,
                 read.last_frame_start_pos,
+                read.loaded_frames_end,
                 read.next_preload_chunk,
                 read.cur_preload_symbol,
             )
@@ -578,12 +585,16 @@ impl<'gc> MovieClip<'gc> {
                     .0
                     .write(context.gc_context)
                     .jpeg_tables(context, reader),
-                TagCode::ShowFrame => self.0.write(context.gc_context).show_frame(
-                    reader,
-                    tag_len,
-                    &mut cur_frame,
-                    &mut start_pos,
-                ),
+                TagCode::ShowFrame => {
+                    let tag = data.resize_to_reader(reader, tag_len);
+                    loaded_frames_end = tag.end.saturating_sub(data.start) as u64;
+                    self.0.write(context.gc_context).show_frame(
+                        reader,
+                        tag_len,
+                        &mut cur_frame,
+                        &mut start_pos,
+                    )
+                }
                 TagCode::ScriptLimits => self
                     .0
                     .write(context.gc_context)
@@ -648,6 +659,7 @@ impl<'gc> MovieClip<'gc> {
                 cur_frame
             };
             write.last_frame_start_pos = start_pos;
+            write.loaded_frames_end = loaded_frames_end;
         }
 
         if is_finished {
@@ -1145,6 +1157,12 @@ impl<'gc> MovieClip<'gc> {
 
         let swf_header_size = self.total_bytes() - self.tag_stream_len() as u32;
 
+        // The root timeline streams in whole frames, so a preloader only sees
+        // bytes up to the end of the last frame that has finished loading.
+        if self.is_root() {
+            return swf_header_size + progress_read.loaded_frames_end as u32;
+        }
+
         swf_header_size + progress_read.next_preload_chunk as u32
     }
 
@@ -1918,11 +1936,7 @@ impl<'gc> MovieClip<'gc> {
                     if let Some(init_object) = init_object {
                         // AVM1 sets keys in reverse order (compared to enumeration order).
                         // This behavior is visible to setters, and some SWFs depend on it.
-                        for key in init_object.get_keys(&mut activation).into_iter().rev() {
-                            if let Ok(value) = init_object.get(key, &mut activation) {
-                                let _ = object.set(key, value, &mut activation);
-                            }
-                        }
+                        apply_init_object(object, init_object, &mut activation, true);
                     }
                     let _ = constructor.construct_on_existing(&mut activation, object, &[]);
                 }
@@ -1949,11 +1963,7 @@ impl<'gc> MovieClip<'gc> {
                     self.into(),
                 );
 
-                for key in init_object.get_keys(&mut activation) {
-                    if let Ok(value) = init_object.get(key, &mut activation) {
-                        let _ = object.set(key, value, &mut activation);
-                    }
-                }
+                apply_init_object(object, init_object, &mut activation, false);
             }
 
             let mut events = Vec::new();
@@ -2454,6 +2464,10 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         self.0.read().drawing.self_bounds()
     }
 
+    fn self_edge_bounds(&self) -> BoundingBox {
+        self.0.read().drawing.self_edge_bounds()
+    }
+
     fn hit_test_shape(
         &self,
         context: &mut UpdateContext<'_, 'gc, '_>,
@@ -3905,6 +3919,32 @@ impl Default for Scene {
     }
 }
 
+/// Copy the properties of an AVM1 init object (as passed to `attachMovie` or
+/// `duplicateMovieClip`) onto a newly created clip's object.
+///
+/// Only the init object's own enumerable properties are copied; anything it
+/// inherits from its prototype chain is not. Values are copied as-is, so
+/// objects and functions are shared by reference. If `reverse` is set, the
+/// properties are set in the reverse of their enumeration order.
+fn apply_init_object<'gc>(
+    object: Avm1Object<'gc>,
+    init_object: Avm1Object<'gc>,
+    activation: &mut Avm1Activation<'_, 'gc, '_>,
+    reverse: bool,
+) {
+    let mut keys = init_object.get_keys(activation);
+    keys.retain(|key| init_object.has_own_property(activation, *key));
+    if reverse {
+        keys.reverse();
+    }
+
+    for key in keys {
+        if let Ok(value) = init_object.get(key, activation) {
+            let _ = object.set(key, value, activation);
+        }
+    }
+}
+
 /// The load progress for a given SWF or substream of that SWF.
 #[derive(Clone, Collect)]
 #[collect(require_static)]
@@ -3918,6 +3958,9 @@ struct PreloadProgress {
     /// The SWF offset that the current frame started in.
     last_frame_start_pos: u64,
 
+    /// The SWF offset just past the last fully preloaded frame.
+    loaded_frames_end: u64,
+
     /// The symbol we are currently asynchronously preloading.
     cur_preload_symbol: Option<CharacterId>,
 }
@@ -3928,6 +3971,7 @@ impl Default for PreloadProgress {
             next_preload_chunk: 0,
             cur_preload_frame: 1,
             last_frame_start_pos: 0,
+            loaded_frames_end: 0,
             cur_preload_symbol: None,
         }
     }
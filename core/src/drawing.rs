@@ -126,6 +126,17 @@ impl Drawing {
         self.dirty.set(true);
     }
 
+    /// The style of the fill currently being drawn, if any.
+    #[cfg(test)]
+    pub fn fill_style(&self) -> Option<&FillStyle> {
+        self.current_fill.as_ref().map(|fill| &fill.style)
+    }
+
+    /// The style of the line currently being drawn, if any.
+    pub fn line_style(&self) -> Option<&LineStyle> {
+        self.current_line.as_ref().map(|line| &line.style)
+    }
+
     pub fn clear(&mut self) {
         self.current_fill = None;
         self.current_line = None;
@@ -300,6 +311,11 @@ impl Drawing {
         self.shape_bounds.clone()
     }
 
+    /// The bounds of the drawing, excluding the width of any strokes.
+    pub fn self_edge_bounds(&self) -> BoundingBox {
+        self.edge_bounds.clone()
+    }
+
     pub fn hit_test(
         &self,
         point: (Twips, Twips),
@@ -56,4 +56,57 @@ impl<'gc> FocusTracker<'gc> {
             ],
         );
     }
+
+    /// Move the focus to the next tab stop, or to the previous one if
+    /// `reverse` is set, as when the user presses Tab or Shift+Tab.
+    ///
+    /// If any tab stop has a `tabIndex`, only those are visited, in order of
+    /// their indices. Otherwise, every tab stop is visited in display list
+    /// order.
+    pub fn cycle(&self, context: &mut UpdateContext<'_, 'gc, '_>, reverse: bool) {
+        let mut stops = Vec::new();
+        for level in context.stage.iter_render_list() {
+            Self::collect_tab_stops(level, &mut stops);
+        }
+
+        if stops.iter().any(|stop| stop.tab_index().is_some()) {
+            stops.retain(|stop| stop.tab_index().is_some());
+            stops.sort_by_key(|stop| stop.tab_index());
+        }
+
+        if stops.is_empty() {
+            return;
+        }
+
+        let current = self.get().and_then(|focus| {
+            stops
+                .iter()
+                .position(|&stop| DisplayObject::ptr_eq(stop, focus))
+        });
+        let next = match (current, reverse) {
+            (Some(i), false) => (i + 1) % stops.len(),
+            (Some(i), true) => (i + stops.len() - 1) % stops.len(),
+            (None, false) => 0,
+            (None, true) => stops.len() - 1,
+        };
+        self.set(Some(stops[next]), context);
+    }
+
+    /// Collect the visible tab stops in and below `object`, in display list
+    /// order.
+    fn collect_tab_stops(object: DisplayObject<'gc>, stops: &mut Vec<DisplayObject<'gc>>) {
+        if !object.visible() {
+            return;
+        }
+
+        if object.is_tab_stop() {
+            stops.push(object);
+        }
+
+        if let Some(container) = object.as_container() {
+            for child in container.iter_render_list() {
+                Self::collect_tab_stops(child, stops);
+            }
+        }
+    }
 }
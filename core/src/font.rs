@@ -9,6 +9,14 @@ use std::cmp::max;
 
 pub use swf::TextGridFit;
 
+/// A soft hyphen, which marks a word break opportunity that renders as a
+/// hyphen only if a line is actually broken there.
+const SOFT_HYPHEN: char = '\u{00AD}';
+
+/// A zero-width space, which marks a word break opportunity that never
+/// renders anything.
+const ZERO_WIDTH_SPACE: char = '\u{200B}';
+
 /// Certain Flash routines measure text by rounding down to the nearest whole pixel.
 pub fn round_down_to_pixel(t: Twips) -> Twips {
     Twips::from_pixels(t.to_pixels().floor())
@@ -163,6 +171,48 @@ impl<'gc> Font<'gc> {
         ))
     }
 
+    /// Construct a font from the built-in approximate metrics of a device
+    /// font.
+    ///
+    /// The font has no glyph shapes, so it only serves to measure and wrap
+    /// text when no real device font is available. Glyphs are provided for
+    /// printable ASCII and Latin-1 characters.
+    pub fn from_device_metrics(gc_context: MutationContext<'gc, '_>, kind: DeviceFont) -> Self {
+        let mut glyphs = vec![];
+        let mut code_point_to_glyph = fnv::FnvHashMap::default();
+
+        for c in (' '..='~').chain('\u{A0}'..='\u{FF}') {
+            let code = c as u16;
+            let advance = kind.advance(c);
+            code_point_to_glyph.insert(code, glyphs.len());
+            glyphs.push(Glyph {
+                shape_handle: Cell::new(None),
+                advance,
+                shape: RefCell::new(None),
+                swf_glyph: swf::Glyph {
+                    shape_records: vec![],
+                    code,
+                    advance,
+                    bounds: None,
+                },
+            });
+        }
+
+        Font(Gc::allocate(
+            gc_context,
+            FontData {
+                glyphs,
+                code_point_to_glyph,
+                scale: DeviceFont::EM_SIZE,
+                kerning_pairs: fnv::FnvHashMap::default(),
+                ascent: kind.ascent(),
+                descent: kind.descent(),
+                leading: 0,
+                descriptor: FontDescriptor::from_parts(kind.name(), false, false),
+            },
+        ))
+    }
+
     /// Returns whether this font contains glyph shapes.
     /// If not, this font should be rendered as a device font.
     pub fn has_glyphs(&self) -> bool {
@@ -251,6 +301,10 @@ impl<'gc> Font<'gc> {
     /// of transforms and glyphs which will be consumed by the `glyph_func`
     /// closure. This corresponds to the series of drawing operations necessary
     /// to render the text on a single horizontal line.
+    ///
+    /// Zero-width spaces are never rendered. Soft hyphens are only rendered
+    /// (as a regular hyphen) when they end the evaluated text, which is the
+    /// case when a line was broken at them.
     pub fn evaluate<FGlyph>(
         &self,
         text: &WStr, // TODO: take an `IntoIterator<Item=char>`, to not depend on string representation?
@@ -269,7 +323,12 @@ impl<'gc> Font<'gc> {
         let has_kerning_info = self.has_kerning_info();
         let mut x = Twips::ZERO;
         while let Some((pos, c)) = char_indices.next() {
-            let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+            let c = match c.unwrap_or(char::REPLACEMENT_CHARACTER) {
+                ZERO_WIDTH_SPACE => continue,
+                SOFT_HYPHEN if char_indices.peek().is_some() => continue,
+                SOFT_HYPHEN => '-',
+                c => c,
+            };
             if let Some(glyph) = self.get_glyph_for_char(c) {
                 let mut advance = Twips::new(glyph.advance);
                 if has_kerning_info && params.kerning {
@@ -324,9 +383,11 @@ impl<'gc> Font<'gc> {
 
     /// Given a line of text, find the first breakpoint within the text.
     ///
-    /// This function assumes only `" "` is valid whitespace to split words on,
-    /// and will not attempt to break words that are longer than `width`, nor
-    /// will it break at newlines.
+    /// This function splits words on `" "`, as well as on soft hyphens and
+    /// zero-width spaces. It will not attempt to break words that are longer
+    /// than `width`, nor will it break at newlines. A soft hyphen is only
+    /// considered a valid breakpoint if the hyphen it renders also fits on
+    /// the line.
     ///
     /// The given `offset` determines the start of the initial line, while the
     /// `width` indicates how long the line is supposed to be. Be careful to
@@ -353,17 +414,26 @@ impl<'gc> Font<'gc> {
 
         let mut line_end = 0;
 
-        for word in text.split(b' ') {
+        let delimiters = [b' ' as u16, SOFT_HYPHEN as u16, ZERO_WIDTH_SPACE as u16];
+        for word in text.split(&delimiters[..]) {
             let word_start = word.offset_in(text).unwrap();
             let word_end = word_start + word.len();
 
             let measure = self.measure(
-                // +1 is fine because ' ' is 1 unit
+                // +1 is fine because all delimiters are 1 unit
                 text.slice(word_start..word_end + 1).unwrap_or(word),
                 params,
                 false,
             );
 
+            // A soft hyphen only takes up space if we break the line there,
+            // so the word itself may fit even if its hyphen doesn't.
+            let word_width = if text.get(word_end) == Some(SOFT_HYPHEN as u16) {
+                self.measure(word, params, false).0
+            } else {
+                measure.0
+            };
+
             if is_start_of_line && measure.0 > remaining_width {
                 //Failsafe for if we get a word wider than the field.
                 let mut last_passing_breakpoint = (Twips::ZERO, Twips::ZERO);
@@ -388,18 +458,22 @@ impl<'gc> Font<'gc> {
                 }
 
                 return Some(prev_char_index);
-            } else if measure.0 > remaining_width {
+            } else if word_width > remaining_width {
                 //The word is wider than our remaining width, return the end of
                 //the line.
                 return Some(line_end);
             } else {
                 //Space remains for our current word, move up the word pointer.
-                line_end = word_end;
-                is_start_of_line = is_start_of_line && text[0..line_end].trim().is_empty();
+                //If the word only fits without its soft hyphen, we can't break
+                //after it.
+                if measure.0 <= remaining_width {
+                    line_end = word_end;
+                }
+                is_start_of_line = is_start_of_line && text[0..word_end].trim().is_empty();
 
                 //If the additional space were to cause an overflow, then
                 //return now.
-                remaining_width -= measure.0;
+                remaining_width -= word_width;
                 if remaining_width < Twips::from_pixels(0.0) {
                     return Some(word_end);
                 }
@@ -451,6 +525,96 @@ impl Glyph {
     }
 }
 
+/// One of the generic device fonts, which Flash maps to a font installed on
+/// the system.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeviceFont {
+    Sans,
+    Serif,
+    Typewriter,
+}
+
+impl DeviceFont {
+    /// The size of the EM square that device font metrics are given in.
+    const EM_SIZE: f32 = 1000.0;
+
+    /// Advances of the printable ASCII characters (`' '..='~'`) in `_sans`,
+    /// approximating Arial.
+    const SANS_ADVANCES: [i16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722,
+        722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556,
+        556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500,
+        500, 334, 260, 334, 584,
+    ];
+
+    /// Advances of the printable ASCII characters (`' '..='~'`) in `_serif`,
+    /// approximating Times New Roman.
+    const SERIF_ADVANCES: [i16; 95] = [
+        250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278, 500, 500,
+        500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444, 921, 722, 667, 667,
+        722, 611, 556, 722, 722, 333, 389, 722, 611, 889, 722, 722, 556, 722, 667, 556, 611, 722,
+        722, 944, 722, 722, 611, 333, 278, 333, 469, 500, 333, 444, 500, 444, 500, 444, 333, 500,
+        500, 278, 278, 500, 278, 778, 500, 500, 500, 500, 333, 389, 278, 500, 500, 722, 500, 500,
+        444, 480, 200, 480, 541,
+    ];
+
+    /// Resolve one of the device font names (`_sans`, `_serif` or
+    /// `_typewriter`).
+    pub fn from_name(name: &WStr) -> Option<Self> {
+        if name == b"_sans" {
+            Some(Self::Sans)
+        } else if name == b"_serif" {
+            Some(Self::Serif)
+        } else if name == b"_typewriter" {
+            Some(Self::Typewriter)
+        } else {
+            None
+        }
+    }
+
+    /// The name of this device font.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sans => "_sans",
+            Self::Serif => "_serif",
+            Self::Typewriter => "_typewriter",
+        }
+    }
+
+    /// The approximate advance of a character, in EM-square coordinates.
+    ///
+    /// `_typewriter` is monospaced; characters missing from the other tables
+    /// use the width of a digit.
+    fn advance(self, c: char) -> i16 {
+        let index = (c as usize).wrapping_sub(' ' as usize);
+        match self {
+            Self::Sans => Self::SANS_ADVANCES.get(index).copied().unwrap_or(556),
+            Self::Serif => Self::SERIF_ADVANCES.get(index).copied().unwrap_or(500),
+            Self::Typewriter => 600,
+        }
+    }
+
+    /// The approximate ascent, in EM-square coordinates.
+    fn ascent(self) -> u16 {
+        match self {
+            Self::Sans => 905,
+            Self::Serif => 891,
+            Self::Typewriter => 833,
+        }
+    }
+
+    /// The approximate descent, in EM-square coordinates.
+    fn descent(self) -> u16 {
+        match self {
+            Self::Sans => 212,
+            Self::Serif => 216,
+            Self::Typewriter => 300,
+        }
+    }
+}
+
 /// Structure which identifies a particular font by name and properties.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Collect)]
 #[collect(require_static)]
@@ -686,9 +850,9 @@ impl Default for TextRenderSettings {
 
 #[cfg(test)]
 mod tests {
-    use crate::font::{EvalParameters, Font};
+    use crate::font::{DeviceFont, EvalParameters, Font};
     use crate::player::Player;
-    use crate::string::WStr;
+    use crate::string::{WStr, WString};
     use gc_arena::{rootless_arena, MutationContext};
     use ruffle_render::backend::{null::NullRenderer, ViewportDimensions};
     use swf::Twips;
@@ -866,4 +1030,172 @@ mod tests {
             assert_eq!(None, breakpoint5);
         });
     }
+
+    #[test]
+    fn wrap_line_breakpoint_soft_hyphen() {
+        with_device_font(|_mc, df| {
+            let params = EvalParameters::from_parts(
+                Twips::from_pixels(12.0),
+                Twips::from_pixels(0.0),
+                false,
+            );
+            let string = WString::from_utf8("hyphen\u{AD}ation");
+            let hyphenated = df.measure(WStr::from_units(b"hyphen-"), params, false).0;
+
+            // The soft hyphen renders as a hyphen when it ends a line...
+            assert_eq!(hyphenated, df.measure(&string[..7], params, false).0);
+
+            // ...and is invisible otherwise.
+            assert_eq!(
+                df.measure(WStr::from_units(b"hyphenation"), params, false)
+                    .0,
+                df.measure(&string, params, false).0,
+            );
+
+            let breakpoint = df.wrap_line(
+                &string,
+                params,
+                hyphenated + Twips::new(1),
+                Twips::from_pixels(0.0),
+                true,
+            );
+
+            assert_eq!(Some(6), breakpoint);
+        });
+    }
+
+    #[test]
+    fn wrap_line_breakpoint_zero_width_space() {
+        with_device_font(|_mc, df| {
+            let params = EvalParameters::from_parts(
+                Twips::from_pixels(12.0),
+                Twips::from_pixels(0.0),
+                false,
+            );
+            let string = WString::from_utf8("hyphen\u{200B}ation");
+            let unhyphenated = df.measure(WStr::from_units(b"hyphen"), params, false).0;
+
+            assert_eq!(unhyphenated, df.measure(&string[..7], params, false).0);
+
+            let breakpoint = df.wrap_line(
+                &string,
+                params,
+                unhyphenated + Twips::new(1),
+                Twips::from_pixels(0.0),
+                true,
+            );
+
+            assert_eq!(Some(6), breakpoint);
+        });
+    }
+
+    #[test]
+    fn device_font_metrics_typewriter_is_monospaced() {
+        rootless_arena(|mc| {
+            let font = Font::from_device_metrics(mc, DeviceFont::Typewriter);
+            let params = EvalParameters::from_parts(
+                Twips::from_pixels(12.0),
+                Twips::from_pixels(0.0),
+                false,
+            );
+            let text = WStr::from_units(b"iiii WWWW, mm!");
+
+            // 600 units of a 1000 unit EM square, at 12px.
+            let advance = 600 * params.height().get() / 1000;
+            let (width, height) = font.measure(text, params, false);
+            assert_eq!(width, Twips::new(advance * text.len() as i32));
+            assert_eq!(height, Twips::from_pixels(12.0));
+
+            let narrow = font.measure(WStr::from_units(b"iiii"), params, false).0;
+            let wide = font.measure(WStr::from_units(b"WWWW"), params, false).0;
+            assert_eq!(narrow, wide);
+        });
+    }
+
+    #[test]
+    fn device_font_metrics_proportional() {
+        rootless_arena(|mc| {
+            let params = EvalParameters::from_parts(
+                Twips::from_pixels(12.0),
+                Twips::from_pixels(0.0),
+                false,
+            );
+
+            for kind in [DeviceFont::Sans, DeviceFont::Serif] {
+                let font = Font::from_device_metrics(mc, kind);
+                let narrow = font.measure(WStr::from_units(b"iiii"), params, false).0;
+                let wide = font.measure(WStr::from_units(b"WWWW"), params, false).0;
+                assert!(narrow < wide);
+            }
+        });
+    }
+
+    #[test]
+    fn device_font_from_name() {
+        assert_eq!(
+            DeviceFont::from_name(WStr::from_units(b"_sans")),
+            Some(DeviceFont::Sans)
+        );
+        assert_eq!(
+            DeviceFont::from_name(WStr::from_units(b"_serif")),
+            Some(DeviceFont::Serif)
+        );
+        assert_eq!(
+            DeviceFont::from_name(WStr::from_units(b"_typewriter")),
+            Some(DeviceFont::Typewriter)
+        );
+        assert_eq!(DeviceFont::from_name(WStr::from_units(b"Arial")), None);
+    }
+
+    #[test]
+    fn embedded_font_kerning_pairs() {
+        rootless_arena(|mc| {
+            let mut renderer = NullRenderer::new(ViewportDimensions {
+                width: 0,
+                height: 0,
+                scale_factor: 1.0,
+            });
+            let glyph = |code: u8| swf::Glyph {
+                shape_records: vec![],
+                code: code.into(),
+                advance: 1024,
+                bounds: None,
+            };
+            let tag = swf::Font {
+                version: 2,
+                id: 1,
+                name: swf::SwfStr::from_utf8_str("Kerned"),
+                language: swf::Language::Latin,
+                layout: Some(swf::FontLayout {
+                    ascent: 1024,
+                    descent: 0,
+                    leading: 0,
+                    kerning: vec![swf::KerningRecord {
+                        left_code: b'a'.into(),
+                        right_code: b'b'.into(),
+                        adjustment: Twips::new(-512),
+                    }],
+                }),
+                glyphs: vec![glyph(b'a'), glyph(b'b'), glyph(b'c')],
+                flags: swf::FontFlag::empty(),
+            };
+            let font = Font::from_swf_tag(mc, &mut renderer, tag, swf::UTF_8);
+            assert!(font.has_kerning_info());
+            assert_eq!(font.get_kerning_offset('a', 'b'), Twips::new(-512));
+            assert_eq!(font.get_kerning_offset('b', 'a'), Twips::ZERO);
+            assert_eq!(font.get_kerning_offset('a', 'c'), Twips::ZERO);
+
+            let width = |text: &[u8], kerning: bool| {
+                let params =
+                    EvalParameters::from_parts(Twips::from_pixels(12.0), Twips::ZERO, kerning);
+                font.measure(WStr::from_units(text), params, false).0
+            };
+
+            // The pair pulls `b` half a glyph closer to `a`, only when kerning.
+            assert_eq!(width(b"ab", false), Twips::from_pixels(24.0));
+            assert_eq!(width(b"ab", true), Twips::from_pixels(18.0));
+            assert_eq!(width(b"ba", true), Twips::from_pixels(24.0));
+            assert_eq!(width(b"ac", true), Twips::from_pixels(24.0));
+        });
+    }
 }
@@ -9,7 +9,7 @@ pub use dimensions::BoxBounds;
 pub use dimensions::Position;
 pub use dimensions::Size;
 pub use layout::{LayoutBox, LayoutContent, LayoutMetrics};
-pub use text_format::{FormatSpans, TextFormat, TextSpan};
+pub use text_format::{parse_hex_color, FormatSpans, TextFormat, TextSpan, TextSpanFormat};
 
 #[cfg(test)]
 mod test;
@@ -5,7 +5,7 @@ use crate::drawing::Drawing;
 use crate::font::{EvalParameters, Font};
 use crate::html::dimensions::{BoxBounds, Position, Size};
 use crate::html::text_format::{FormatSpans, TextFormat, TextSpan};
-use crate::string::{utils as string_utils, WStr};
+use crate::string::{utils as string_utils, WStr, WString};
 use crate::tag_utils::SwfMovie;
 use gc_arena::Collect;
 use ruffle_render::shape_utils::DrawCommand;
@@ -392,14 +392,16 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
     /// tab stops have been manually determined. If they have been, then the
     /// text cursor goes to the next closest tab stop that has not yet been
     /// passed, or if no such stop exists, tabs do nothing. If no tab stops
-    /// exist, then the cursor is advanced to some position modulo the natural
-    /// tab index.
+    /// exist, then the cursor is advanced to the next stop on a default grid
+    /// whose spacing is proportional to the font size.
     fn tab(&mut self) {
         if self.current_line_span.tab_stops.is_empty() {
-            let modulo_factor = Twips::from_pixels(self.current_line_span.size * 2.7);
-            let stop_modulo_tab =
-                ((self.cursor.x().get() / modulo_factor.get()) + 1) * modulo_factor.get();
-            self.cursor.set_x(Twips::new(stop_modulo_tab));
+            let modulo_factor = Self::default_tab_width(&self.current_line_span);
+            if modulo_factor > Twips::ZERO {
+                let stop_modulo_tab =
+                    ((self.cursor.x().get() / modulo_factor.get()) + 1) * modulo_factor.get();
+                self.cursor.set_x(Twips::new(stop_modulo_tab));
+            }
         } else {
             for stop in self.current_line_span.tab_stops.iter() {
                 let twips_stop = Twips::from_pixels(*stop);
@@ -411,6 +413,12 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         }
     }
 
+    /// The spacing of the default tab stops, used when a span has no tab
+    /// stops of its own.
+    fn default_tab_width(span: &TextSpan) -> Twips {
+        Twips::from_pixels(span.size * 2.7)
+    }
+
     /// Enter a new span.
     fn newspan(&mut self, first_span: &TextSpan) {
         if self.is_start_of_line() {
@@ -427,16 +435,9 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         span: &TextSpan,
         is_device_font: bool,
     ) -> Option<Font<'gc>> {
-        let library = context.library.library_for_movie_mut(self.movie.clone());
-
         // If this text field is set to use device fonts, fallback to using our embedded Noto Sans.
-        // Note that the SWF can still contain a DefineFont tag with no glyphs/layout info in this case (see #451).
         // In an ideal world, device fonts would search for a matching font on the system and render it in some way.
-        if let Some(font) = library
-            .get_font_by_name(&span.font.to_utf8_lossy(), span.bold, span.italic)
-            .filter(|f| !is_device_font && f.has_glyphs())
-            .or_else(|| context.library.device_font())
-        {
+        if let Some(font) = span.resolve_font(context, self.movie.clone(), is_device_font) {
             self.font = Some(font);
             return self.font;
         }
@@ -488,12 +489,8 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
     /// should be appended after line fixup has completed, but before the text
     /// cursor is moved down.
     fn append_bullet(&mut self, context: &mut UpdateContext<'_, 'gc, '_>, span: &TextSpan) {
-        let library = context.library.library_for_movie_mut(self.movie.clone());
-
-        if let Some(bullet_font) = library
-            .get_font_by_name(&span.font.to_utf8_lossy(), span.bold, span.italic)
-            .filter(|f| f.has_glyphs())
-            .or_else(|| context.library.device_font())
+        if let Some(bullet_font) = span
+            .resolve_font(context, self.movie.clone(), false)
             .or(self.font)
         {
             let mut bullet_cursor = self.cursor;
@@ -504,8 +501,8 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
             );
 
             let params = EvalParameters::from_span(span);
-            let bullet = WStr::from_units(&[0x2022u16]);
-            let text_size = Size::from(bullet_font.measure(bullet, params, false));
+            let label = span.bullet_label();
+            let text_size = Size::from(bullet_font.measure(&label, params, false));
             let text_bounds = BoxBounds::from_position_and_size(bullet_cursor, text_size);
             let mut new_bullet = LayoutBox::from_bullet(bullet_font, span);
 
@@ -635,9 +632,13 @@ pub enum LayoutContent<'gc> {
 
     /// A layout box containing a bullet.
     ///
-    /// This is almost identical to `Text`, but the text contents are assumed
-    /// to be U+2022.
+    /// This is almost identical to `Text`, but the text contents are the
+    /// bullet label of the span, rather than part of the laid out text.
     Bullet {
+        /// The bullet character, or the number of an ordered list item.
+        #[collect(require_static)]
+        label: WString,
+
         /// The formatting options for the text box.
         text_format: TextFormat,
 
@@ -686,6 +687,7 @@ impl<'gc> LayoutBox<'gc> {
         Self {
             bounds: Default::default(),
             content: LayoutContent::Bullet {
+                label: span.bullet_label(),
                 text_format: span.get_text_format(),
                 font,
                 params,
@@ -831,9 +833,15 @@ impl<'gc> LayoutBox<'gc> {
     /// Returns a reference to the text this box contains, as well as font
     /// rendering parameters, if the layout box has any.
     pub fn as_renderable_text<'a>(
-        &self,
+        &'a self,
         text: &'a WStr,
-    ) -> Option<(&'a WStr, &TextFormat, Font<'gc>, EvalParameters, swf::Color)> {
+    ) -> Option<(
+        &'a WStr,
+        &'a TextFormat,
+        Font<'gc>,
+        EvalParameters,
+        swf::Color,
+    )> {
         match &self.content {
             LayoutContent::Text {
                 start,
@@ -850,12 +858,13 @@ impl<'gc> LayoutBox<'gc> {
                 swf::Color::from_rgb(color.to_rgb(), 0xFF),
             )),
             LayoutContent::Bullet {
+                label,
                 text_format,
                 font,
                 params,
                 color,
             } => Some((
-                WStr::from_units(&[0x2022u16]),
+                label.as_wstr(),
                 text_format,
                 *font,
                 *params,
@@ -865,6 +874,42 @@ impl<'gc> LayoutBox<'gc> {
         }
     }
 
+    /// Returns the bounds of the character at `position` of the text this box
+    /// was laid out from, if this box renders that character.
+    pub fn char_bounds(&self, text: &WStr, position: usize) -> Option<BoxBounds<Twips>> {
+        let (start, end, font, params) = match &self.content {
+            LayoutContent::Text {
+                start,
+                end,
+                font,
+                params,
+                ..
+            } => (*start, *end, *font, *params),
+            _ => return None,
+        };
+
+        if position < start || position >= end {
+            return None;
+        }
+
+        let mut char_bounds = None;
+        font.evaluate(
+            text.slice(start..end)?,
+            Default::default(),
+            params,
+            |pos, _transform, _glyph, advance, x| {
+                if start + pos == position {
+                    char_bounds = Some((x, advance));
+                }
+            },
+        );
+
+        let (x, advance) = char_bounds?;
+        let origin = self.bounds.origin() + Position::from((x, Twips::ZERO));
+        let size = Size::from((advance, self.bounds.height()));
+        Some(BoxBounds::from_position_and_size(origin, size))
+    }
+
     /// Returns a reference to the drawing this box contains, if it has one.
     pub fn as_renderable_drawing(&self) -> Option<&Drawing> {
         match &self.content {
@@ -1,10 +1,68 @@
 //! Tests for HTML module
 
+use crate::character::Character;
+use crate::context::UpdateContext;
+use crate::font::Font;
 use crate::html::dimensions::{BoxBounds, Position, Size};
-use crate::html::text_format::{FormatSpans, TextFormat, TextSpan};
+use crate::html::layout::LayoutBox;
+use crate::html::text_format::{parse_html_color, FormatSpans, TextFormat, TextSpan};
+use crate::player::PlayerBuilder;
 use crate::string::{WStr, WString};
+use crate::tag_utils::SwfMovie;
+use crate::xml::ParseOptions;
+use std::sync::Arc;
 use swf::{Rectangle, Twips};
 
+/// Run a test with an `UpdateContext` for an empty movie.
+fn with_update_context<F>(test: F)
+where
+    F: for<'a, 'gc> FnOnce(&mut UpdateContext<'a, 'gc, '_>, Arc<SwfMovie>),
+{
+    let player = PlayerBuilder::new().with_movie(SwfMovie::empty(8)).build();
+    let mut player = player.lock().unwrap();
+    player.mutate_with_update_context(|context| {
+        let movie = context.swf.clone();
+        test(context, movie)
+    })
+}
+
+/// Register an embedded font with a single glyph, `a`, in the given movie's
+/// library.
+fn register_test_font<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    movie: Arc<SwfMovie>,
+    id: swf::CharacterId,
+    name: &str,
+    flags: swf::FontFlag,
+) -> Font<'gc> {
+    let tag = swf::Font {
+        version: 3,
+        id,
+        name: swf::SwfStr::from_utf8_str(name),
+        language: swf::Language::Latin,
+        layout: Some(swf::FontLayout {
+            ascent: 16384,
+            descent: 4096,
+            leading: 0,
+            kerning: vec![],
+        }),
+        glyphs: vec![swf::Glyph {
+            shape_records: vec![],
+            code: b'a'.into(),
+            advance: 1024,
+            bounds: None,
+        }],
+        flags,
+    };
+    let font = Font::from_swf_tag(context.gc_context, context.renderer, tag, swf::UTF_8);
+    context
+        .library
+        .library_for_movie_mut(movie)
+        .register_character(id, Character::Font(font));
+
+    font
+}
+
 #[test]
 fn position_add() {
     let pos1 = Position::from((12, 31));
@@ -269,6 +327,27 @@ fn textformat_merge() {
     assert_eq!(merged.italic, None);
 }
 
+#[test]
+fn textspan_get_text_format_bullet() {
+    let span = TextSpan::with_length_and_format(
+        1,
+        TextFormat {
+            bullet: Some(true),
+            bold: Some(false),
+            ..Default::default()
+        },
+    );
+    let tf = span.get_text_format();
+    assert_eq!(Some(true), tf.bullet);
+    assert_eq!(Some(false), tf.bold);
+
+    // The fields survive a round trip through a text field, too.
+    let mut fs = FormatSpans::from_text(WString::from_utf8("ab"), Default::default());
+    fs.set_text_format(0, 2, &tf);
+    assert_eq!(Some(true), fs.get_text_format(0, 2).bullet);
+    assert_eq!(Some(false), fs.get_text_format(0, 2).bold);
+}
+
 #[test]
 fn textformat_mix() {
     let tf1 = TextFormat {
@@ -295,6 +374,103 @@ fn textformat_mix() {
     assert_eq!(mixed.italic, None);
 }
 
+#[test]
+fn textformat_from_css_declarations() {
+    let declarations = |decls: &[(&str, &str)]| {
+        TextFormat::from_css_declarations(
+            &decls
+                .iter()
+                .map(|(property, value)| (property.to_string(), value.to_string()))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let tf = declarations(&[
+        ("color", "#FF8000"),
+        ("font-family", "Arial"),
+        ("font-size", "14px"),
+        ("font-weight", "bold"),
+        ("font-style", "italic"),
+        ("text-align", "center"),
+        ("text-decoration", "underline"),
+        ("text-indent", "4"),
+        ("letter-spacing", "1.5px"),
+        ("kerning", "true"),
+        ("leading", "2"),
+        ("margin-left", "10px"),
+        ("margin-right", "20"),
+        ("display", "block"),
+        ("direction", "rtl"),
+        ("unknown-property", "42"),
+    ]);
+    assert_eq!(Some(WString::from_utf8("Arial")), tf.font);
+    assert_eq!(Some(14.0), tf.size);
+    assert_eq!(Some(swf::Color::from_rgb(0xFF8000, 255)), tf.color);
+    assert_eq!(Some(swf::TextAlign::Center), tf.align);
+    assert_eq!(Some(true), tf.bold);
+    assert_eq!(Some(true), tf.italic);
+    assert_eq!(Some(true), tf.underline);
+    assert_eq!(Some(10.0), tf.left_margin);
+    assert_eq!(Some(20.0), tf.right_margin);
+    assert_eq!(Some(4.0), tf.indent);
+    assert_eq!(Some(true), tf.kerning);
+    assert_eq!(Some(2.0), tf.leading);
+    assert_eq!(Some(1.5), tf.letter_spacing);
+    assert_eq!(None, tf.block_indent);
+    assert_eq!(None, tf.tab_stops);
+    assert_eq!(None, tf.bullet);
+    assert_eq!(None, tf.url);
+    assert_eq!(None, tf.target);
+
+    // Keywords that turn properties off, case and whitespace insensitively.
+    let tf = declarations(&[
+        (" Font-Weight ", " NORMAL "),
+        ("font-style", "normal"),
+        ("text-decoration", "none"),
+        ("kerning", "false"),
+        ("text-align", "justify"),
+    ]);
+    assert_eq!(Some(false), tf.bold);
+    assert_eq!(Some(false), tf.italic);
+    assert_eq!(Some(false), tf.underline);
+    assert_eq!(Some(false), tf.kerning);
+    assert_eq!(Some(swf::TextAlign::Justify), tf.align);
+
+    // Invalid values and unknown properties leave the format undefined, and
+    // later declarations win.
+    let tf = declarations(&[
+        ("color", "red"),
+        ("font-size", "large"),
+        ("font-weight", "heavy"),
+        ("leading", "3"),
+        ("leading", "5"),
+        ("unknown-property", "42"),
+    ]);
+    assert_eq!(None, tf.color);
+    assert_eq!(None, tf.size);
+    assert_eq!(None, tf.bold);
+    assert_eq!(Some(5.0), tf.leading);
+}
+
+#[test]
+fn formatspans_from_html_style_attribute() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(
+            b"<font color=\"#FF0000\" style=\"color: #0000FF; font-weight: bold\">a</font>b",
+        ),
+        Default::default(),
+        false,
+    );
+    assert_eq!(WStr::from_units(b"ab"), fs.text());
+
+    let tf = fs.get_text_format(0, 1);
+    assert_eq!(Some(swf::Color::from_rgb(0x0000FF, 255)), tf.color);
+    assert_eq!(Some(true), tf.bold);
+
+    let tf = fs.get_text_format(1, 2);
+    assert_eq!(Some(false), tf.bold);
+}
+
 #[test]
 fn formatspans_set_default() {
     let mut fs = FormatSpans::new();
@@ -356,7 +532,9 @@ fn formatspans_resolve_position() {
     assert_eq!(Some((3, 0)), fs.resolve_position_as_span(6));
     assert_eq!(Some((4, 0)), fs.resolve_position_as_span(7));
     assert_eq!(Some((4, 1)), fs.resolve_position_as_span(8));
-    assert_eq!(None, fs.resolve_position_as_span(9));
+    // The end of the text resolves to the end of the last span.
+    assert_eq!(Some((4, 2)), fs.resolve_position_as_span(9));
+    assert_eq!(None, fs.resolve_position_as_span(10));
 }
 
 #[test]
@@ -384,7 +562,7 @@ fn formatspans_ensure_span_break() {
     assert_eq!(Some((4, 0)), fs.resolve_position_as_span(6));
     assert_eq!(Some((5, 0)), fs.resolve_position_as_span(7));
     assert_eq!(Some((5, 1)), fs.resolve_position_as_span(8));
-    assert_eq!(None, fs.resolve_position_as_span(9));
+    assert_eq!(Some((5, 2)), fs.resolve_position_as_span(9));
 }
 
 #[test]
@@ -412,7 +590,49 @@ fn formatspans_ensure_span_break_redundant() {
     assert_eq!(Some((3, 0)), fs.resolve_position_as_span(6));
     assert_eq!(Some((4, 0)), fs.resolve_position_as_span(7));
     assert_eq!(Some((4, 1)), fs.resolve_position_as_span(8));
-    assert_eq!(None, fs.resolve_position_as_span(9));
+    assert_eq!(Some((4, 2)), fs.resolve_position_as_span(9));
+}
+
+#[test]
+fn formatspans_ensure_span_break_at_span_ends() {
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcdefghi"),
+        &[
+            TextSpan::with_length_and_format(2, Default::default()),
+            TextSpan::with_length_and_format(3, Default::default()),
+            TextSpan::with_length_and_format(1, Default::default()),
+            TextSpan::with_length_and_format(1, Default::default()),
+            TextSpan::with_length_and_format(2, Default::default()),
+        ],
+    );
+
+    // Every span end, including the end of the text, is already a break: the
+    // index of the span starting there is returned and no span is created.
+    for _ in 0..2 {
+        assert_eq!(Some(0), fs.ensure_span_break_at(0));
+        assert_eq!(Some(1), fs.ensure_span_break_at(2));
+        assert_eq!(Some(2), fs.ensure_span_break_at(5));
+        assert_eq!(Some(3), fs.ensure_span_break_at(6));
+        assert_eq!(Some(4), fs.ensure_span_break_at(7));
+        assert_eq!(Some(5), fs.ensure_span_break_at(9));
+        assert_eq!(None, fs.ensure_span_break_at(10));
+        assert!(fs.span(5).is_none());
+    }
+
+    let mut span = TextSpan::with_length_and_format(3, Default::default());
+    assert!(span.split_at(0).is_none());
+    assert!(span.split_at(3).is_none());
+    assert_eq!(3, span.span_length);
+
+    let second = span.split_at(1).unwrap();
+    assert_eq!(1, span.span_length);
+    assert_eq!(2, second.span_length);
+    assert!(span.can_merge(&second));
+
+    // An empty field has a break at its start, which is also its end.
+    let mut fs = FormatSpans::new();
+    assert_eq!(Some(0), fs.ensure_span_break_at(0));
+    assert!(fs.span(1).is_none());
 }
 
 #[test]
@@ -474,6 +694,44 @@ fn formatspans_get_text_format() {
     assert_eq!(tf1.size, all.size);
 }
 
+#[test]
+fn formatspans_resolve_end_position() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcde"),
+        &[
+            TextSpan::with_length_and_format(2, Default::default()),
+            TextSpan::with_length_and_format(3, bold),
+        ],
+    );
+    let end = fs.text().len();
+
+    assert_eq!(Some((1, 3)), fs.resolve_position_as_span(end));
+    assert_eq!(None, fs.resolve_position_as_span(end + 1));
+
+    // The end is already a span break, so no empty span is created there.
+    assert_eq!(Some(2), fs.ensure_span_break_at(end));
+    assert!(fs.span(2).is_none());
+    assert_eq!(3, fs.span(1).unwrap().span_length);
+
+    assert_eq!((0, 2), fs.get_span_boundaries(0, end));
+    assert_eq!((1, 2), fs.get_span_boundaries(2, end));
+    assert_eq!((1, 2), fs.get_span_boundaries(4, end));
+    assert_eq!(Some(true), fs.get_text_format(2, end).bold);
+
+    assert_eq!(7, fs.replace_text(end, end, WStr::from_units(b"fg"), None));
+    assert_eq!(WStr::from_units(b"abcdefg"), fs.text());
+    assert_eq!(Some((2, 2)), fs.resolve_position_as_span(7));
+
+    // Empty text is covered by a single empty span.
+    let fs = FormatSpans::new();
+    assert_eq!(Some((0, 0)), fs.resolve_position_as_span(0));
+    assert_eq!(None, fs.resolve_position_as_span(1));
+}
+
 #[test]
 fn formatspans_normalize_no_spans() {
     let mut fs = FormatSpans::from_str_and_spans(WStr::from_units(b"abcdefghi"), &[]);
@@ -864,3 +1122,2008 @@ fn formatspans_replace_text_degenerate() {
     assert_eq!((0, 1), fs.get_span_boundaries(0, 5));
     assert_eq!((1, 2), fs.get_span_boundaries(5, 9));
 }
+
+#[test]
+fn textspan_resolve_font() {
+    with_update_context(|context, movie| {
+        register_test_font(
+            context,
+            movie.clone(),
+            1,
+            "Embedded",
+            swf::FontFlag::empty(),
+        );
+        let bold = register_test_font(
+            context,
+            movie.clone(),
+            2,
+            "Embedded",
+            swf::FontFlag::IS_BOLD,
+        );
+
+        let span = TextSpan::with_length_and_format(
+            3,
+            TextFormat {
+                font: Some(WString::from_utf8("Embedded")),
+                bold: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let font = span
+            .resolve_font(context, movie.clone(), false)
+            .expect("font");
+        assert_eq!(bold.descriptor(), font.descriptor());
+
+        // Device fonts ignore the embedded font.
+        let device_font = context.library.device_font().expect("device font");
+        let font = span.resolve_font(context, movie, true).expect("font");
+        assert_eq!(device_font.descriptor(), font.descriptor());
+    });
+}
+
+#[test]
+fn textspan_resolve_font_fallback() {
+    with_update_context(|context, movie| {
+        register_test_font(
+            context,
+            movie.clone(),
+            1,
+            "Embedded",
+            swf::FontFlag::empty(),
+        );
+
+        let span = TextSpan::with_length_and_format(
+            3,
+            TextFormat {
+                font: Some(WString::from_utf8("Unknown")),
+                ..Default::default()
+            },
+        );
+
+        let device_font = context.library.device_font().expect("device font");
+        let font = span.resolve_font(context, movie, false).expect("font");
+        assert_eq!(device_font.descriptor(), font.descriptor());
+    });
+}
+
+#[test]
+fn formatspans_replace_text_link_edges() {
+    let plain = TextFormat::default();
+    let link = TextFormat {
+        url: Some(WString::from_utf8("http://example.com")),
+        target: Some(WString::from_utf8("_blank")),
+        ..Default::default()
+    };
+
+    let make_fs = || {
+        FormatSpans::from_str_and_spans(
+            WStr::from_units(b"abcdefg"),
+            &[
+                TextSpan::with_length_and_format(2, plain.clone()),
+                TextSpan::with_length_and_format(3, link.clone()),
+                TextSpan::with_length_and_format(2, plain.clone()),
+            ],
+        )
+    };
+
+    // Inserting strictly inside the link extends it.
+    let mut fs = make_fs();
+    fs.replace_text(3, 3, WStr::from_units(b"Z"), None);
+    assert_eq!(WStr::from_units(b"abcZdefg"), fs.text());
+    assert_eq!(link.url, fs.get_text_format(3, 4).url);
+    assert_eq!(link.target, fs.get_text_format(3, 4).target);
+    assert_eq!((1, 2), fs.get_span_boundaries(2, 6));
+
+    // Inserting at the leading edge of the link doesn't.
+    let mut fs = make_fs();
+    fs.replace_text(2, 2, WStr::from_units(b"Z"), None);
+    assert_eq!(WStr::from_units(b"abZcdefg"), fs.text());
+    assert_eq!(Some(WString::new()), fs.get_text_format(2, 3).url);
+    assert_eq!(Some(WString::new()), fs.get_text_format(2, 3).target);
+    assert_eq!(link.url, fs.get_text_format(3, 6).url);
+
+    // Neither does inserting at the trailing edge of the link.
+    let mut fs = make_fs();
+    fs.replace_text(5, 5, WStr::from_units(b"Z"), None);
+    assert_eq!(WStr::from_units(b"abcdeZfg"), fs.text());
+    assert_eq!(Some(WString::new()), fs.get_text_format(5, 6).url);
+    assert_eq!(link.url, fs.get_text_format(2, 5).url);
+}
+
+#[test]
+fn formatspans_replace_text_deletes_link() {
+    let plain = TextFormat::default();
+    let link = TextFormat {
+        url: Some(WString::from_utf8("http://example.com")),
+        target: Some(WString::from_utf8("_blank")),
+        ..Default::default()
+    };
+    let has_link = |fs: &FormatSpans| {
+        fs.iter_spans()
+            .any(|(_, _, _, span)| !span.url.is_empty() || !span.target.is_empty())
+    };
+
+    // Deleting all of the link's text removes it, leaving no empty span.
+    for (spans, from, to, text) in [
+        (
+            vec![(2, &plain), (3, &link), (2, &plain)],
+            2,
+            5,
+            &b"abfg"[..],
+        ),
+        (vec![(2, &plain), (5, &link)], 2, 7, &b"ab"[..]),
+        (vec![(3, &link), (4, &plain)], 0, 3, &b"defg"[..]),
+    ] {
+        let spans: Vec<_> = spans
+            .into_iter()
+            .map(|(length, format)| TextSpan::with_length_and_format(length, format.clone()))
+            .collect();
+        let mut fs = FormatSpans::from_str_and_spans(WStr::from_units(b"abcdefg"), &spans);
+        assert!(has_link(&fs));
+
+        fs.replace_text(from, to, WStr::empty(), None);
+        assert_eq!(WStr::from_units(text), fs.text());
+        assert!(!has_link(&fs));
+        assert!(fs.iter_spans().all(|(start, end, _, _)| start < end));
+        assert!(fs.span(1).is_none());
+
+        // Typing where the link used to be doesn't revive it.
+        fs.replace_text(from, from, WStr::from_units(b"Z"), None);
+        assert!(!has_link(&fs));
+    }
+}
+
+#[test]
+fn formatspans_replace_text_link_at_end() {
+    let link = TextFormat {
+        url: Some(WString::from_utf8("http://example.com")),
+        ..Default::default()
+    };
+
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcde"),
+        &[
+            TextSpan::with_length_and_format(2, Default::default()),
+            TextSpan::with_length_and_format(3, link.clone()),
+        ],
+    );
+
+    fs.replace_text(5, 5, WStr::from_units(b"Z"), None);
+
+    assert_eq!(WStr::from_units(b"abcdeZ"), fs.text());
+    assert_eq!(link.url, fs.get_text_format(2, 5).url);
+    assert_ne!(link.url, fs.get_text_format(5, 6).url);
+}
+
+#[test]
+fn formatspans_replace_text_keeps_default_link() {
+    let link = TextFormat {
+        url: Some(WString::from_utf8("http://example.com")),
+        target: Some(WString::from_utf8("_blank")),
+        ..Default::default()
+    };
+
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abc"),
+        &[TextSpan::with_length_and_format(3, Default::default())],
+    );
+    fs.set_default_format(link.clone());
+
+    // Appending at the end uses the default format, link included.
+    fs.replace_text(3, 3, WStr::from_units(b"Z"), None);
+    assert_eq!(WStr::from_units(b"abcZ"), fs.text());
+    assert_eq!(link.url, fs.get_text_format(3, 4).url);
+    assert_eq!(link.target, fs.get_text_format(3, 4).target);
+
+    // So does replacing the tail, where there's no following span either.
+    fs.replace_text(1, 4, WStr::from_units(b"Y"), None);
+    assert_eq!(WStr::from_units(b"aY"), fs.text());
+    assert_eq!(link.url, fs.get_text_format(1, 2).url);
+    assert_eq!(link.target, fs.get_text_format(1, 2).target);
+}
+
+#[test]
+fn formatspans_normalize_empty_text() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcdef"),
+        &[
+            TextSpan::with_length_and_format(3, bold.clone()),
+            TextSpan::with_length_and_format(3, Default::default()),
+        ],
+    );
+
+    fs.replace_text(2, 6, WStr::from_units(b""), None);
+    fs.replace_text(0, 2, WStr::from_units(b""), None);
+
+    assert_eq!(WStr::from_units(b""), fs.text());
+    assert_eq!(1, fs.iter_spans().count());
+    assert!(fs.span(1).is_none());
+
+    let span = fs.span(0).unwrap();
+    assert_eq!(0, span.span_length);
+    // The remaining span carries the default format, not that of the
+    // deleted text.
+    assert!(!span.bold);
+
+    // Formatting the empty field must not add or remove spans.
+    fs.set_text_format(0, 0, &bold);
+    assert_eq!(1, fs.iter_spans().count());
+    assert_eq!(0, fs.span(0).unwrap().span_length);
+    assert!(fs.span(1).is_none());
+}
+
+#[test]
+fn formatspans_normalize_empty_text_then_type() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abc"),
+        &[TextSpan::with_length_and_format(3, Default::default())],
+    );
+
+    fs.replace_text(0, 3, WStr::from_units(b""), None);
+    fs.set_default_format(bold.clone());
+    fs.replace_text(0, 0, WStr::from_units(b"xyz"), None);
+
+    assert_eq!(WStr::from_units(b"xyz"), fs.text());
+    assert_eq!(3, fs.span(0).unwrap().span_length);
+    assert!(fs.span(1).is_none());
+    assert_eq!(Some(true), fs.get_text_format(0, 3).bold);
+}
+
+#[test]
+fn formatspans_replace_text_caret() {
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcdef"),
+        &[TextSpan::with_length_and_format(6, Default::default())],
+    );
+
+    // Insertion, replacement and deletion.
+    assert_eq!(4, fs.replace_text(3, 3, WStr::from_units(b"Z"), None));
+    assert_eq!(WStr::from_units(b"abcZdef"), fs.text());
+    assert_eq!(3, fs.replace_text(1, 4, WStr::from_units(b"XY"), None));
+    assert_eq!(WStr::from_units(b"aXYdef"), fs.text());
+    assert_eq!(2, fs.replace_text(2, 4, WStr::empty(), None));
+    assert_eq!(WStr::from_units(b"aXef"), fs.text());
+
+    // Positions are in UTF-16 code units, so characters outside the BMP
+    // advance the caret by two.
+    let with = WString::from_utf8("é😀");
+    assert_eq!(with.len(), 3);
+    assert_eq!(1 + with.len(), fs.replace_text(1, 1, &with, None));
+    assert_eq!(WString::from_utf8("aé😀Xef"), fs.text());
+
+    // Inserting past the end appends to the text.
+    let len = fs.text().len();
+    assert_eq!(
+        len + 3,
+        fs.replace_text(len + 10, len + 10, WStr::from_units(b"end"), None)
+    );
+    assert_eq!(WString::from_utf8("aé😀Xefend"), fs.text());
+
+    // Degenerate ranges leave the caret where it was.
+    assert_eq!(5, fs.replace_text(5, 2, WStr::from_units(b"Q"), None));
+    assert_eq!(WString::from_utf8("aé😀Xefend"), fs.text());
+}
+
+/// The text format used by `formatspans_char_boundaries` tests.
+fn char_boundaries_format() -> TextFormat {
+    TextFormat {
+        font: Some(WString::from_utf8("Mono")),
+        size: Some(12.0),
+        letter_spacing: Some(0.0),
+        kerning: Some(false),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn formatspans_char_boundaries() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let spaced = TextFormat {
+            letter_spacing: Some(2.0),
+            ..char_boundaries_format()
+        };
+        let fs = FormatSpans::from_str_and_spans(
+            WStr::from_units(b"aaaaaa"),
+            &[
+                TextSpan::with_length_and_format(4, char_boundaries_format()),
+                TextSpan::with_length_and_format(2, spaced),
+            ],
+        );
+
+        let bounds = Twips::from_pixels(1000.0);
+        let rects: Vec<Rectangle<Twips>> = (0..6)
+            .map(|i| {
+                fs.char_boundaries(context, movie.clone(), bounds, false, false, i)
+                    .expect("char bounds")
+            })
+            .collect();
+
+        let width = rects[0].x_max - rects[0].x_min;
+        assert!(width > Twips::ZERO);
+
+        for pair in rects.windows(2) {
+            assert_eq!(pair[0].x_max, pair[1].x_min);
+            assert_eq!(pair[0].y_min, pair[1].y_min);
+        }
+        for rect in &rects[..4] {
+            assert_eq!(width, rect.x_max - rect.x_min);
+        }
+        for rect in &rects[4..] {
+            assert_eq!(width + Twips::from_pixels(2.0), rect.x_max - rect.x_min);
+        }
+
+        assert_eq!(
+            None,
+            fs.char_boundaries(context, movie.clone(), bounds, false, false, 6)
+        );
+    });
+}
+
+#[test]
+fn formatspans_char_boundaries_wrap() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let fs = FormatSpans::from_str_and_spans(
+            WStr::from_units(b"aaaa aaaa"),
+            &[TextSpan::with_length_and_format(
+                9,
+                char_boundaries_format(),
+            )],
+        );
+
+        let first = fs
+            .char_boundaries(
+                context,
+                movie.clone(),
+                Twips::from_pixels(1000.0),
+                true,
+                false,
+                0,
+            )
+            .expect("char bounds");
+        let width = first.x_max - first.x_min;
+
+        // Only one word fits on each line.
+        let bounds = width * 6;
+        let rects: Vec<Option<Rectangle<Twips>>> = (0..9)
+            .map(|i| fs.char_boundaries(context, movie.clone(), bounds, true, false, i))
+            .collect();
+
+        let line1 = rects[0].clone().expect("char bounds");
+        let line2 = rects[5].clone().expect("char bounds");
+        assert_eq!(line1.x_min, line2.x_min);
+        assert!(line2.y_min > line1.y_min);
+
+        for (i, rect) in rects.iter().enumerate().take(4).skip(1) {
+            let rect = rect.clone().expect("char bounds");
+            assert_eq!(line1.y_min, rect.y_min);
+            assert_eq!(line1.x_min + width * i as i32, rect.x_min);
+        }
+        for (i, rect) in rects.iter().enumerate().skip(6) {
+            let rect = rect.clone().expect("char bounds");
+            assert_eq!(line2.y_min, rect.y_min);
+            assert_eq!(line2.x_min + width * (i - 5) as i32, rect.x_min);
+        }
+    });
+}
+
+#[test]
+fn layout_indent_first_line_only() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        // The start of each character with the given indents, wrapping so
+        // that only one word fits on each line.
+        let mut line_starts = |indent: f64, block_indent: f64| {
+            let fs = FormatSpans::from_str_and_spans(
+                WStr::from_units(b"aaaa aaaa\naaaa"),
+                &[TextSpan::with_length_and_format(
+                    14,
+                    TextFormat {
+                        indent: Some(indent),
+                        block_indent: Some(block_indent),
+                        ..char_boundaries_format()
+                    },
+                )],
+            );
+            let first = fs
+                .char_boundaries(
+                    context,
+                    movie.clone(),
+                    Twips::from_pixels(1000.0),
+                    false,
+                    false,
+                    0,
+                )
+                .expect("char bounds");
+            let width = first.x_max - first.x_min;
+            let bounds = width * 6 + Twips::from_pixels(indent + block_indent);
+            [0, 5, 10].map(|i| {
+                fs.char_boundaries(context, movie.clone(), bounds, true, false, i)
+                    .expect("char bounds")
+            })
+        };
+
+        let plain = line_starts(0.0, 0.0);
+        let indented = line_starts(10.0, 20.0);
+
+        // Each character starts its own line.
+        assert!(plain[1].y_min > plain[0].y_min);
+        assert!(plain[2].y_min > plain[1].y_min);
+
+        // The first line of each paragraph gets both indents; lines wrapped
+        // within a paragraph only get the block indent.
+        assert_eq!(indented[0].x_min - plain[0].x_min, Twips::from_pixels(30.0));
+        assert_eq!(indented[1].x_min - plain[1].x_min, Twips::from_pixels(20.0));
+        assert_eq!(indented[2].x_min - plain[2].x_min, Twips::from_pixels(30.0));
+    });
+}
+
+#[test]
+fn formatspans_wrap_width_relayout() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let mut fs = FormatSpans::from_str_and_spans(
+            WStr::from_units(b"aaaa aaaa aaaa\naaaa"),
+            &[TextSpan::with_length_and_format(
+                19,
+                char_boundaries_format(),
+            )],
+        );
+        let char_bounds = fs
+            .char_boundaries(
+                context,
+                movie.clone(),
+                Twips::from_pixels(1000.0),
+                false,
+                false,
+                0,
+            )
+            .expect("char bounds");
+        let char_width = char_bounds.x_max - char_bounds.x_min;
+
+        // Count the distinct lines that the text was laid out on.
+        let mut line_count = |fs: &mut FormatSpans| {
+            let (layout, _) = fs.layout(context, movie.clone(), Twips::ZERO, false);
+            let mut lines: Vec<Twips> = layout.iter().map(|b| b.bounds().offset_y()).collect();
+            lines.dedup();
+            lines.len()
+        };
+
+        assert!(fs.is_layout_dirty());
+        fs.set_wrap_width(Some(char_width * 6));
+        let narrow = line_count(&mut fs);
+        assert!(!fs.is_layout_dirty());
+        assert_eq!(4, narrow);
+
+        fs.set_wrap_width(Some(char_width * 6));
+        assert!(!fs.is_layout_dirty());
+
+        fs.set_wrap_width(Some(char_width * 100));
+        assert!(fs.is_layout_dirty());
+        let wide = line_count(&mut fs);
+        assert!(wide < narrow);
+        assert_eq!(2, wide);
+
+        fs.set_wrap_width(None);
+        assert!(fs.is_layout_dirty());
+        assert_eq!(2, line_count(&mut fs));
+    });
+}
+
+#[test]
+fn formatspans_lines() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let mut fs = FormatSpans::from_str_and_spans(
+            WStr::from_units(b"aaaa aaaa\naa"),
+            &[TextSpan::with_length_and_format(
+                12,
+                char_boundaries_format(),
+            )],
+        );
+        let char_bounds = fs
+            .char_boundaries(
+                context,
+                movie.clone(),
+                Twips::from_pixels(1000.0),
+                false,
+                false,
+                0,
+            )
+            .expect("char bounds");
+        let char_width = char_bounds.x_max - char_bounds.x_min;
+
+        // Without wrapping, only the hard line break splits lines.
+        assert_eq!(2, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(
+            Some(WString::from_utf8("aaaa aaaa")),
+            fs.line_text(context, movie.clone(), false, 0)
+        );
+        assert_eq!(
+            Some(WString::from_utf8("aa")),
+            fs.line_text(context, movie.clone(), false, 1)
+        );
+
+        fs.set_wrap_width(Some(char_width * 6));
+        assert_eq!(3, fs.num_lines(context, movie.clone(), false));
+        let lines: Vec<_> = (0..4)
+            .map(|line| fs.line_text(context, movie.clone(), false, line))
+            .collect();
+        assert_eq!(
+            vec![
+                Some(WString::from_utf8("aaaa ")),
+                Some(WString::from_utf8("aaaa")),
+                Some(WString::from_utf8("aa")),
+                None,
+            ],
+            lines
+        );
+    });
+}
+
+#[test]
+fn formatspans_line_cache() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let mut fs = FormatSpans::from_str_and_spans(
+            WStr::from_units(b"aaaa aaaa\naa"),
+            &[TextSpan::with_length_and_format(
+                12,
+                char_boundaries_format(),
+            )],
+        );
+        let hash = fs.content_hash();
+
+        // Laying the text out caches its lines, and repeated queries of
+        // unchanged content reuse them.
+        assert_eq!(0, fs.line_layout_count());
+        fs.layout(context, movie.clone(), Twips::ZERO, false);
+        assert_eq!(1, fs.line_layout_count());
+        assert_eq!(2, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(
+            Some(WString::from_utf8("aa")),
+            fs.line_text(context, movie.clone(), false, 1)
+        );
+        assert_eq!(2, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(1, fs.line_layout_count());
+
+        // Setting the same width again changes nothing.
+        fs.set_wrap_width(None);
+        assert_eq!(2, fs.num_lines(context, movie.clone(), false));
+        assert!(fs
+            .layout_if_changed(context, movie.clone(), Twips::ZERO, false)
+            .is_none());
+        assert_eq!(1, fs.line_layout_count());
+
+        // Changing the width lays the text out again, once.
+        fs.set_wrap_width(Some(Twips::from_pixels(1.0)));
+        let wrapped = fs.num_lines(context, movie.clone(), false);
+        assert!(wrapped > 2);
+        assert_eq!(wrapped, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(2, fs.line_layout_count());
+        fs.set_wrap_width(None);
+
+        // So does editing the text or its formatting.
+        fs.replace_text(12, 12, WStr::from_units(b"\na"), None);
+        assert_ne!(hash, fs.content_hash());
+        assert_eq!(3, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(3, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(3, fs.line_layout_count());
+
+        let hash = fs.content_hash();
+        fs.set_text_format(
+            0,
+            1,
+            &TextFormat {
+                size: Some(20.0),
+                ..Default::default()
+            },
+        );
+        assert_ne!(hash, fs.content_hash());
+        assert_eq!(3, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(4, fs.line_layout_count());
+
+        // Fonts resolved from another movie's library lay out differently.
+        let other_movie = Arc::new(SwfMovie::empty(8));
+        fs.num_lines(context, other_movie.clone(), false);
+        fs.num_lines(context, other_movie, false);
+        assert_eq!(5, fs.line_layout_count());
+        assert_eq!(3, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(6, fs.line_layout_count());
+
+        // Lines found without a full layout don't stand in for one.
+        assert!(fs
+            .layout_if_changed(context, movie.clone(), Twips::ZERO, false)
+            .is_some());
+        assert_eq!(7, fs.line_layout_count());
+    });
+}
+
+#[test]
+fn layout_default_tab_stops() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        // Measure how far a tab moves the character after it.
+        let mut tab_offset = |size: f64| {
+            let fs = FormatSpans::from_str_and_spans(
+                WStr::from_units(b"a\ta"),
+                &[TextSpan::with_length_and_format(
+                    3,
+                    TextFormat {
+                        size: Some(size),
+                        ..char_boundaries_format()
+                    },
+                )],
+            );
+            let bounds = Twips::from_pixels(1000.0);
+            let before = fs
+                .char_boundaries(context, movie.clone(), bounds, false, false, 0)
+                .expect("char bounds");
+            let after = fs
+                .char_boundaries(context, movie.clone(), bounds, false, false, 2)
+                .expect("char bounds");
+            after.x_min - before.x_min
+        };
+
+        let small = tab_offset(12.0);
+        let large = tab_offset(24.0);
+        assert_eq!(Twips::from_pixels(12.0 * 2.7), small);
+        assert_eq!(Twips::from_pixels(24.0 * 2.7), large);
+        assert_eq!(small + small, large);
+    });
+}
+
+#[test]
+fn layout_leading() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        // Measure the height of laid-out text with the given leading.
+        let mut height = |text: &[u8], leading: f64| {
+            let fs = FormatSpans::from_str_and_spans(
+                WStr::from_units(text),
+                &[TextSpan::with_length_and_format(
+                    text.len(),
+                    TextFormat {
+                        leading: Some(leading),
+                        ..char_boundaries_format()
+                    },
+                )],
+            );
+            let (_, bounds) = LayoutBox::lower_from_text_spans(
+                &fs,
+                context,
+                movie.clone(),
+                Twips::from_pixels(1000.0),
+                false,
+                false,
+            );
+            bounds.height()
+        };
+
+        // Leading goes between consecutive lines, but not below the last one.
+        let paragraph = b"aaa\naaa\naaa";
+        assert_eq!(
+            height(paragraph, 5.0) - height(paragraph, 0.0),
+            Twips::from_pixels(10.0)
+        );
+        assert_eq!(
+            height(paragraph, 10.0) - height(paragraph, 0.0),
+            Twips::from_pixels(20.0)
+        );
+
+        // A lone line still gets one count of leading.
+        assert_eq!(
+            height(b"aaa", 5.0) - height(b"aaa", 0.0),
+            Twips::from_pixels(5.0)
+        );
+    });
+}
+
+#[test]
+fn formatspans_paragraphs() {
+    let paragraphs = |text: &[u8]| {
+        FormatSpans::from_text(WStr::from_units(text).into(), Default::default())
+            .paragraphs()
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(vec![0..1, 2..3, 4..4], paragraphs(b"a\nb\n"));
+    assert_eq!(vec![0..3], paragraphs(b"abc"));
+    assert_eq!(vec![0..0, 1..1], paragraphs(b"\n"));
+    assert_eq!(vec![0..0], paragraphs(b""));
+}
+
+#[test]
+fn formatspans_set_paragraph_format() {
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abc\ndef\nghi"),
+        &[TextSpan::with_length_and_format(11, Default::default())],
+    );
+
+    // Setting `align` on one character applies to its whole paragraph, while
+    // character-level properties stay local.
+    fs.set_text_format(
+        5,
+        6,
+        &TextFormat {
+            align: Some(swf::TextAlign::Right),
+            bold: Some(true),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(Some(swf::TextAlign::Left), fs.get_text_format(0, 4).align);
+    assert_eq!(Some(swf::TextAlign::Right), fs.get_text_format(4, 8).align);
+    assert_eq!(Some(swf::TextAlign::Left), fs.get_text_format(8, 11).align);
+    assert_eq!(Some(false), fs.get_text_format(4, 5).bold);
+    assert_eq!(Some(true), fs.get_text_format(5, 6).bold);
+    assert_eq!(Some(false), fs.get_text_format(6, 8).bold);
+
+    // A range spanning a line break touches both paragraphs.
+    fs.set_text_format(
+        2,
+        5,
+        &TextFormat {
+            indent: Some(10.0),
+            ..Default::default()
+        },
+    );
+    assert_eq!(Some(10.0), fs.get_text_format(0, 8).indent);
+    assert_eq!(Some(0.0), fs.get_text_format(8, 11).indent);
+}
+
+#[test]
+fn layout_paragraph_align() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let mut fs = FormatSpans::from_str_and_spans(
+            WStr::from_units(b"aaa\naaa\naaa"),
+            &[TextSpan::with_length_and_format(
+                11,
+                TextFormat {
+                    align: Some(swf::TextAlign::Left),
+                    ..char_boundaries_format()
+                },
+            )],
+        );
+        fs.set_text_format(
+            5,
+            6,
+            &TextFormat {
+                align: Some(swf::TextAlign::Right),
+                ..Default::default()
+            },
+        );
+
+        let bounds = Twips::from_pixels(1000.0);
+        let mut x_min = |index: usize| {
+            fs.char_boundaries(context, movie.clone(), bounds, false, false, index)
+                .expect("char bounds")
+                .x_min
+        };
+
+        // The whole middle line moves to the right, not just the one character.
+        let left = x_min(0);
+        assert!(x_min(4) > left);
+        assert_eq!(x_min(5) - x_min(4), x_min(1) - left);
+        assert_eq!(left, x_min(8));
+    });
+}
+
+#[test]
+fn formatspans_from_source_modes() {
+    let source = WStr::from_units(b"<b>x</b>");
+
+    let html = FormatSpans::from_source(source, Default::default(), true, false);
+    assert_eq!(WStr::from_units(b"x"), html.text());
+    assert_eq!(Some(true), html.get_text_format(0, 1).bold);
+
+    let plain = FormatSpans::from_source(source, Default::default(), false, false);
+    assert_eq!(source, plain.text());
+    assert_eq!(Some(false), plain.get_text_format(0, 8).bold);
+}
+
+#[test]
+fn formatspans_from_html_bare_ampersand() {
+    let fs = FormatSpans::from_html(WStr::from_units(b"Tom & Jerry"), Default::default(), false);
+    assert_eq!(WStr::from_units(b"Tom & Jerry"), fs.text());
+
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<b>R&D</b> &amp; Tom &lt;3 &"),
+        Default::default(),
+        false,
+    );
+    assert_eq!(WStr::from_units(b"R&D & Tom <3 &"), fs.text());
+    assert_eq!(Some(true), fs.get_text_format(0, 3).bold);
+    assert_eq!(Some(false), fs.get_text_format(3, 14).bold);
+}
+
+#[test]
+fn formatspans_from_html_unterminated_entity() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"a &b c; d &unknown; e"),
+        Default::default(),
+        false,
+    );
+    assert_eq!(WStr::from_units(b"a &b c; d &unknown; e"), fs.text());
+}
+
+#[test]
+fn formatspans_from_html_nested_formats() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<b>Hello <i>world</i></b>"),
+        Default::default(),
+        false,
+    );
+    assert_eq!(WStr::from_units(b"Hello world"), fs.text());
+    assert_eq!(
+        fs.format_runs()
+            .into_iter()
+            .map(|(range, tf)| (range, tf.bold, tf.italic))
+            .collect::<Vec<_>>(),
+        vec![
+            (0..6, Some(true), Some(false)),
+            (6..11, Some(true), Some(true))
+        ]
+    );
+
+    // Inner tags override the formats of outer ones, which apply again
+    // after the inner tag closes.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<font color=\"#FF0000\">r<font color=\"#00FF00\">g</font>r</font>"),
+        Default::default(),
+        false,
+    );
+    assert_eq!(WStr::from_units(b"rgr"), fs.text());
+    assert_eq!(
+        fs.format_runs()
+            .into_iter()
+            .map(|(range, tf)| (range, tf.color.unwrap().to_rgb()))
+            .collect::<Vec<_>>(),
+        vec![(0..1, 0xFF0000), (1..2, 0x00FF00), (2..3, 0xFF0000)]
+    );
+}
+
+#[test]
+fn formatspans_from_html_font_sizes() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(
+            b"<font size=\"16\">a<font size=\"+2\">b<font size=\"-5\">c</font></font><font size=\"20\">d</font></font>",
+        ),
+        Default::default(),
+        true,
+    );
+    assert_eq!(WStr::from_units(b"abcd"), fs.text());
+    assert_eq!(Some(16.0), fs.get_text_format(0, 1).size);
+    assert_eq!(Some(18.0), fs.get_text_format(1, 2).size);
+    assert_eq!(Some(13.0), fs.get_text_format(2, 3).size);
+    assert_eq!(Some(20.0), fs.get_text_format(3, 4).size);
+
+    // Relative sizes resolve against the default format outside of any tag,
+    // and are ignored when there is no size to resolve them against.
+    let default_format = TextFormat {
+        size: Some(12.0),
+        ..Default::default()
+    };
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<font size=\"+3\">a</font>"),
+        default_format,
+        true,
+    );
+    assert_eq!(Some(15.0), fs.get_text_format(0, 1).size);
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<font size=\"+3\">a</font>"),
+        Default::default(),
+        true,
+    );
+    let plain = FormatSpans::from_html(WStr::from_units(b"a"), Default::default(), true);
+    assert_eq!(
+        plain.get_text_format(0, 1).size,
+        fs.get_text_format(0, 1).size
+    );
+}
+
+#[test]
+fn formatspans_from_html_named_colors() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(
+            b"<font color=\"red\">r</font><font color=\"#00FF00\">g</font><font color=\"LIME\">l</font>",
+        ),
+        Default::default(),
+        true,
+    );
+    assert_eq!(
+        Some(swf::Color::from_rgb(0xFF0000, 255)),
+        fs.get_text_format(0, 1).color
+    );
+    assert_eq!(
+        Some(swf::Color::from_rgb(0x00FF00, 255)),
+        fs.get_text_format(1, 2).color
+    );
+    assert_eq!(
+        Some(swf::Color::from_rgb(0x00FF00, 255)),
+        fs.get_text_format(2, 3).color
+    );
+
+    // An unknown name is ignored, leaving the enclosing color in place.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<font color=\"#0000FF\">a<font color=\"bluish\">b</font></font>"),
+        Default::default(),
+        true,
+    );
+    assert_eq!(
+        Some(swf::Color::from_rgb(0x0000FF, 255)),
+        fs.get_text_format(1, 2).color
+    );
+
+    assert_eq!(
+        Some(swf::Color::from_rgb(0x800080, 255)),
+        parse_html_color(WStr::from_units(b"Purple"))
+    );
+    assert_eq!(None, parse_html_color(WStr::from_units(b"bluish")));
+    assert_eq!(None, parse_html_color(WStr::from_units(b"")));
+}
+
+#[test]
+fn formatspans_from_html_list_item_lines() {
+    /// Whether each character of `fs` is bulleted.
+    fn bullets(fs: &FormatSpans) -> Vec<bool> {
+        fs.iter_spans()
+            .flat_map(|(start, end, _, span)| std::iter::repeat(span.bullet).take(end - start))
+            .collect()
+    }
+
+    // An item starts a line of its own, and every span on its lines is
+    // bulleted, down to the line breaks ending them.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"x<b>y</b><li>a<font size=\"20\">b</font><br>c</li>z"),
+        Default::default(),
+        true,
+    );
+    assert_eq!(WStr::from_units(b"xy\nab\nc\nz"), fs.text());
+    assert_eq!(
+        vec![false, false, false, true, true, true, true, true, false],
+        bullets(&fs)
+    );
+
+    // Nested items don't leave an empty line behind when their enclosing item
+    // ends.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<ul><li>a<ul><li>b</li></ul></li><li>c</li></ul>"),
+        Default::default(),
+        true,
+    );
+    assert_eq!(WStr::from_units(b"a\nb\nc\n"), fs.text());
+    assert!(bullets(&fs).into_iter().all(|bullet| bullet));
+
+    // Numbered items nested in a bulleted one aren't bulleted, while text
+    // after them is part of the enclosing item.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<ul><li>a<ol><li>b</li></ol>c</li></ul>"),
+        Default::default(),
+        true,
+    );
+    assert_eq!(WStr::from_units(b"a\n1. b\nc\n"), fs.text());
+    assert_eq!(
+        vec![true, true, false, false, false, false, false, true, true],
+        bullets(&fs)
+    );
+}
+
+#[test]
+fn formatspans_from_html_line_break_format() {
+    let runs = |html: &[u8]| {
+        FormatSpans::from_html(WStr::from_units(html), Default::default(), true)
+            .format_runs()
+            .into_iter()
+            .map(|(range, tf)| (range, tf.bold, tf.italic))
+            .collect::<Vec<_>>()
+    };
+
+    // A line break between two styled spans ends the one before it.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<b>bold</b><br/><i>italic</i>"),
+        Default::default(),
+        true,
+    );
+    assert_eq!(WStr::from_units(b"bold\nitalic"), fs.text());
+    assert_eq!(
+        runs(b"<b>bold</b><br/><i>italic</i>"),
+        vec![
+            (0..5, Some(true), Some(false)),
+            (5..11, Some(false), Some(true))
+        ]
+    );
+
+    // Inside a styled span, it keeps that style.
+    assert_eq!(
+        runs(b"<b>a<br/>b</b>"),
+        vec![(0..3, Some(true), Some(false))]
+    );
+
+    // At the start of the text, it takes the format of the enclosing tags.
+    assert_eq!(
+        runs(b"<i><br/>x</i>y"),
+        vec![
+            (0..2, Some(false), Some(true)),
+            (2..3, Some(false), Some(false))
+        ]
+    );
+    assert_eq!(
+        runs(b"<b><p></p></b>x"),
+        vec![
+            (0..1, Some(true), Some(false)),
+            (1..2, Some(false), Some(false))
+        ]
+    );
+}
+
+#[test]
+fn formatspans_line_endings() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let fs = FormatSpans::from_text(
+            WString::from_utf8("aa\r\na\raaa\na"),
+            char_boundaries_format(),
+        );
+        assert_eq!(WStr::from_units(b"aa\na\naaa\na"), fs.text());
+        assert_eq!(10, fs.span(0).unwrap().span_length);
+        assert_eq!(4, fs.num_lines(context, movie.clone(), false));
+        assert_eq!(
+            Some(WString::from_utf8("a")),
+            fs.line_text(context, movie.clone(), false, 1)
+        );
+        assert_eq!(
+            WString::from_utf8("aa\r\na\r\naaa\r\na"),
+            fs.text_with_line_ending()
+        );
+
+        let mut fs = FormatSpans::from_text(WString::from_utf8("aa"), char_boundaries_format());
+        assert_eq!(4, fs.replace_text(1, 1, WStr::from_units(b"\ra\r"), None));
+        assert_eq!(WStr::from_units(b"a\na\na"), fs.text());
+        assert_eq!(WString::from_utf8("a\ra\ra"), fs.text_with_line_ending());
+        assert_eq!(3, fs.num_lines(context, movie.clone(), false));
+
+        let fs = FormatSpans::from_html(
+            WStr::from_units(b"<p>a\r\na</p>"),
+            char_boundaries_format(),
+            true,
+        );
+        assert_eq!(WStr::from_units(b"a\na\n"), fs.text());
+        assert_eq!(WString::from_utf8("a\r\na\r\n"), fs.text_with_line_ending());
+    });
+}
+
+#[test]
+fn formatspans_format_runs() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcdefgh"),
+        &[
+            TextSpan::with_length_and_format(2, bold.clone()),
+            TextSpan::with_length_and_format(2, Default::default()),
+            TextSpan::with_length_and_format(0, bold.clone()),
+            TextSpan::with_length_and_format(2, Default::default()),
+            TextSpan::with_length_and_format(2, bold),
+        ],
+    );
+
+    let runs = fs.format_runs();
+    assert_eq!(
+        runs.iter()
+            .map(|(range, _)| range.clone())
+            .collect::<Vec<_>>(),
+        vec![0..2, 2..6, 6..8]
+    );
+    assert_eq!(
+        runs.iter().map(|(_, tf)| tf.bold).collect::<Vec<_>>(),
+        vec![Some(true), Some(false), Some(true)]
+    );
+    for (_, tf) in &runs {
+        assert!(tf.font.is_some());
+        assert!(tf.size.is_some());
+        assert!(tf.url.is_some());
+    }
+
+    let fs = FormatSpans::from_str_and_spans(WStr::empty(), &[]);
+    assert!(fs.format_runs().is_empty());
+}
+
+#[test]
+fn formatspans_format_before_typing() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let italic = TextFormat {
+        italic: Some(true),
+        ..Default::default()
+    };
+
+    // Typing a character over the selection, as `EditText::text_input` does.
+    let type_char = |fs: &mut FormatSpans, from: usize, to: usize| {
+        let format = fs.format_before(from);
+        fs.replace_text(from, to, WStr::from_units(b"x"), Some(&format))
+    };
+
+    // Empty text has no character to take the format from.
+    let mut fs = FormatSpans::from_text(WString::new(), italic.clone());
+    assert_eq!(1, type_char(&mut fs, 0, 0));
+    assert_eq!(Some(true), fs.get_text_format(0, 1).italic);
+
+    let mut fs = FormatSpans::from_text(WString::from_utf8("abcd"), italic);
+    fs.set_text_format(
+        0,
+        4,
+        &TextFormat {
+            italic: Some(false),
+            ..Default::default()
+        },
+    );
+    fs.set_text_format(2, 4, &bold);
+
+    // Typed text takes the format of the character before the caret, rather
+    // than that of the span after it. At the start, the default format applies.
+    for (pos, is_bold, is_italic) in [
+        (0, false, true),
+        (2, false, false),
+        (3, true, false),
+        (4, true, false),
+    ] {
+        assert_eq!(pos + 1, type_char(&mut fs, pos, pos));
+        let format = fs.get_text_format(pos, pos + 1);
+        assert_eq!(Some(is_bold), format.bold, "{}", pos);
+        assert_eq!(Some(is_italic), format.italic, "{}", pos);
+        fs.replace_text(pos, pos + 1, WStr::empty(), None);
+    }
+
+    // Typing over a selection takes the format of the character before it,
+    // not that of the selected text.
+    assert_eq!(3, type_char(&mut fs, 2, 4));
+    assert_eq!(WStr::from_units(b"abx"), fs.text());
+    assert_eq!(Some(false), fs.get_text_format(2, 3).bold);
+}
+
+#[test]
+fn formatspans_utf16_positions() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let mut fs = FormatSpans::from_text(WString::from_utf8("café™ ok"), Default::default());
+
+    // Positions are UTF-16 code units, so `é` is the single unit at 3 and `™`
+    // the single unit at 4, even though both are multibyte in UTF-8.
+    assert_eq!(8, fs.text().len());
+    fs.set_text_format(3, 4, &bold);
+
+    let bold_spans: Vec<_> = fs
+        .iter_spans()
+        .filter(|(_, _, _, span)| span.bold)
+        .map(|(start, end, text, _)| (start, end, text.to_owned()))
+        .collect();
+    assert_eq!(1, bold_spans.len());
+    assert_eq!((3, 4), (bold_spans[0].0, bold_spans[0].1));
+    assert_eq!(WString::from_utf8("é"), bold_spans[0].2);
+    assert_eq!(Some(true), fs.get_text_format(3, 4).bold);
+    assert_eq!(Some(false), fs.get_text_format(4, 5).bold);
+
+    fs.replace_text(4, 5, &WString::from_utf8("€"), None);
+    assert_eq!(WString::from_utf8("café€ ok"), fs.text());
+    assert_eq!(Some(true), fs.get_text_format(3, 4).bold);
+}
+
+#[test]
+fn formatspans_format_before() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let default_format = TextFormat {
+        size: Some(20.0),
+        ..Default::default()
+    };
+    let mut fs = FormatSpans::from_text(WString::from_utf8("abcd"), default_format);
+    fs.set_text_format(
+        0,
+        4,
+        &TextFormat {
+            size: Some(12.0),
+            bold: Some(false),
+            ..Default::default()
+        },
+    );
+    fs.set_text_format(1, 2, &bold);
+
+    // A caret after the bold `b` types bold text; one after a plain character
+    // types plain text.
+    assert_eq!(Some(true), fs.format_before(2).bold);
+    assert_eq!(Some(false), fs.format_before(1).bold);
+    assert_eq!(Some(false), fs.format_before(3).bold);
+    assert_eq!(Some(false), fs.format_before(4).bold);
+    assert_eq!(Some(12.0), fs.format_before(4).size);
+    assert_eq!(Some(12.0), fs.format_before(100).size);
+
+    // At the start, there is no character before the caret.
+    assert_eq!(None, fs.format_before(0).bold);
+    assert_eq!(Some(20.0), fs.format_before(0).size);
+}
+
+#[test]
+fn formatspans_ordered_lists() {
+    fn labels(fs: &FormatSpans) -> Vec<String> {
+        fs.iter_spans()
+            .map(|(_, _, _, span)| span.bullet_label().to_string())
+            .collect()
+    }
+
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<ol><li>one</li><li>two</li><li>three</li></ol>"),
+        Default::default(),
+        true,
+    );
+    assert_eq!(WStr::from_units(b"one\ntwo\nthree\n"), fs.text());
+    assert_eq!(vec!["1. ", "2. ", "3. "], labels(&fs));
+    assert!(fs.iter_spans().all(|(_, _, _, span)| span.bullet));
+    assert_eq!(Some(2), fs.get_text_format(4, 7).list_number);
+
+    // Each list restarts its numbering, and unordered lists keep bullets,
+    // even inside of ordered ones.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(
+            b"<ol><li>a</li><li>b</li></ol><ol><li>c</li><ul><li>d</li></ul><li>e</li></ol>",
+        ),
+        Default::default(),
+        true,
+    );
+    assert_eq!(vec!["1. ", "2. ", "1. ", "\u{2022}", "2. "], labels(&fs));
+
+    // Without multiline, lists aren't formatted at all.
+    let fs = FormatSpans::from_html(
+        WStr::from_units(b"<ol><li>one</li><li>two</li></ol>"),
+        Default::default(),
+        false,
+    );
+    assert_eq!(WStr::from_units(b"onetwo"), fs.text());
+}
+
+#[test]
+fn layout_ordered_list_numbers() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Mono", swf::FontFlag::empty());
+
+        let mut fs = FormatSpans::from_html(
+            WStr::from_units(b"<ol><li>a</li><li>aa</li></ol>"),
+            char_boundaries_format(),
+            true,
+        );
+        assert_eq!(WStr::from_units(b"a\naa\n"), fs.text());
+
+        // The numbers are laid out like bullets, in front of each item.
+        let (layout, _) = fs.layout(context, movie, Twips::ZERO, false);
+        let (bullets, text_boxes): (Vec<_>, Vec<_>) =
+            layout.iter().partition(|layout_box| layout_box.is_bullet());
+        let labels: Vec<_> = bullets
+            .iter()
+            .map(|bullet| bullet.as_renderable_text(fs.text()).unwrap().0.to_owned())
+            .collect();
+        assert_eq!(
+            vec![WString::from_utf8("1. "), WString::from_utf8("2. ")],
+            labels
+        );
+        for bullet in &bullets {
+            for text_box in &text_boxes {
+                assert!(bullet.bounds().offset_x() < text_box.bounds().offset_x());
+            }
+        }
+    });
+}
+
+#[test]
+fn formatspans_to_html_nesting() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let italic = TextFormat {
+        italic: Some(true),
+        ..Default::default()
+    };
+    let bold_italic = TextFormat {
+        bold: Some(true),
+        italic: Some(true),
+        ..Default::default()
+    };
+    let fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abc"),
+        &[
+            TextSpan::with_length_and_format(1, bold),
+            TextSpan::with_length_and_format(1, bold_italic),
+            TextSpan::with_length_and_format(1, italic),
+        ],
+    );
+
+    let html = fs.to_html().to_string();
+    assert_well_nested(&html);
+    assert!(html.contains("<B>a<I>b</I></B><I>c</I></FONT>"), "{}", html);
+
+    let parsed = FormatSpans::from_html(&fs.to_html(), Default::default(), true);
+    assert_eq!(WStr::from_units(b"abc"), parsed.text());
+    for (i, (bold, italic)) in [(true, false), (true, true), (false, true)]
+        .into_iter()
+        .enumerate()
+    {
+        let format = parsed.get_text_format(i, i + 1);
+        assert_eq!(Some(bold), format.bold);
+        assert_eq!(Some(italic), format.italic);
+    }
+}
+
+#[test]
+fn formatspans_to_html_round_trip() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(
+            b"<p align=\"center\"><font face=\"Mono\" size=\"14\" color=\"#ff0000\">1 &lt; 2 &amp;&amp; <b>bold</b></font></p>\
+<p><i>it</i><br><u>under</u> <a href=\"http://example.com\" target=\"_blank\">link</a></p>",
+        ),
+        Default::default(),
+        true,
+    );
+    assert_eq!(
+        WStr::from_units(b"1 < 2 && bold\nit\nunder link\n"),
+        fs.text()
+    );
+
+    let html = fs.to_html();
+    let html_string = html.to_string();
+    assert_well_nested(&html_string);
+    assert!(
+        html_string.contains("1 &lt; 2 &amp;&amp; "),
+        "{}",
+        html_string
+    );
+
+    // Lowering the serialized markup again gives the same text and formatting.
+    let parsed = FormatSpans::from_html(&html, Default::default(), true);
+    assert_eq!(fs.text(), parsed.text());
+    assert_eq!(html, parsed.to_html());
+
+    let format = parsed.get_text_format(0, 1);
+    assert_eq!(Some(swf::TextAlign::Center), format.align);
+    assert_eq!(Some(WString::from_utf8("Mono")), format.font);
+    assert_eq!(Some(14.0), format.size);
+    assert_eq!(Some(swf::Color::from_rgb(0xFF0000, 255)), format.color);
+    assert_eq!(Some(true), parsed.get_text_format(9, 13).bold);
+    assert_eq!(Some(true), parsed.get_text_format(14, 16).italic);
+    assert_eq!(Some(true), parsed.get_text_format(17, 22).underline);
+    let link = parsed.get_text_format(23, 27);
+    assert_eq!(Some(WString::from_utf8("http://example.com")), link.url);
+    assert_eq!(Some(WString::from_utf8("_blank")), link.target);
+}
+
+#[test]
+fn formatspans_to_html_font_nesting() {
+    let format = |bold, size| TextFormat {
+        bold: Some(bold),
+        size: Some(size),
+        ..Default::default()
+    };
+    let fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcd\ne"),
+        &[
+            TextSpan::with_length_and_format(1, format(true, 12.0)),
+            TextSpan::with_length_and_format(1, format(true, 20.0)),
+            TextSpan::with_length_and_format(1, format(false, 20.0)),
+            TextSpan::with_length_and_format(2, format(false, 12.0)),
+            TextSpan::with_length_and_format(1, format(true, 12.0)),
+        ],
+    );
+
+    let html = fs.to_html().to_string();
+    assert_well_nested(&html);
+    assert!(
+        html.contains("<B>a<FONT SIZE=\"20\">b</FONT></B><FONT SIZE=\"20\">c</FONT>d</FONT></P>"),
+        "{}",
+        html
+    );
+
+    let parsed = FormatSpans::from_html(&fs.to_html(), Default::default(), true);
+    assert_eq!(WStr::from_units(b"abcd\ne\n"), parsed.text());
+    for (i, (bold, size)) in [(true, 12.0), (true, 20.0), (false, 20.0), (false, 12.0)]
+        .into_iter()
+        .enumerate()
+    {
+        let format = parsed.get_text_format(i, i + 1);
+        assert_eq!(Some(bold), format.bold);
+        assert_eq!(Some(size), format.size);
+    }
+    assert_eq!(Some(true), parsed.get_text_format(5, 6).bold);
+}
+
+#[test]
+fn formatspans_large_field_edits() {
+    // A small xorshift generator, so that the edits are reproducible.
+    let mut state: u32 = 0x9E37_79B9;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % bound
+    };
+
+    let format = |bold| TextFormat {
+        bold: Some(bold),
+        ..Default::default()
+    };
+
+    // The field starts out as 50k characters in alternating runs of 100 bold
+    // and 100 regular characters. A plain per-character model of the text
+    // and its boldness is kept alongside as the reference.
+    let mut model: Vec<(u16, bool)> = (0..50_000)
+        .map(|i| (u16::from(b'a') + (i % 26) as u16, i / 100 % 2 == 0))
+        .collect();
+    let text: WString = model.iter().map(|(c, _)| *c).collect();
+    let spans: Vec<TextSpan> = (0..500)
+        .map(|i| TextSpan::with_length_and_format(100, format(i % 2 == 0)))
+        .collect();
+    let mut fs = FormatSpans::from_str_and_spans(&text, &spans);
+
+    for i in 0..2000 {
+        let from = next(model.len() + 1);
+        let to = (from + next(50)).min(model.len());
+        let bold = next(2) == 0;
+
+        if i % 4 == 0 {
+            fs.set_text_format(from, to, &format(bold));
+            for (_, b) in &mut model[from..to] {
+                *b = bold;
+            }
+        } else {
+            let insert = WStr::from_units(b"xyz");
+            let insert = &insert[..next(4)];
+            fs.replace_text(from, to, insert, Some(&format(bold)));
+            model.splice(from..to, insert.iter().map(|c| (c, bold)));
+        }
+    }
+
+    let expected_text: WString = model.iter().map(|(c, _)| *c).collect();
+    assert_eq!(&*expected_text, fs.text());
+
+    let mut position = 0;
+    let mut span_count = 0;
+    for (start, end, _text, span) in fs.iter_spans() {
+        assert_eq!(position, start);
+        assert!(model[start..end].iter().all(|(_, bold)| *bold == span.bold));
+        position = end;
+        span_count += 1;
+    }
+    assert_eq!(model.len(), position);
+
+    // Adjacent spans with the same format have all been merged.
+    let run_count = 1 + model.windows(2).filter(|w| w[0].1 != w[1].1).count();
+    assert_eq!(run_count, span_count);
+}
+
+#[test]
+fn textformat_from_swf_tag_font_style() {
+    with_update_context(|context, movie| {
+        register_test_font(context, movie.clone(), 1, "Bold", swf::FontFlag::IS_BOLD);
+        register_test_font(
+            context,
+            movie.clone(),
+            2,
+            "Italic",
+            swf::FontFlag::IS_ITALIC,
+        );
+
+        fn style(
+            context: &mut UpdateContext<'_, '_, '_>,
+            movie: Arc<SwfMovie>,
+            font_id: swf::CharacterId,
+        ) -> (Option<bool>, Option<bool>, Option<bool>) {
+            let tag = swf::EditText::new().with_font_id(font_id, Twips::from_pixels(12.0));
+            let format = TextFormat::from_swf_tag(tag, movie, context);
+            (format.bold, format.italic, format.underline)
+        }
+
+        let plain = (Some(false), Some(false), Some(false));
+        assert_eq!(
+            (Some(true), Some(false), Some(false)),
+            style(context, movie.clone(), 1)
+        );
+        assert_eq!(
+            (Some(false), Some(true), Some(false)),
+            style(context, movie.clone(), 2)
+        );
+
+        // Without a font to take the style from, the text is plain.
+        assert_eq!(plain, style(context, movie, 3));
+    });
+}
+
+#[test]
+fn textformat_lengths_are_pixels() {
+    with_update_context(|context, movie| {
+        let layout = swf::TextLayout {
+            left_margin: Twips::from_pixels(10.0),
+            indent: Twips::from_pixels(4.0),
+            ..Default::default()
+        };
+        let tag = swf::EditText::new()
+            .with_font_id(0, Twips::from_pixels(20.0))
+            .with_layout(Some(layout));
+        let from_tag = TextFormat::from_swf_tag(tag, movie, context);
+
+        let fs = FormatSpans::from_html(
+            WStr::from_units(b"<textformat leftmargin=\"10\" indent=\"4\"><font size=\"20\">a</font></textformat>"),
+            Default::default(),
+            true,
+        );
+        let from_html = fs.get_text_format(0, 1);
+
+        assert_eq!(Some(20.0), from_tag.size);
+        assert_eq!(from_tag.size, from_html.size);
+        assert_eq!(Some(10.0), from_tag.left_margin);
+        assert_eq!(from_tag.left_margin, from_html.left_margin);
+        assert_eq!(Some(4.0), from_tag.indent);
+        assert_eq!(from_tag.indent, from_html.indent);
+
+        // Serializing keeps the same units.
+        let html = fs.to_html().to_string();
+        assert!(html.contains("LEFTMARGIN=\"10\""), "{}", html);
+        assert!(html.contains("INDENT=\"4\""), "{}", html);
+        assert!(html.contains("SIZE=\"20\""), "{}", html);
+    });
+}
+
+#[test]
+fn formatspans_from_html_typed_attributes() {
+    let fs = FormatSpans::from_html(
+        WStr::from_units(
+            b"<textformat leftmargin=\" 10 \" leading=\"2px\" tabstops=\"1, 2,x\"><font kerning=\"true\" letterspacing=\"1.5\">a</font><font kerning=\"yes\">b</font></textformat>",
+        ),
+        Default::default(),
+        true,
+    );
+
+    let a = fs.get_text_format(0, 1);
+    assert_eq!(Some(10.0), a.left_margin);
+    assert_eq!(Some(0.0), a.leading);
+    assert_eq!(Some(vec![1.0, 2.0]), a.tab_stops);
+    assert_eq!(Some(true), a.kerning);
+    assert_eq!(Some(1.5), a.letter_spacing);
+
+    // Values that aren't booleans leave the property as it was.
+    let b = fs.get_text_format(1, 2);
+    assert_eq!(Some(false), b.kerning);
+}
+
+/// Describe the spans of `fs`, for comparing two sets of format spans.
+fn describe_spans(fs: &FormatSpans) -> Vec<(usize, String)> {
+    (0..)
+        .map_while(|i| fs.span(i))
+        .map(|span| (span.span_length, format!("{span:?}")))
+        .collect()
+}
+
+#[test]
+fn formatspans_normalize_idempotent() {
+    let plain = TextFormat::default;
+    let bold = || TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let italic = || TextFormat {
+        italic: Some(true),
+        ..Default::default()
+    };
+    let span = TextSpan::with_length_and_format;
+
+    let cases: Vec<(&[u8], Vec<TextSpan>)> = vec![
+        (b"", vec![]),
+        (b"", vec![span(0, bold()), span(0, italic())]),
+        (b"", vec![span(3, bold())]),
+        (b"abcdef", vec![]),
+        (b"abcdef", vec![span(0, bold())]),
+        (
+            b"abcdef",
+            vec![span(0, bold()), span(0, italic()), span(6, plain())],
+        ),
+        (
+            b"abcdef",
+            vec![span(0, bold()), span(3, italic()), span(3, bold())],
+        ),
+        (
+            b"abcdef",
+            vec![span(3, bold()), span(0, italic()), span(3, bold())],
+        ),
+        (
+            b"abcdef",
+            vec![
+                span(2, bold()),
+                span(0, italic()),
+                span(0, plain()),
+                span(4, bold()),
+            ],
+        ),
+        (
+            b"abcdef",
+            vec![span(3, bold()), span(3, italic()), span(0, plain())],
+        ),
+        (
+            b"abcdef",
+            vec![
+                span(1, bold()),
+                span(1, bold()),
+                span(1, bold()),
+                span(3, bold()),
+            ],
+        ),
+        (
+            b"abcdef",
+            vec![span(2, plain()), span(2, bold()), span(2, plain())],
+        ),
+        // Spans that are too short, leaving a remainder that merges with the
+        // last span.
+        (b"abcdef", vec![span(2, bold()), span(2, plain())]),
+        (b"abcdef", vec![span(2, plain()), span(0, bold())]),
+        // Spans that are too long, with whole spans to drop from the end.
+        (
+            b"abcdef",
+            vec![span(4, bold()), span(4, bold()), span(4, italic())],
+        ),
+        (
+            b"abcdef",
+            vec![span(6, bold()), span(0, italic()), span(5, bold())],
+        ),
+        (
+            b"abcdef",
+            vec![span(3, bold()), span(3, italic()), span(2, bold())],
+        ),
+        (b"abcdef", vec![span(0, plain()), span(10, bold())]),
+    ];
+
+    for (text, spans) in cases {
+        let mut fs = FormatSpans::from_str_and_spans(WStr::from_units(text), &spans);
+        fs.normalize();
+        let once = describe_spans(&fs);
+        fs.normalize();
+        assert_eq!(once, describe_spans(&fs), "{:?}", WStr::from_units(text));
+
+        // The normalized spans uphold the invariants that make the second
+        // pass a no-op.
+        assert_eq!(text.len(), once.iter().map(|(len, _)| len).sum::<usize>());
+        if !text.is_empty() {
+            assert!(once.iter().all(|(len, _)| *len > 0));
+        }
+        for i in 1..once.len() {
+            assert!(!fs.span(i - 1).unwrap().can_merge(fs.span(i).unwrap()));
+        }
+    }
+
+    // A small xorshift generator, so that the inputs are reproducible.
+    let mut state: u32 = 0x9E37_79B9;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % bound
+    };
+    let formats = [plain(), bold(), italic()];
+    for _ in 0..1000 {
+        let len = next(8);
+        let text = WString::from_utf8(&"abcdefgh"[..len]);
+        let spans: Vec<_> = (0..next(6))
+            .map(|_| span(next(4), formats[next(formats.len())].clone()))
+            .collect();
+
+        let mut fs = FormatSpans::from_str_and_spans(&text, &spans);
+        fs.normalize();
+        let once = describe_spans(&fs);
+        fs.normalize();
+        assert_eq!(once, describe_spans(&fs), "{:?} {:?}", text, spans);
+    }
+}
+
+#[test]
+fn formatspans_normalize_range_matches_normalize() {
+    // A small xorshift generator, so that the edits are reproducible.
+    let mut state: u32 = 0x2545_F491;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % bound
+    };
+
+    let formats = [
+        TextFormat::default(),
+        TextFormat {
+            bold: Some(true),
+            ..Default::default()
+        },
+        TextFormat {
+            bold: Some(false),
+            ..Default::default()
+        },
+        TextFormat {
+            size: Some(20.0),
+            ..Default::default()
+        },
+    ];
+
+    for _ in 0..1000 {
+        let len = next(12);
+        let text = WString::from_utf8(&"abcdefghijklmnopqrstuvwxyz"[..len]);
+
+        let mut spans = Vec::new();
+        let mut covered = 0;
+        while covered < len {
+            let span_length = 1 + next(len - covered);
+            let format = formats[next(formats.len())].clone();
+            spans.push(TextSpan::with_length_and_format(span_length, format));
+            covered += span_length;
+        }
+
+        let mut fs = FormatSpans::from_str_and_spans(&text, &spans);
+        fs.normalize();
+        let before = describe_spans(&fs);
+
+        let from = next(len + 2);
+        let to = from + next(len + 3 - from);
+        let format = &formats[next(formats.len())];
+        if next(2) == 0 {
+            fs.set_text_format(from, to, format);
+        } else {
+            let with = WString::from_utf8(&"XYZ"[..next(4)]);
+            let new_tf = if next(2) == 0 { Some(format) } else { None };
+            fs.replace_text(from, to, &with, new_tf);
+        }
+
+        let mut expected = fs.clone();
+        expected.normalize();
+        assert_eq!(
+            describe_spans(&expected),
+            describe_spans(&fs),
+            "edit of [{from}, {to}) over {before:?}"
+        );
+    }
+}
+
+/// Check the invariants documented on `FormatSpans::normalize`, and that the
+/// text matches `reference`.
+fn assert_format_spans_invariants(fs: &FormatSpans, reference: &str, context: &str) {
+    assert_eq!(
+        WStr::from_units(reference.as_bytes()),
+        fs.text(),
+        "text mismatch {}",
+        context
+    );
+
+    let spans: Vec<&TextSpan> = (0..).map_while(|i| fs.span(i)).collect();
+    let total: usize = spans.iter().map(|span| span.span_length).sum();
+    assert_eq!(reference.len(), total, "span length mismatch {}", context);
+
+    if reference.is_empty() {
+        assert_eq!(1, spans.len(), "empty text needs one span {}", context);
+        return;
+    }
+
+    for (i, span) in spans.iter().enumerate() {
+        assert_ne!(0, span.span_length, "null-length span {} {}", i, context);
+    }
+    for (i, pair) in spans.windows(2).enumerate() {
+        assert!(
+            !pair[0].can_merge(pair[1]),
+            "mergeable spans {} and {} {}",
+            i,
+            i + 1,
+            context
+        );
+    }
+}
+
+/// Apply `steps` random `set_text_format`/`replace_text`/deletion operations
+/// to a `FormatSpans`, checking its invariants after every step. The seed
+/// makes any failure reproducible.
+fn fuzz_format_spans(seed: u64, steps: usize) {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let formats = [
+        TextFormat::default(),
+        TextFormat {
+            bold: Some(true),
+            ..Default::default()
+        },
+        TextFormat {
+            italic: Some(true),
+            ..Default::default()
+        },
+        TextFormat {
+            color: Some(swf::Color::from_rgb(0xFF0000, 255)),
+            ..Default::default()
+        },
+    ];
+
+    let mut reference = String::from("abcdefgh");
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(reference.as_bytes()),
+        &[TextSpan::with_length_and_format(
+            reference.len(),
+            Default::default(),
+        )],
+    );
+
+    for step in 0..steps {
+        let len = reference.len();
+        let from = rng.gen_range(0..=len);
+        let to = rng.gen_range(from..=len);
+        let format = &formats[rng.gen_range(0..formats.len())];
+
+        let operation = match rng.gen_range(0..3) {
+            0 => {
+                fs.set_text_format(from, to, format);
+                "set_text_format"
+            }
+            1 => {
+                let count = rng.gen_range(1..=4);
+                let insert: String = (0..count)
+                    .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                    .collect();
+                let new_tf = if rng.gen() { Some(format) } else { None };
+                let caret = fs.replace_text(from, to, WStr::from_units(insert.as_bytes()), new_tf);
+                reference.replace_range(from..to, &insert);
+                assert_eq!(from + insert.len(), caret);
+                "replace_text"
+            }
+            _ => {
+                fs.replace_text(from, to, WStr::empty(), None);
+                reference.replace_range(from..to, "");
+                "delete"
+            }
+        };
+
+        let context = format!(
+            "after {}({}, {}) at step {} of seed {}",
+            operation, from, to, step, seed
+        );
+        assert_format_spans_invariants(&fs, &reference, &context);
+    }
+}
+
+#[test]
+fn formatspans_fuzz_invariants() {
+    for seed in [1, 2, 3, 42, 1234, 0xDEADBEEF] {
+        fuzz_format_spans(seed, 200);
+    }
+}
+
+#[test]
+fn formatspans_insert_at_start() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abcdef"),
+        &[
+            TextSpan::with_length_and_format(3, bold),
+            TextSpan::with_length_and_format(3, Default::default()),
+        ],
+    );
+
+    // Inserting at the very start leaves the first span's text intact.
+    assert_eq!(2, fs.replace_text(0, 0, WStr::from_units(b"xy"), None));
+    assert_format_spans_invariants(&fs, "xyabcdef", "after inserting at the start");
+    assert_eq!(2, fs.span(0).unwrap().span_length);
+    assert_eq!(3, fs.span(1).unwrap().span_length);
+    assert!(fs.span(1).unwrap().bold);
+}
+
+#[test]
+fn formatspans_insert_at_start_uses_default_format() {
+    let bold = TextFormat {
+        bold: Some(true),
+        ..Default::default()
+    };
+    let mut fs = FormatSpans::from_str_and_spans(
+        WStr::from_units(b"abc"),
+        &[TextSpan::with_length_and_format(3, bold)],
+    );
+    fs.set_default_format(TextFormat {
+        size: Some(30.0),
+        bold: Some(false),
+        ..Default::default()
+    });
+    assert_eq!(Some(30.0), fs.start_format().size);
+
+    // There's no preceding text to inherit from, so the inserted text takes
+    // the format for new text rather than that of the first span.
+    fs.replace_text(0, 0, WStr::from_units(b"xy"), None);
+    assert_eq!(WStr::from_units(b"xyabc"), fs.text());
+    assert_eq!(Some(false), fs.get_text_format(0, 2).bold);
+    assert_eq!(Some(30.0), fs.get_text_format(0, 2).size);
+    assert_eq!(Some(true), fs.get_text_format(2, 5).bold);
+
+    // An explicit format still takes precedence.
+    let italic = TextFormat {
+        italic: Some(true),
+        ..Default::default()
+    };
+    fs.replace_text(0, 0, WStr::from_units(b"z"), Some(&italic));
+    assert_eq!(Some(true), fs.get_text_format(0, 1).italic);
+}
+
+#[test]
+fn formatspans_from_html_with_options() {
+    let from_html = |html: &[u8], options| {
+        FormatSpans::from_html_with_options(
+            WStr::from_units(html),
+            TextFormat::default(),
+            false,
+            options,
+        )
+    };
+
+    let fs = from_html(b"<p>a  \t b</p> ", ParseOptions::html());
+    assert_eq!(WStr::from_units(b"a  \t b "), fs.text());
+
+    let condense_white = ParseOptions {
+        condense_white: true,
+        ..ParseOptions::html()
+    };
+    let fs = from_html(b"<p>a  \t b</p> ", condense_white);
+    assert_eq!(WStr::from_units(b"a b "), fs.text());
+
+    let ignore_white = ParseOptions {
+        ignore_white: true,
+        ..ParseOptions::html()
+    };
+    let fs = from_html(b"<p>a  \t b</p> ", ignore_white);
+    assert_eq!(WStr::from_units(b"a  \t b"), fs.text());
+
+    // Markup past the limits is discarded.
+    let max_depth = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::html()
+    };
+    let fs = from_html(b"<b>a<i>b<u>c</u></i>d</b>e", max_depth);
+    assert_eq!(WStr::from_units(b"ab"), fs.text());
+
+    // Line breaks are never closed, but don't count towards the depth.
+    let fs = from_html(b"<b>a<br>b<br/>c<br>d</b>e", max_depth);
+    assert_eq!(WStr::from_units(b"abcde"), fs.text());
+
+    let max_nodes = ParseOptions {
+        max_nodes: 3,
+        ..ParseOptions::html()
+    };
+    let fs = from_html(b"<b>a</b><i>b</i>c", max_nodes);
+    assert_eq!(WStr::from_units(b"a"), fs.text());
+}
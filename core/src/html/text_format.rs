@@ -1,20 +1,33 @@
 //! Classes that store formatting options
 
 use crate::context::UpdateContext;
+use crate::font::{DeviceFont, Font, FontDescriptor};
+use crate::html::dimensions::BoxBounds;
 use crate::html::iterators::TextSpanIter;
+use crate::html::layout::{LayoutBox, LayoutContent};
 use crate::string::{Integer, Units, WStr, WString};
 use crate::tag_utils::SwfMovie;
+use crate::xml::{attribute_as_bool, attribute_as_f64, ParseOptions};
 use gc_arena::Collect;
-use quick_xml::{escape::escape, events::Event, Reader};
+use quick_xml::{escape::escape, events::Event};
 use std::borrow::Cow;
-use std::cmp::{min, Ordering};
-use std::collections::VecDeque;
+#[cfg(test)]
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::cmp::{max, min, Ordering};
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Write;
-use std::sync::Arc;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, Range};
+use std::rc::Rc;
+use std::sync::{Arc, Weak};
+use swf::{Rectangle, Twips};
 
 /// Replace HTML entities with their equivalent characters.
 ///
-/// Unknown entities will be ignored.
+/// Unknown entities will be ignored. Like Flash, this is lenient: an `&` that
+/// does not start a valid entity (e.g. `Tom & Jerry`) is kept as a literal
+/// `&` rather than being treated as an error.
 fn process_html_entity(src: &WStr) -> Option<WString> {
     let amp_index = match src.find(b'&') {
         Some(i) => i,
@@ -98,6 +111,87 @@ fn process_html_entity(src: &WStr) -> Option<WString> {
     Some(result_str)
 }
 
+/// Parse a color in the `#RRGGBB` form used by HTML `<font color>` attributes.
+///
+/// Returns `None` if the string is not in that form. The color is always
+/// opaque.
+pub fn parse_hex_color(color: &WStr) -> Option<swf::Color> {
+    if !color.starts_with(b'#') {
+        return None;
+    }
+
+    let component = |range: std::ops::Range<usize>| {
+        color
+            .slice(range)
+            .and_then(|v| u8::from_wstr_radix(v, 16).ok())
+    };
+    let r = component(1..3)?;
+    let g = component(3..5)?;
+    let b = component(5..7)?;
+    Some(swf::Color { r, g, b, a: 255 })
+}
+
+/// Parse the `size` attribute of an HTML `<font>` tag.
+///
+/// A size with a leading sign, such as `+2` or `-1`, is relative to the size
+/// of the enclosing text, given as `enclosing`; it is ignored if that size is
+/// unknown. Any other size is an absolute point size. Invalid sizes yield
+/// `None`.
+fn parse_font_size(size: &WStr, enclosing: Option<f64>) -> Option<f64> {
+    let is_relative = size.starts_with(b'+') || size.starts_with(b'-');
+    let size: f64 = size.parse().ok()?;
+    if is_relative {
+        enclosing.map(|enclosing| enclosing + size)
+    } else {
+        Some(size)
+    }
+}
+
+/// The 16 basic color names of HTML 4 and CSS, with their RGB values.
+const NAMED_COLORS: [(&[u8], u32); 16] = [
+    (b"black", 0x000000),
+    (b"silver", 0xC0C0C0),
+    (b"gray", 0x808080),
+    (b"white", 0xFFFFFF),
+    (b"maroon", 0x800000),
+    (b"red", 0xFF0000),
+    (b"purple", 0x800080),
+    (b"fuchsia", 0xFF00FF),
+    (b"green", 0x008000),
+    (b"lime", 0x00FF00),
+    (b"olive", 0x808000),
+    (b"yellow", 0xFFFF00),
+    (b"navy", 0x000080),
+    (b"blue", 0x0000FF),
+    (b"teal", 0x008080),
+    (b"aqua", 0x00FFFF),
+];
+
+/// Parse a color given either as one of the basic HTML color names (matched
+/// case-insensitively) or in the `#RRGGBB` form.
+///
+/// Returns `None` for anything else. The color is always opaque.
+pub fn parse_html_color(color: &WStr) -> Option<swf::Color> {
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| color.eq_ignore_case(WStr::from_units(*name)))
+        .map(|(_, rgb)| swf::Color::from_rgb(*rgb, 255))
+        .or_else(|| parse_hex_color(color))
+}
+
+/// Split an inline `style` attribute into its CSS declarations.
+///
+/// Declarations are separated by `;`, and each one is a property name and a
+/// value separated by `:`. Anything without a `:` is skipped.
+fn parse_style_declarations(style: &WStr) -> Vec<(String, String)> {
+    style
+        .to_utf8_lossy()
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .map(|(property, value)| (property.to_string(), value.to_string()))
+        .collect()
+}
+
 /// A set of text formatting options to be applied to some part, or the whole
 /// of, a given text field.
 ///
@@ -107,6 +201,9 @@ fn process_html_entity(src: &WStr) -> Option<WString> {
 /// means that multiple regions of text apply. When setting the format of a
 /// particular region of text, `None` means that the existing setting for that
 /// property will be retained.
+///
+/// All lengths (`size`, margins, indents, `leading`, `letter_spacing` and
+/// `tab_stops`) are in pixels, however the format was constructed.
 #[derive(Clone, Debug, Collect, Default)]
 #[collect(require_static)]
 pub struct TextFormat {
@@ -126,6 +223,7 @@ pub struct TextFormat {
     pub letter_spacing: Option<f64>,
     pub tab_stops: Option<Vec<f64>>,
     pub bullet: Option<bool>,
+    pub list_number: Option<u32>,
     pub url: Option<WString>,
     pub target: Option<WString>,
 }
@@ -162,7 +260,7 @@ impl TextFormat {
             size: et.height().map(|h| h.to_pixels()),
             color: et
                 .color()
-                .map(|color| swf::Color::from_rgb(color.to_rgb(), 0)),
+                .map(|color| swf::Color::from_rgb(color.to_rgb(), 255)),
             align,
             bold: Some(font.map(|font| font.descriptor().bold()).unwrap_or(false)),
             italic: Some(font.map(|font| font.descriptor().italic()).unwrap_or(false)),
@@ -174,8 +272,10 @@ impl TextFormat {
             kerning: Some(false),
             leading,
             letter_spacing: Some(0.0), // TODO: This isn't specified by the tag itself
-            tab_stops: Some(vec![]),   // TODO: Are there default tab stops?
-            bullet: Some(false),       // TODO: Default tab stops?
+            // TODO: Default tab stops? Layout falls back to a default grid.
+            tab_stops: Some(vec![]),
+            bullet: Some(false),
+            list_number: None,
 
             // TODO: These are probably empty strings by default
             url: Some(WString::new()),
@@ -183,6 +283,81 @@ impl TextFormat {
         }
     }
 
+    /// Construct a `TextFormat` from a list of CSS declarations, as found in a
+    /// stylesheet rule or an inline `style` attribute.
+    ///
+    /// Each declaration is a property name and its value. Properties that do
+    /// not map onto a text format, or values that cannot be parsed, are
+    /// ignored and leave the corresponding property undefined. Later
+    /// declarations override earlier ones.
+    ///
+    /// `display` and `direction` are accepted but have no text format
+    /// equivalent; they control the layout of the element itself.
+    pub fn from_css_declarations(decls: &[(String, String)]) -> Self {
+        let mut format = Self::default();
+
+        for (property, value) in decls {
+            let property = property.trim().to_ascii_lowercase();
+            let value = value.trim();
+            let keyword = value.to_ascii_lowercase();
+            let length = || {
+                value
+                    .strip_suffix("px")
+                    .unwrap_or(value)
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+            };
+
+            match property.as_str() {
+                "color" => {
+                    if let Some(color) = parse_html_color(&WString::from_utf8(value)) {
+                        format.color = Some(color);
+                    }
+                }
+                "font-family" => format.font = Some(WString::from_utf8(value)),
+                "font-size" => format.size = length().or(format.size),
+                "font-weight" => match keyword.as_str() {
+                    "bold" => format.bold = Some(true),
+                    "normal" => format.bold = Some(false),
+                    _ => {}
+                },
+                "font-style" => match keyword.as_str() {
+                    "italic" => format.italic = Some(true),
+                    "normal" => format.italic = Some(false),
+                    _ => {}
+                },
+                "text-align" => match keyword.as_str() {
+                    "left" => format.align = Some(swf::TextAlign::Left),
+                    "center" => format.align = Some(swf::TextAlign::Center),
+                    "right" => format.align = Some(swf::TextAlign::Right),
+                    "justify" => format.align = Some(swf::TextAlign::Justify),
+                    _ => {}
+                },
+                "text-decoration" => match keyword.as_str() {
+                    "underline" => format.underline = Some(true),
+                    "none" => format.underline = Some(false),
+                    _ => {}
+                },
+                "text-indent" => format.indent = length().or(format.indent),
+                "letter-spacing" => format.letter_spacing = length().or(format.letter_spacing),
+                "kerning" => match keyword.as_str() {
+                    "true" => format.kerning = Some(true),
+                    "false" => format.kerning = Some(false),
+                    _ => {}
+                },
+                "leading" => format.leading = length().or(format.leading),
+                "margin-left" => format.left_margin = length().or(format.left_margin),
+                "margin-right" => format.right_margin = length().or(format.right_margin),
+                // TODO: Support these once elements can be laid out as blocks.
+                "display" | "direction" => {}
+                _ => {}
+            }
+        }
+
+        format
+    }
+
     /// Given two text formats, construct a new `TextFormat` where only
     /// matching properties between the two formats are defined.
     pub fn merge_matching_properties(self, rhs: TextFormat) -> Self {
@@ -267,6 +442,11 @@ impl TextFormat {
             } else {
                 None
             },
+            list_number: if self.list_number == rhs.list_number {
+                self.list_number
+            } else {
+                None
+            },
             url: if self.url == rhs.url { self.url } else { None },
             target: if self.target == rhs.target {
                 self.target
@@ -298,10 +478,102 @@ impl TextFormat {
             letter_spacing: self.letter_spacing.or(rhs.letter_spacing),
             tab_stops: self.tab_stops.or(rhs.tab_stops),
             bullet: self.bullet.or(rhs.bullet),
+            list_number: self.list_number.or(rhs.list_number),
             url: self.url.or(rhs.url),
             target: self.target.or(rhs.target),
         }
     }
+
+    /// Construct a new `TextFormat` with only the paragraph-level properties
+    /// of this one, or `None` if it defines none of them.
+    ///
+    /// Paragraph-level properties apply to whole paragraphs at a time, while
+    /// all other properties apply to individual characters.
+    pub fn paragraph_format(&self) -> Option<Self> {
+        let format = Self {
+            align: self.align,
+            left_margin: self.left_margin,
+            right_margin: self.right_margin,
+            indent: self.indent,
+            block_indent: self.block_indent,
+            leading: self.leading,
+            tab_stops: self.tab_stops.clone(),
+            bullet: self.bullet,
+            list_number: self.list_number,
+            ..Default::default()
+        };
+
+        let is_empty = format.align.is_none()
+            && format.left_margin.is_none()
+            && format.right_margin.is_none()
+            && format.indent.is_none()
+            && format.block_indent.is_none()
+            && format.leading.is_none()
+            && format.tab_stops.is_none()
+            && format.bullet.is_none()
+            && format.list_number.is_none();
+        if is_empty {
+            None
+        } else {
+            Some(format)
+        }
+    }
+}
+
+/// A line break convention.
+///
+/// `FormatSpans` always stores line breaks as `\n`, but remembers which
+/// convention its text was given in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+
+    /// `\r`
+    Cr,
+
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    /// The units making up a line break in this convention.
+    pub fn as_wstr(self) -> &'static WStr {
+        match self {
+            Self::Lf => WStr::from_units(b"\n"),
+            Self::Cr => WStr::from_units(b"\r"),
+            Self::CrLf => WStr::from_units(b"\r\n"),
+        }
+    }
+
+    /// Replace every line break in `text` (`\r\n`, `\r` or `\n`) with a single
+    /// `\n`.
+    ///
+    /// Also yields the convention of the first line break found, if any.
+    pub fn normalize(text: &WStr) -> (WString, Option<Self>) {
+        let mut result = WString::with_capacity(text.len(), text.is_wide());
+        let mut line_ending = None;
+        let mut units = text.iter().peekable();
+
+        while let Some(c) = units.next() {
+            if c == u16::from(b'\r') {
+                let found = if units.next_if_eq(&u16::from(b'\n')).is_some() {
+                    Self::CrLf
+                } else {
+                    Self::Cr
+                };
+                line_ending.get_or_insert(found);
+                result.push_byte(b'\n');
+            } else {
+                if c == u16::from(b'\n') {
+                    line_ending.get_or_insert(Self::Lf);
+                }
+                result.push(c);
+            }
+        }
+
+        (result, line_ending)
+    }
 }
 
 /// Represents the application of a `TextFormat` to a particular text span.
@@ -311,8 +583,9 @@ impl TextFormat {
 /// within the string is implicit in the sum of all previous text span's
 /// lengths. See `TextSpans` for more information.
 ///
-/// This struct also contains a resolved version of the `TextFormat` structure
-/// listed above.
+/// The resolved format of a `TextSpan` is shared behind an `Rc`, so that
+/// splitting or copying spans is cheap; it is only cloned once one of the
+/// copies has its format changed.
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
 pub struct TextSpan {
@@ -322,6 +595,15 @@ pub struct TextSpan {
     /// length of the underlying source string.
     pub span_length: usize,
 
+    format: Rc<TextSpanFormat>,
+}
+
+/// The formatting applied to a `TextSpan`.
+///
+/// This is a resolved version of the `TextFormat` structure listed above,
+/// with every property defined.
+#[derive(Clone, Debug)]
+pub struct TextSpanFormat {
     pub font: WString,
     pub size: f64,
     pub color: swf::Color,
@@ -338,14 +620,18 @@ pub struct TextSpan {
     pub letter_spacing: f64,
     pub tab_stops: Vec<f64>,
     pub bullet: bool,
+
+    /// The number of an ordered list item, which labels its bullet instead
+    /// of the bullet character.
+    pub list_number: Option<u32>,
+
     pub url: WString,
     pub target: WString,
 }
 
-impl Default for TextSpan {
+impl Default for TextSpanFormat {
     fn default() -> Self {
         Self {
-            span_length: 0,
             font: WString::new(),
             size: 12.0,
             color: swf::Color {
@@ -367,12 +653,100 @@ impl Default for TextSpan {
             letter_spacing: 0.0,
             tab_stops: vec![],
             bullet: false,
+            list_number: None,
             url: WString::new(),
             target: WString::new(),
         }
     }
 }
 
+/// The bits of a float, as compared and hashed by `TextSpanFormat`.
+///
+/// Both zeroes compare equal, as they do as floats, while any NaN compares
+/// equal to itself so that the comparison is an equivalence.
+fn float_bits(value: f64) -> u64 {
+    if value == 0.0 {
+        0
+    } else {
+        value.to_bits()
+    }
+}
+
+impl PartialEq for TextSpanFormat {
+    fn eq(&self, other: &Self) -> bool {
+        self.font == other.font
+            && float_bits(self.size) == float_bits(other.size)
+            && self.color == other.color
+            && self.align == other.align
+            && self.bold == other.bold
+            && self.italic == other.italic
+            && self.underline == other.underline
+            && float_bits(self.left_margin) == float_bits(other.left_margin)
+            && float_bits(self.right_margin) == float_bits(other.right_margin)
+            && float_bits(self.indent) == float_bits(other.indent)
+            && float_bits(self.block_indent) == float_bits(other.block_indent)
+            && self.kerning == other.kerning
+            && float_bits(self.leading) == float_bits(other.leading)
+            && float_bits(self.letter_spacing) == float_bits(other.letter_spacing)
+            && self.tab_stops.len() == other.tab_stops.len()
+            && self
+                .tab_stops
+                .iter()
+                .zip(&other.tab_stops)
+                .all(|(a, b)| float_bits(*a) == float_bits(*b))
+            && self.bullet == other.bullet
+            && self.list_number == other.list_number
+            && self.url == other.url
+            && self.target == other.target
+    }
+}
+
+impl Eq for TextSpanFormat {}
+
+impl Hash for TextSpanFormat {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.font.hash(state);
+        float_bits(self.size).hash(state);
+        self.color.to_rgba().hash(state);
+        (self.align as u8).hash(state);
+        self.bold.hash(state);
+        self.italic.hash(state);
+        self.underline.hash(state);
+        float_bits(self.left_margin).hash(state);
+        float_bits(self.right_margin).hash(state);
+        float_bits(self.indent).hash(state);
+        float_bits(self.block_indent).hash(state);
+        self.kerning.hash(state);
+        float_bits(self.leading).hash(state);
+        float_bits(self.letter_spacing).hash(state);
+        self.tab_stops.len().hash(state);
+        for tab_stop in &self.tab_stops {
+            float_bits(*tab_stop).hash(state);
+        }
+        self.bullet.hash(state);
+        self.list_number.hash(state);
+        self.url.hash(state);
+        self.target.hash(state);
+    }
+}
+
+impl Default for TextSpan {
+    fn default() -> Self {
+        Self {
+            span_length: 0,
+            format: Default::default(),
+        }
+    }
+}
+
+impl Deref for TextSpan {
+    type Target = TextSpanFormat;
+
+    fn deref(&self) -> &TextSpanFormat {
+        &self.format
+    }
+}
+
 impl TextSpan {
     pub fn with_length_and_format(length: usize, tf: TextFormat) -> Self {
         let mut data = Self {
@@ -385,106 +759,151 @@ impl TextSpan {
         data
     }
 
+    /// Split this span in two at `split_point`, keeping the text before it in
+    /// this span and returning a new span with the same format for the rest.
+    ///
+    /// The start and end of a span are already span breaks, so splitting
+    /// there yields `None` and leaves the span untouched.
+    pub(crate) fn split_at(&mut self, split_point: usize) -> Option<Self> {
+        if split_point == 0 || split_point >= self.span_length {
+            return None;
+        }
+
+        let mut second_span = self.clone();
+        second_span.span_length = self.span_length - split_point;
+        self.span_length = split_point;
+        Some(second_span)
+    }
+
     /// Determine if this and another span have identical text formats.
     ///
     /// It is assumed that the two text spans being considered are adjacent;
     /// and we have no way of checking, so this function doesn't check that.
-    #[allow(clippy::float_cmp)]
-    fn can_merge(&self, rhs: &Self) -> bool {
-        self.font == rhs.font
-            && self.size == rhs.size
-            && self.color == rhs.color
-            && self.align == rhs.align
-            && self.bold == rhs.bold
-            && self.italic == rhs.italic
-            && self.underline == rhs.underline
-            && self.left_margin == rhs.left_margin
-            && self.right_margin == rhs.right_margin
-            && self.indent == rhs.indent
-            && self.block_indent == rhs.block_indent
-            && self.kerning == rhs.kerning
-            && self.leading == rhs.leading
-            && self.letter_spacing == rhs.letter_spacing
-            && self.tab_stops == rhs.tab_stops
-            && self.bullet == rhs.bullet
-            && self.url == rhs.url
-            && self.target == rhs.target
+    pub(crate) fn can_merge(&self, rhs: &Self) -> bool {
+        Rc::ptr_eq(&self.format, &rhs.format) || self.format == rhs.format
     }
 
     /// Apply a text format to this text span.
     ///
     /// Properties marked `None` on the `TextFormat` will remain unchanged.
     fn set_text_format(&mut self, tf: &TextFormat) {
+        let format = Rc::make_mut(&mut self.format);
+
         if let Some(font) = &tf.font {
-            self.font = font.clone();
+            format.font = font.clone();
         }
 
         if let Some(size) = &tf.size {
-            self.size = *size;
+            format.size = *size;
         }
 
         if let Some(color) = &tf.color {
-            self.color = color.clone();
+            format.color = color.clone();
         }
 
         if let Some(align) = &tf.align {
-            self.align = *align;
+            format.align = *align;
         }
 
         if let Some(bold) = &tf.bold {
-            self.bold = *bold;
+            format.bold = *bold;
         }
 
         if let Some(italic) = &tf.italic {
-            self.italic = *italic;
+            format.italic = *italic;
         }
 
         if let Some(underline) = &tf.underline {
-            self.underline = *underline;
+            format.underline = *underline;
         }
 
         if let Some(left_margin) = &tf.left_margin {
-            self.left_margin = *left_margin;
+            format.left_margin = *left_margin;
         }
 
         if let Some(right_margin) = &tf.right_margin {
-            self.right_margin = *right_margin;
+            format.right_margin = *right_margin;
         }
 
         if let Some(indent) = &tf.indent {
-            self.indent = *indent;
+            format.indent = *indent;
         }
 
         if let Some(block_indent) = &tf.block_indent {
-            self.block_indent = *block_indent;
+            format.block_indent = *block_indent;
         }
 
         if let Some(kerning) = &tf.kerning {
-            self.kerning = *kerning;
+            format.kerning = *kerning;
         }
 
         if let Some(leading) = &tf.leading {
-            self.leading = *leading;
+            format.leading = *leading;
         }
 
         if let Some(letter_spacing) = &tf.letter_spacing {
-            self.letter_spacing = *letter_spacing;
+            format.letter_spacing = *letter_spacing;
         }
 
         if let Some(tab_stops) = &tf.tab_stops {
-            self.tab_stops = tab_stops.clone();
+            format.tab_stops = tab_stops.clone();
         }
 
         if let Some(bullet) = &tf.bullet {
-            self.bullet = *bullet;
+            format.bullet = *bullet;
+        }
+
+        if let Some(list_number) = &tf.list_number {
+            format.list_number = Some(*list_number);
         }
 
         if let Some(url) = &tf.url {
-            self.url = url.clone();
+            format.url = url.clone();
         }
 
         if let Some(target) = &tf.target {
-            self.target = target.clone();
+            format.target = target.clone();
+        }
+    }
+
+    /// Resolve the font used to render this span.
+    ///
+    /// The font is looked up by name and style in the library of the given
+    /// movie. If no embedded font with glyphs matches, or if the text is
+    /// rendered with device fonts, the player's device font is used instead.
+    pub fn resolve_font<'gc>(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        movie: Arc<SwfMovie>,
+        is_device_font: bool,
+    ) -> Option<Font<'gc>> {
+        let library = context.library.library_for_movie_mut(movie);
+
+        // Note that the SWF can still contain a DefineFont tag with no glyphs/layout info when
+        // device fonts are requested (see #451).
+        let font = library
+            .get_font_by_name(&self.font.to_utf8_lossy(), self.bold, self.italic)
+            .filter(|f| !is_device_font && f.has_glyphs())
+            .or_else(|| context.library.device_font());
+
+        // Without a loaded device font, fall back to approximate metrics so that
+        // the text can still be measured and wrapped.
+        font.or_else(|| {
+            let kind = DeviceFont::from_name(&self.font).unwrap_or(DeviceFont::Sans);
+            Some(
+                context
+                    .library
+                    .device_font_metrics(context.gc_context, kind),
+            )
+        })
+    }
+
+    /// The text that layout places at the start of each line of a bulleted
+    /// paragraph: a bullet (U+2022), or the number of an ordered list item.
+    pub fn bullet_label(&self) -> WString {
+        match self.list_number {
+            Some(number) => WString::from_utf8(&format!("{}. ", number)),
+            None => WString::from_unit(0x2022),
         }
     }
 
@@ -509,12 +928,53 @@ impl TextSpan {
             letter_spacing: Some(self.letter_spacing),
             tab_stops: Some(self.tab_stops.clone()),
             bullet: Some(self.bullet),
+            list_number: self.list_number,
             url: Some(self.url.clone()),
             target: Some(self.target.clone()),
         }
     }
 }
 
+/// Everything the lines of a `FormatSpans` layout depend on.
+///
+/// Text laid out with equal keys is broken into the same lines, barring
+/// content hash collisions. Only the alignment of the lines can differ,
+/// depending on the width they are aligned within.
+#[derive(Clone, Debug)]
+struct LayoutKey {
+    content_hash: u64,
+    wrap_width: Option<Twips>,
+    is_device_font: bool,
+
+    /// The movie whose library fonts were resolved from.
+    movie: Weak<SwfMovie>,
+
+    /// The font resolved for each span.
+    fonts: Vec<Option<FontDescriptor>>,
+}
+
+impl PartialEq for LayoutKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.content_hash == other.content_hash
+            && self.wrap_width == other.wrap_width
+            && self.is_device_font == other.is_device_font
+            && Weak::ptr_eq(&self.movie, &other.movie)
+            && self.fonts == other.fonts
+    }
+}
+
+/// The lines of a previous layout, along with what they were laid out from.
+#[derive(Clone, Debug)]
+struct LineCache {
+    key: LayoutKey,
+
+    /// The width the lines were aligned within, or `None` if only the lines
+    /// were needed and the layout was thrown away.
+    width: Option<Twips>,
+
+    lines: Vec<Range<usize>>,
+}
+
 /// Struct which contains text formatted by `TextSpan`s.
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
@@ -523,6 +983,24 @@ pub struct FormatSpans {
     displayed_text: WString,
     spans: Vec<TextSpan>,
     default_format: TextFormat,
+
+    /// The width at which text is wrapped by `layout`, or `None` if lines are
+    /// only broken at newlines.
+    wrap_width: Option<Twips>,
+
+    /// Whether the wrap width changed since the text was last laid out.
+    layout_dirty: bool,
+
+    /// The lines of the most recent layout, reused for as long as its layout
+    /// key stays the same.
+    line_cache: RefCell<Option<LineCache>>,
+
+    /// How many times the text has been laid out, for testing the line cache.
+    #[cfg(test)]
+    line_layout_count: Cell<usize>,
+
+    /// The line break convention of the most recently set text.
+    line_ending: LineEnding,
 }
 
 impl Default for FormatSpans {
@@ -533,32 +1011,74 @@ impl Default for FormatSpans {
 
 impl FormatSpans {
     pub fn new() -> Self {
+        Self::from_parts(
+            WString::new(),
+            vec![TextSpan::default()],
+            TextFormat::default(),
+            LineEnding::Lf,
+        )
+    }
+
+    /// Construct a format span that has not been laid out yet.
+    fn from_parts(
+        text: WString,
+        spans: Vec<TextSpan>,
+        default_format: TextFormat,
+        line_ending: LineEnding,
+    ) -> Self {
         Self {
-            text: WString::new(),
+            text,
             displayed_text: WString::new(),
-            spans: vec![TextSpan::default()],
-            default_format: TextFormat::default(),
+            spans,
+            default_format,
+            wrap_width: None,
+            layout_dirty: true,
+            line_cache: RefCell::new(None),
+            #[cfg(test)]
+            line_layout_count: Cell::new(0),
+            line_ending,
         }
     }
 
     /// Construct a format span from its raw parts.
     #[allow(dead_code)]
     pub fn from_str_and_spans(text: &WStr, spans: &[TextSpan]) -> Self {
-        Self {
-            text: text.into(),
-            displayed_text: WString::new(),
-            spans: spans.to_vec(),
-            default_format: Default::default(),
-        }
+        Self::from_parts(
+            text.into(),
+            spans.to_vec(),
+            Default::default(),
+            LineEnding::Lf,
+        )
     }
 
+    /// Construct a format span covering the given text with a single format.
+    ///
+    /// Line breaks in the text are normalized to `\n`.
     pub fn from_text(text: WString, format: TextFormat) -> Self {
+        let (text, line_ending) = LineEnding::normalize(&text);
         let len = text.len();
-        Self {
+        Self::from_parts(
             text,
-            displayed_text: WString::new(),
-            spans: vec![TextSpan::with_length_and_format(len, format.clone())],
-            default_format: format,
+            vec![TextSpan::with_length_and_format(len, format.clone())],
+            format,
+            line_ending.unwrap_or(LineEnding::Lf),
+        )
+    }
+
+    /// Construct a format span from the source text of a text field, which is
+    /// interpreted as HTML in `is_html` mode and as plain text otherwise.
+    ///
+    /// `is_multiline` only matters in HTML mode; see `from_html`.
+    pub fn from_source(
+        text: &WStr,
+        default_format: TextFormat,
+        is_html: bool,
+        is_multiline: bool,
+    ) -> Self {
+        if is_html {
+            Self::from_html(text, default_format, is_multiline)
+        } else {
+            Self::from_text(text.into(), default_format)
         }
     }
 
@@ -568,9 +1088,43 @@ impl FormatSpans {
     /// a handful of presentational attributes in the HTML tree to generate
     /// styling. There's also a `lower_from_css` that respects both
     /// presentational markup and CSS stylesheets.
+    ///
+    /// Unlike strict XML parsing, stray `&` characters that are not part of an
+    /// entity are tolerated and kept in the text as-is. Line breaks in text
+    /// content are normalized to `\n`.
+    ///
+    /// In multiline mode, list items are paragraphs of their own: an `<li>`
+    /// always starts a new line and ends with a line break, and all of the
+    /// text in between, including the final line break, is bulleted. Each line
+    /// of an item with `<br>`s inside gets its own bullet. Items of the
+    /// innermost `<ol>` are numbered instead, even inside a bulleted item, and
+    /// an item ending in a nested list adds no further, empty line.
     pub fn from_html(html: &WStr, default_format: TextFormat, is_multiline: bool) -> Self {
+        Self::from_html_with_options(html, default_format, is_multiline, ParseOptions::html())
+    }
+
+    /// Lower an HTML tree into text-span representation, parsing it with the
+    /// given options.
+    ///
+    /// Whitespace in text is dropped or condensed as the options ask. Once
+    /// the markup nests deeper than `max_depth` or has more than `max_nodes`
+    /// elements and text nodes, the rest of it is discarded.
+    pub fn from_html_with_options(
+        html: &WStr,
+        default_format: TextFormat,
+        is_multiline: bool,
+        options: ParseOptions,
+    ) -> Self {
         let mut format_stack = vec![default_format.clone()];
         let mut text = WString::new();
+        let mut line_ending = None;
+
+        // The lists enclosing the current position: the number of the last
+        // item for each `<ol>`, or `None` for each `<ul>`.
+        let mut list_stack: Vec<Option<u32>> = Vec::new();
+        // The list items enclosing the current position, and whether each
+        // contains nested items.
+        let mut item_stack: Vec<bool> = Vec::new();
         let mut spans: Vec<TextSpan> = Vec::new();
 
         // quick_xml::Reader requires a [u8] slice, but doesn't actually care about Unicode;
@@ -603,16 +1157,38 @@ impl FormatSpans {
         let mut opened_buffer: Vec<u8> = Vec::new();
         let mut opened_starts = Vec::new();
 
-        let mut reader = Reader::from_reader(&raw_bytes[..]);
-        reader.expand_empty_elements(true);
-        reader.check_end_names(false);
+        // Line breaks extend the span before them, so that they share the
+        // format of the text they end. Only at the very start of the text do
+        // they take the format of the enclosing tags instead.
+        fn push_line_break(text: &mut WString, spans: &mut Vec<TextSpan>, format: &TextFormat) {
+            text.push_byte(b'\n');
+            match spans.last_mut() {
+                Some(span) => span.span_length += 1,
+                None => spans.push(TextSpan::with_length_and_format(1, format.clone())),
+            }
+        }
+
+        let mut reader = options.reader(&raw_bytes[..]);
         let mut buf = Vec::new();
+        let mut node_count = 0;
         loop {
             buf.clear();
             match reader.read_event(&mut buf) {
                 Ok(Event::Start(ref e)) => {
-                    opened_starts.push(opened_buffer.len());
-                    opened_buffer.extend(e.name());
+                    // Line breaks have no end tag, so they don't nest.
+                    let is_void = matches!(&e.name().to_ascii_lowercase()[..], b"br" | b"sbr");
+                    if (!is_void && opened_starts.len() >= options.max_depth)
+                        || node_count >= options.max_nodes
+                    {
+                        log::warn!("HTML exceeds the parse limits, discarding the rest");
+                        break;
+                    }
+                    node_count += 1;
+
+                    if !is_void {
+                        opened_starts.push(opened_buffer.len());
+                        opened_buffer.extend(e.name());
+                    }
 
                     let attributes: Result<Vec<_>, _> = e.attributes().with_checks(false).collect();
                     let attributes = match attributes {
@@ -634,10 +1210,7 @@ impl FormatSpans {
                     match &e.name().to_ascii_lowercase()[..] {
                         b"br" => {
                             if is_multiline {
-                                text.push_byte(b'\n');
-                                if let Some(span) = spans.last_mut() {
-                                    span.span_length += 1;
-                                }
+                                push_line_break(&mut text, &mut spans, &format);
                             }
 
                             // Skip push to `format_stack`.
@@ -646,10 +1219,7 @@ impl FormatSpans {
                         b"sbr" => {
                             // TODO: <sbr> tags do not add a newline, but rather only break
                             // the format span.
-                            text.push_byte(b'\n');
-                            if let Some(span) = spans.last_mut() {
-                                span.span_length += 1;
-                            }
+                            push_line_break(&mut text, &mut spans, &format);
 
                             // Skip push to `format_stack`.
                             continue;
@@ -680,37 +1250,23 @@ impl FormatSpans {
                             }
 
                             if let Some(size) = attribute(b"size") {
-                                format.size = size.parse().ok();
+                                format.size = parse_font_size(&size, format.size);
                             }
 
                             if let Some(color) = attribute(b"color") {
-                                if color.starts_with(b'#') {
-                                    let rval = color
-                                        .slice(1..3)
-                                        .and_then(|v| u8::from_wstr_radix(v, 16).ok());
-                                    let gval = color
-                                        .slice(3..5)
-                                        .and_then(|v| u8::from_wstr_radix(v, 16).ok());
-                                    let bval = color
-                                        .slice(5..7)
-                                        .and_then(|v| u8::from_wstr_radix(v, 16).ok());
-
-                                    if let (Some(r), Some(g), Some(b)) = (rval, gval, bval) {
-                                        format.color = Some(swf::Color { r, g, b, a: 0 });
-                                    }
+                                if let Some(color) = parse_html_color(&color) {
+                                    format.color = Some(color);
                                 }
                             }
 
                             if let Some(letter_spacing) = attribute(b"letterSpacing") {
-                                format.letter_spacing = letter_spacing.parse().ok();
+                                format.letter_spacing = attribute_as_f64(&letter_spacing);
                             }
 
-                            if let Some(kerning) = attribute(b"kerning") {
-                                if kerning == WStr::from_units(b"1") {
-                                    format.kerning = Some(true);
-                                } else if kerning == WStr::from_units(b"0") {
-                                    format.kerning = Some(false);
-                                }
+                            if let Some(kerning) = attribute(b"kerning")
+                                .and_then(|kerning| attribute_as_bool(&kerning))
+                            {
+                                format.kerning = Some(kerning);
                             }
                         }
                         b"b" => {
@@ -723,47 +1279,81 @@ impl FormatSpans {
                             format.underline = Some(true);
                         }
                         b"li" if is_multiline => {
+                            if !text.is_empty() && !text.ends_with(b'\n') {
+                                push_line_break(&mut text, &mut spans, &format);
+                            }
+                            if let Some(has_nested_items) = item_stack.last_mut() {
+                                *has_nested_items = true;
+                            }
+                            item_stack.push(false);
+
+                            // Items of ordered lists are labelled with their
+                            // number rather than a bullet.
                             format.bullet = Some(true);
+                            format.list_number = match list_stack.last_mut() {
+                                Some(Some(number)) => {
+                                    *number += 1;
+                                    Some(*number)
+                                }
+                                _ => None,
+                            };
                         }
+                        b"ol" if is_multiline => list_stack.push(Some(0)),
+                        b"ul" if is_multiline => list_stack.push(None),
                         b"textformat" => {
-                            //TODO: Spec says these are all in twips. That doesn't seem to
-                            //match Flash 8.
+                            // The spec says these are all in twips, but Flash Player
+                            // treats them as pixels, like every other length here.
                             if let Some(left_margin) = attribute(b"leftmargin") {
-                                format.left_margin = left_margin.parse().ok();
+                                format.left_margin = attribute_as_f64(&left_margin);
                             }
 
                             if let Some(right_margin) = attribute(b"rightmargin") {
-                                format.right_margin = right_margin.parse().ok();
+                                format.right_margin = attribute_as_f64(&right_margin);
                             }
 
                             if let Some(indent) = attribute(b"indent") {
-                                format.indent = indent.parse().ok();
+                                format.indent = attribute_as_f64(&indent);
                             }
 
                             if let Some(block_indent) = attribute(b"blockindent") {
-                                format.block_indent = block_indent.parse().ok();
+                                format.block_indent = attribute_as_f64(&block_indent);
                             }
 
                             if let Some(leading) = attribute(b"leading") {
-                                format.leading = leading.parse().ok();
+                                format.leading = attribute_as_f64(&leading);
                             }
 
                             if let Some(tab_stops) = attribute(b"tabstops") {
                                 format.tab_stops = Some(
-                                    tab_stops
-                                        .split(b',')
-                                        .filter_map(|v| v.trim().parse().ok())
-                                        .collect(),
+                                    tab_stops.split(b',').filter_map(attribute_as_f64).collect(),
                                 );
                             }
                         }
                         _ => {}
                     }
+
+                    // An inline style takes precedence over the tag's own attributes.
+                    if let Some(style) = attribute(b"style") {
+                        let declarations = parse_style_declarations(&style);
+                        format = TextFormat::from_css_declarations(&declarations).mix_with(format);
+                    }
                     format_stack.push(format);
                 }
                 Ok(Event::Text(e)) if !e.is_empty() => {
-                    let e = decode_to_wstr(e.escaped());
+                    let raw = match options.process_white(e.escaped()) {
+                        Some(raw) => raw,
+                        None => continue,
+                    };
+                    if node_count >= options.max_nodes {
+                        log::warn!("HTML exceeds the parse limits, discarding the rest");
+                        break;
+                    }
+                    node_count += 1;
+
+                    let e = decode_to_wstr(&raw);
                     let e = process_html_entity(&e).unwrap_or(e);
+                    let (e, found) = LineEnding::normalize(&e);
+                    line_ending = line_ending.or(found);
                     let format = format_stack.last().unwrap().clone();
                     text.push_str(&e);
                     spans.push(TextSpan::with_length_and_format(e.len(), format));
@@ -783,16 +1373,23 @@ impl FormatSpans {
                     }
 
                     match &e.name().to_ascii_lowercase()[..] {
-                        b"br" | b"sbr" => {
-                            // Skip pop from `format_stack`.
-                            continue;
+                        b"p" if is_multiline => {
+                            push_line_break(&mut text, &mut spans, format_stack.last().unwrap());
                         }
-                        b"p" | b"li" if is_multiline => {
-                            text.push_byte(b'\n');
-                            if let Some(span) = spans.last_mut() {
-                                span.span_length += 1;
+                        b"li" if is_multiline => {
+                            // The last nested item already ended the line.
+                            let has_nested_items = item_stack.pop().unwrap_or(false);
+                            if !has_nested_items || !text.ends_with(b'\n') {
+                                push_line_break(
+                                    &mut text,
+                                    &mut spans,
+                                    format_stack.last().unwrap(),
+                                );
                             }
                         }
+                        b"ol" | b"ul" if is_multiline => {
+                            list_stack.pop();
+                        }
                         _ => {}
                     }
                     format_stack.pop();
@@ -806,18 +1403,59 @@ impl FormatSpans {
             }
         }
 
-        Self {
+        let mut fs = Self::from_parts(
             text,
-            displayed_text: WString::new(),
             spans,
             default_format,
-        }
+            line_ending.unwrap_or(LineEnding::Lf),
+        );
+        fs.normalize();
+        fs
     }
 
     pub fn default_format(&self) -> &TextFormat {
         &self.default_format
     }
 
+    /// Retrieve the format of text inserted at the very start of the text,
+    /// when no format is given for it.
+    ///
+    /// There is no preceding text to inherit a format from, so this is the
+    /// default format for new text rather than that of the first span.
+    pub fn start_format(&self) -> TextFormat {
+        self.default_format.clone()
+    }
+
+    /// Retrieve the format of the character before `pos`, which is the format
+    /// that text typed at a caret placed at `pos` takes on.
+    ///
+    /// At the start of the text there is no such character, so this yields
+    /// `start_format` instead. Positions past the end of the text are treated
+    /// as the end.
+    pub fn format_before(&self, pos: usize) -> TextFormat {
+        let pos = pos.min(self.text.len());
+        match pos
+            .checked_sub(1)
+            .and_then(|prev| self.resolve_position_as_span(prev))
+        {
+            Some((index, _)) => self.spans[index].get_text_format(),
+            None => self.start_format(),
+        }
+    }
+
+    /// Retrieve the text with its line breaks converted back to the
+    /// convention it was given in.
+    pub fn text_with_line_ending(&self) -> WString {
+        let mut result = WString::new();
+        for (i, line) in self.text.split(b'\n').enumerate() {
+            if i > 0 {
+                result.push_str(self.line_ending.as_wstr());
+            }
+            result.push_str(line);
+        }
+        result
+    }
+
     pub fn set_default_format(&mut self, tf: TextFormat) {
         self.default_format = tf.mix_with(self.default_format.clone());
     }
@@ -870,6 +1508,10 @@ impl FormatSpans {
     /// This function returns both the index of the span which covers the
     /// search position, but how far into the span its position is.
     ///
+    /// The end of the text is a valid position too (e.g. for a caret placed
+    /// after the last character): it resolves to the last span, with an offset
+    /// equal to that span's length. Positions past the end yield `None`.
+    ///
     /// The index returned from this function is not valid across calls which
     /// mutate spans.
     pub fn resolve_position_as_span(&self, search_pos: usize) -> Option<(usize, usize)> {
@@ -883,6 +1525,11 @@ impl FormatSpans {
             position += span.span_length;
         }
 
+        if search_pos == position {
+            let last = self.spans.len().checked_sub(1)?;
+            return Some((last, self.spans[last].span_length));
+        }
+
         None
     }
 
@@ -890,7 +1537,9 @@ impl FormatSpans {
     /// already exist.
     ///
     /// If `search_pos` is out of bounds for the underlying set of spans, then
-    /// this function returns `None`.
+    /// this function returns `None`. The end of the text always has a span
+    /// break, so no span is created there and the index one past the last
+    /// span is returned.
     ///
     /// The returned index refers to the index of the newly-created span at
     /// `search_pos`. It will be invalidated if another span break is created
@@ -901,21 +1550,16 @@ impl FormatSpans {
     ///  * Discard the values returned by this function and redundantly resolve
     ///    each span again once all breaks are completed.
     pub fn ensure_span_break_at(&mut self, search_pos: usize) -> Option<usize> {
-        if let Some((first_span_pos, break_index)) = self.resolve_position_as_span(search_pos) {
-            if break_index == 0 {
-                return Some(first_span_pos);
-            }
-
-            let first_span = self.spans.get_mut(first_span_pos).unwrap();
-            let mut second_span = first_span.clone();
-            second_span.span_length = first_span.span_length - break_index;
-            first_span.span_length = break_index;
-
-            self.spans.insert(first_span_pos + 1, second_span);
+        let (first_span_pos, break_index) = self.resolve_position_as_span(search_pos)?;
 
-            Some(first_span_pos + 1)
-        } else {
-            None
+        match self.spans[first_span_pos].split_at(break_index) {
+            Some(second_span) => {
+                self.spans.insert(first_span_pos + 1, second_span);
+                Some(first_span_pos + 1)
+            }
+            // There is already a break at the start or end of the span.
+            None if break_index == 0 => Some(first_span_pos),
+            None => Some(first_span_pos + 1),
         }
     }
 
@@ -933,13 +1577,13 @@ impl FormatSpans {
     /// mutate spans.
     pub fn get_span_boundaries(&self, from: usize, to: usize) -> (usize, usize) {
         let start_pos = self.resolve_position_as_span(from).unwrap_or((0, 0)).0;
-        let end_pos = min(
-            self.resolve_position_as_span(to.saturating_sub(1))
-                .map(|(pos, i)| (pos.saturating_add(1), i))
-                .unwrap_or_else(|| (self.spans.len(), 0))
-                .0,
-            self.spans.len(),
-        );
+        let end_pos = match self.resolve_position_as_span(to) {
+            // `to` is the start of a span, which is excluded from the range.
+            // A range ending at 0 still includes the first span.
+            Some((pos, 0)) if to > 0 => pos,
+            Some((pos, _)) => pos + 1,
+            None => self.spans.len(),
+        };
 
         (start_pos, end_pos)
     }
@@ -962,6 +1606,18 @@ impl FormatSpans {
     /// This function should always be called after mutating text spans in such
     /// a way that might violate the above-mentioned invariants.
     pub fn normalize(&mut self) {
+        // An empty string is always covered by exactly one null-length span.
+        // Handling this up front keeps the span removal logic below from
+        // having to special-case it.
+        if self.text.is_empty() {
+            self.spans.clear();
+            self.spans.push(TextSpan::with_length_and_format(
+                0,
+                self.default_format.clone(),
+            ));
+            return;
+        }
+
         let mut span_length = 0;
         for span in self.spans.iter() {
             span_length += span.span_length;
@@ -1026,8 +1682,7 @@ impl FormatSpans {
         }
 
         // Null span removal can possibly cause the span list to become empty.
-        // If that happens, then insert a new span. We don't care if it's a
-        // null span at this point.
+        // If that happens, then insert a new span covering the whole string.
         if self.spans.is_empty() {
             self.spans.push(TextSpan::with_length_and_format(
                 self.text.len(),
@@ -1036,6 +1691,56 @@ impl FormatSpans {
         }
     }
 
+    /// Adjust the format spans in the range `[from_span, to_span)`, as well as
+    /// their immediate neighbours, to uphold the invariants listed on
+    /// `normalize`.
+    ///
+    /// This is meant for localized edits, where only a handful of spans were
+    /// touched, and avoids rescanning the entire span list. It relies on all
+    /// spans outside of the range already being normalized, and on the span
+    /// lengths already adding up to the length of the text.
+    pub fn normalize_range(&mut self, from_span: usize, to_span: usize) {
+        // The empty string is trivially cheap to normalize in full.
+        if self.text.is_empty() {
+            self.normalize();
+            return;
+        }
+
+        debug_assert_eq!(
+            self.spans
+                .iter()
+                .map(|span| span.span_length)
+                .sum::<usize>(),
+            self.text.len()
+        );
+
+        let start = min(from_span.saturating_sub(1), self.spans.len());
+        let mut end = min(to_span.saturating_add(1), self.spans.len());
+
+        // Remove null-length spans.
+        let mut i = start;
+        while i < end {
+            if self.spans[i].span_length == 0 {
+                self.spans.remove(i);
+                end -= 1;
+            } else {
+                i += 1;
+            }
+        }
+
+        // Merge adjacent spans with identical formatting.
+        let mut i = start;
+        while i + 1 < end {
+            if self.spans[i].can_merge(&self.spans[i + 1]) {
+                self.spans[i].span_length += self.spans[i + 1].span_length;
+                self.spans.remove(i + 1);
+                end -= 1;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
     /// Retrieve a text format covering all of the properties applied to text
     /// from the start index to the end index.
     ///
@@ -1060,7 +1765,20 @@ impl FormatSpans {
 
     /// Change some portion of the text to have a particular set of text
     /// attributes.
+    ///
+    /// Paragraph-level properties (see `TextFormat::paragraph_format`) are
+    /// applied to every paragraph the range touches, in full.
     pub fn set_text_format(&mut self, from: usize, to: usize, fmt: &TextFormat) {
+        if let Some(paragraph_fmt) = fmt.paragraph_format().filter(|_| from < to) {
+            let (paragraph_from, paragraph_to) = self.paragraph_range(from, to);
+            self.set_span_format(paragraph_from, paragraph_to, &paragraph_fmt);
+        }
+
+        self.set_span_format(from, to, fmt);
+    }
+
+    /// Change the text attributes of exactly the range [from, to).
+    fn set_span_format(&mut self, from: usize, to: usize, fmt: &TextFormat) {
         self.ensure_span_break_at(from);
         self.ensure_span_break_at(to);
 
@@ -1072,7 +1790,38 @@ impl FormatSpans {
             }
         }
 
-        self.normalize();
+        self.normalize_range(start_pos, end_pos);
+    }
+
+    /// Iterate over the ranges of each paragraph of the text, excluding the
+    /// newlines that separate them.
+    ///
+    /// Text ending in a newline has an empty paragraph after it, and empty
+    /// text consists of a single empty paragraph.
+    pub fn paragraphs(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let mut start = 0;
+        self.text.split(b'\n').map(move |paragraph| {
+            let range = start..start + paragraph.len();
+            start = range.end + 1;
+            range
+        })
+    }
+
+    /// Extend the range [from, to) to the start and end of the paragraphs it
+    /// touches, including their trailing newlines.
+    fn paragraph_range(&self, from: usize, to: usize) -> (usize, usize) {
+        let len = self.text.len();
+        let from = from.min(len);
+        let last = to.min(len).saturating_sub(1).max(from);
+
+        // Each paragraph owns the newline at its end.
+        let mut touched = self
+            .paragraphs()
+            .filter(|paragraph| paragraph.start <= last && paragraph.end >= from);
+        let first = touched.next().unwrap_or(from..from);
+        let end = touched.last().unwrap_or_else(|| first.clone()).end;
+
+        (first.start, (end + 1).min(len))
     }
 
     /// Replace the text in the range [from, to) with the contents of `with`.
@@ -1085,34 +1834,78 @@ impl FormatSpans {
     /// either the formatting of the last span in the range, or if the range
     /// extends beyond the end of the field, the default text format.
     ///
+    /// Hyperlinks are only extended over the new text if it is inserted
+    /// strictly inside of them; text inserted at the edge of a link will have
+    /// its URL and target cleared.
+    ///
     /// (The text formatting behavior has been confirmed by manual testing with
     /// Flash Player 8.)
+    ///
+    /// Line breaks in the inserted text are normalized to `\n`.
+    ///
+    /// Returns the position immediately after the inserted text, which is
+    /// where the caret should be placed after an edit. Like all positions in
+    /// this struct, it is measured in UTF-16 code units. Degenerate ranges
+    /// leave the text untouched and return `from`.
     pub fn replace_text(
         &mut self,
         from: usize,
         to: usize,
         with: &WStr,
         new_tf: Option<&TextFormat>,
-    ) {
+    ) -> usize {
         if to < from {
-            return;
+            return from;
         }
 
-        if from < self.text.len() {
+        let (with, line_ending) = LineEnding::normalize(with);
+        let with = &with[..];
+        if let Some(line_ending) = line_ending {
+            self.line_ending = line_ending;
+        }
+
+        let new_span_pos = if from < self.text.len() {
             self.ensure_span_break_at(from);
             self.ensure_span_break_at(to);
 
             let (start_pos, end_pos) = self.get_span_boundaries(from, to);
-            let new_tf = new_tf
-                .cloned()
-                .or_else(|| self.spans.get(end_pos).map(|span| span.get_text_format()))
-                .unwrap_or_else(|| self.default_format.clone());
+
+            // An insertion replaces no spans, even at the start of the text
+            // (where the boundaries still include the first span).
+            let end_pos = if from == to { start_pos } else { end_pos };
+            let new_tf = new_tf.cloned().unwrap_or_else(|| {
+                if from == 0 && to == 0 {
+                    return self.start_format();
+                }
+
+                let next_span = self.spans.get(end_pos);
+                let mut new_tf = next_span
+                    .map(|span| span.get_text_format())
+                    .unwrap_or_else(|| self.default_format.clone());
+
+                // Only a link inherited from the following span needs to be
+                // cleared; the default format's link is kept as-is.
+                if let Some(next) = next_span.filter(|next| !next.url.is_empty()) {
+                    let prev_span = start_pos.checked_sub(1).and_then(|i| self.spans.get(i));
+                    let is_inside_link = prev_span.map_or(false, |prev| {
+                        prev.url == next.url && prev.target == next.target
+                    });
+                    if !is_inside_link {
+                        new_tf.url = Some(WString::new());
+                        new_tf.target = Some(WString::new());
+                    }
+                }
+
+                new_tf
+            });
 
             self.spans.drain(start_pos..end_pos);
             self.spans.insert(
                 start_pos,
                 TextSpan::with_length_and_format(with.len(), new_tf),
             );
+
+            start_pos
         } else {
             self.spans.push(TextSpan::with_length_and_format(
                 with.len(),
@@ -1120,7 +1913,9 @@ impl FormatSpans {
                     .cloned()
                     .unwrap_or_else(|| self.default_format.clone()),
             ));
-        }
+
+            self.spans.len() - 1
+        };
 
         let mut new_string = WString::new();
         if let Some(text) = self.text.slice(0..from) {
@@ -1132,6 +1927,7 @@ impl FormatSpans {
             new_string.push_str(&self.text);
         }
         new_string.push_str(with);
+        let caret = new_string.len();
 
         if let Some(text) = self.text.slice(to..) {
             new_string.push_str(text);
@@ -1139,7 +1935,296 @@ impl FormatSpans {
 
         self.text = new_string;
 
-        self.normalize();
+        self.normalize_range(new_span_pos, new_span_pos + 1);
+
+        caret
+    }
+
+    /// Retrieve the bounds of the character at `index`, as laid out in a text
+    /// field of the given width.
+    ///
+    /// The bounds account for the character's span format (such as its size
+    /// and letter spacing) as well as the line it ends up on. Yields `None` if
+    /// the index is out of range or the character is not rendered (e.g. line
+    /// breaks or characters missing from the font).
+    pub fn char_boundaries<'gc>(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        movie: Arc<SwfMovie>,
+        bounds: Twips,
+        is_word_wrap: bool,
+        is_device_font: bool,
+        index: usize,
+    ) -> Option<Rectangle<Twips>> {
+        if index >= self.text.len() {
+            return None;
+        }
+
+        let (layout, _) = LayoutBox::lower_from_text_spans(
+            self,
+            context,
+            movie,
+            bounds,
+            is_word_wrap,
+            is_device_font,
+        );
+
+        layout
+            .iter()
+            .find_map(|layout_box| layout_box.char_bounds(self.displayed_text(), index))
+            .map(Into::into)
+    }
+
+    /// A hash of everything that affects how the text is laid out: the
+    /// displayed text and the length and format of every span.
+    ///
+    /// Two `FormatSpans` with equal hashes lay out identically at the same
+    /// wrap width, barring hash collisions.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.displayed_text().hash(&mut hasher);
+        for span in &self.spans {
+            span.span_length.hash(&mut hasher);
+            span.format.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// The width at which text is wrapped by `layout`, if any.
+    pub fn wrap_width(&self) -> Option<Twips> {
+        self.wrap_width
+    }
+
+    /// Set the width at which `layout` wraps text.
+    ///
+    /// `None` disables wrapping, so that lines are only broken at newlines.
+    /// Changing the width marks the layout as dirty.
+    pub fn set_wrap_width(&mut self, wrap_width: Option<Twips>) {
+        if self.wrap_width != wrap_width {
+            self.wrap_width = wrap_width;
+            self.layout_dirty = true;
+        }
+    }
+
+    /// Whether the text must be laid out again to reflect its wrap width.
+    pub fn is_layout_dirty(&self) -> bool {
+        self.layout_dirty
+    }
+
+    /// Find everything that the lines of the text depend on, as laid out at
+    /// the current wrap width.
+    fn layout_key(
+        &self,
+        context: &mut UpdateContext<'_, '_, '_>,
+        movie: Arc<SwfMovie>,
+        is_device_font: bool,
+    ) -> LayoutKey {
+        let fonts = self
+            .spans
+            .iter()
+            .map(|span| {
+                span.resolve_font(context, movie.clone(), is_device_font)
+                    .map(|font| font.descriptor().clone())
+            })
+            .collect();
+
+        LayoutKey {
+            content_hash: self.content_hash(),
+            wrap_width: self.wrap_width,
+            is_device_font,
+            movie: Arc::downgrade(&movie),
+            fonts,
+        }
+    }
+
+    /// Lay out the text, wrapping it at the current wrap width.
+    ///
+    /// Lines are aligned within the wrap width when wrapping, and within
+    /// `width` otherwise. The lines of the layout are cached for `num_lines`
+    /// and `line_text`.
+    pub fn layout<'gc>(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        movie: Arc<SwfMovie>,
+        width: Twips,
+        is_device_font: bool,
+    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>) {
+        let key = self.layout_key(context, movie.clone(), is_device_font);
+        self.layout_with_key(context, key, movie, width, is_device_font)
+    }
+
+    /// Lay out the text like `layout`, unless nothing it depends on changed
+    /// since it was last laid out within the same `width`.
+    ///
+    /// Returns `None` if the previous layout is still current.
+    pub fn layout_if_changed<'gc>(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        movie: Arc<SwfMovie>,
+        width: Twips,
+        is_device_font: bool,
+    ) -> Option<(Vec<LayoutBox<'gc>>, BoxBounds<Twips>)> {
+        let key = self.layout_key(context, movie.clone(), is_device_font);
+        if let Some(cache) = &*self.line_cache.borrow() {
+            if cache.key == key && cache.width == Some(width) {
+                self.layout_dirty = false;
+                return None;
+            }
+        }
+
+        Some(self.layout_with_key(context, key, movie, width, is_device_font))
+    }
+
+    fn layout_with_key<'gc>(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        key: LayoutKey,
+        movie: Arc<SwfMovie>,
+        width: Twips,
+        is_device_font: bool,
+    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>) {
+        self.layout_dirty = false;
+
+        let (layout, bounds) = self.lower_layout(context, movie, width, is_device_font);
+        self.cache_lines(key, Some(width), &layout);
+
+        (layout, bounds)
+    }
+
+    /// Lay out the text at the current wrap width, leaving the dirty flag
+    /// untouched.
+    fn lower_layout<'gc>(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        movie: Arc<SwfMovie>,
+        width: Twips,
+        is_device_font: bool,
+    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>) {
+        LayoutBox::lower_from_text_spans(
+            self,
+            context,
+            movie,
+            self.wrap_width.unwrap_or(width),
+            self.wrap_width.is_some(),
+            is_device_font,
+        )
+    }
+
+    /// Find the range of text on each rendered line, as laid out at the
+    /// current wrap width.
+    ///
+    /// The lines of the most recent layout are reused for as long as its
+    /// layout key stays the same.
+    fn line_ranges(
+        &self,
+        context: &mut UpdateContext<'_, '_, '_>,
+        movie: Arc<SwfMovie>,
+        is_device_font: bool,
+    ) -> Vec<Range<usize>> {
+        let key = self.layout_key(context, movie.clone(), is_device_font);
+        if let Some(cache) = &*self.line_cache.borrow() {
+            if cache.key == key {
+                return cache.lines.clone();
+            }
+        }
+
+        // Alignment doesn't affect which line text ends up on.
+        let (layout, _) = self.lower_layout(context, movie, Twips::ZERO, is_device_font);
+        self.cache_lines(key, None, &layout)
+    }
+
+    /// Store the lines of a fresh layout in the line cache, returning them.
+    fn cache_lines(
+        &self,
+        key: LayoutKey,
+        width: Option<Twips>,
+        layout: &[LayoutBox<'_>],
+    ) -> Vec<Range<usize>> {
+        let lines = self.lines_of_layout(layout);
+        #[cfg(test)]
+        self.line_layout_count.set(self.line_layout_count.get() + 1);
+        *self.line_cache.borrow_mut() = Some(LineCache {
+            key,
+            width,
+            lines: lines.clone(),
+        });
+        lines
+    }
+
+    /// How many times the text has been laid out, rather than having its
+    /// lines reused from a previous layout.
+    #[cfg(test)]
+    pub fn line_layout_count(&self) -> usize {
+        self.line_layout_count.get()
+    }
+
+    /// Find the range of text on each line of a layout of this text.
+    ///
+    /// Each line runs up to the start of the next one. Like the text field's
+    /// scroll metrics, a text box starts a new line when its top is below the
+    /// bottom of the line so far.
+    fn lines_of_layout(&self, layout: &[LayoutBox<'_>]) -> Vec<Range<usize>> {
+        let mut line_starts = Vec::new();
+        let mut line_extent = None;
+
+        for layout_box in layout.iter() {
+            let start = match layout_box.content() {
+                LayoutContent::Text { start, .. } => *start,
+                _ => continue,
+            };
+            let bounds = layout_box.bounds();
+
+            match line_extent {
+                Some(extent) if bounds.offset_y() <= extent => {
+                    line_extent = Some(max(extent, bounds.extent_y()));
+                }
+                _ => {
+                    line_starts.push(start);
+                    line_extent = Some(bounds.extent_y());
+                }
+            }
+        }
+
+        let line_ends = line_starts.iter().skip(1).copied();
+        line_starts
+            .iter()
+            .copied()
+            .zip(line_ends.chain(std::iter::once(self.text.len())))
+            .map(|(start, end)| start..end)
+            .collect()
+    }
+
+    /// The number of rendered lines, as laid out at the current wrap width.
+    pub fn num_lines(
+        &self,
+        context: &mut UpdateContext<'_, '_, '_>,
+        movie: Arc<SwfMovie>,
+        is_device_font: bool,
+    ) -> usize {
+        self.line_ranges(context, movie, is_device_font).len()
+    }
+
+    /// Retrieve the text of the rendered line at index `line`, as laid out at
+    /// the current wrap width.
+    ///
+    /// A line broken by wrapping keeps the whitespace it was broken at, while
+    /// the line break ending a line is not included. Yields `None` if the
+    /// line doesn't exist.
+    pub fn line_text(
+        &self,
+        context: &mut UpdateContext<'_, '_, '_>,
+        movie: Arc<SwfMovie>,
+        is_device_font: bool,
+        line: usize,
+    ) -> Option<WString> {
+        let range = self
+            .line_ranges(context, movie, is_device_font)
+            .into_iter()
+            .nth(line)?;
+        let text = &self.text[range];
+        let text = text.strip_suffix(&[b'\n', b'\r'][..]).unwrap_or(text);
+
+        Some(text.into())
     }
 
     /// Iterate over all text spans in the current list of format spans.
@@ -1156,12 +2241,38 @@ impl FormatSpans {
         TextSpanIter::for_format_spans(self)
     }
 
+    /// Retrieve the runs of uniformly formatted text, each as a range of the
+    /// text and the format applied to it.
+    ///
+    /// Adjacent spans with identical formatting are combined, so every run is
+    /// as long as possible, and empty spans are skipped. The returned formats
+    /// have all properties defined.
+    #[cfg(test)]
+    pub fn format_runs(&self) -> Vec<(Range<usize>, TextFormat)> {
+        let mut runs: Vec<(Range<usize>, TextFormat)> = Vec::new();
+        let mut last_span: Option<&TextSpan> = None;
+
+        for (start, end, _text, span) in self.iter_spans() {
+            if start == end {
+                continue;
+            }
+
+            match (runs.last_mut(), last_span) {
+                (Some((range, _)), Some(last)) if last.can_merge(span) => range.end = end,
+                _ => runs.push((start..end, span.get_text_format())),
+            }
+            last_span = Some(span);
+        }
+
+        runs
+    }
+
     pub fn to_html(&self) -> WString {
         let mut spans = self.iter_spans();
         let mut state = if let Some((_start, _end, text, span)) = spans.next() {
             let mut state = FormatState {
                 result: WString::new(),
-                font_stack: VecDeque::new(),
+                tag_stack: Vec::new(),
                 span,
                 is_open: false,
             };
@@ -1181,14 +2292,53 @@ impl FormatSpans {
     }
 }
 
+/// An inline tag opened while serializing text spans to HTML.
+#[derive(Clone, Copy)]
+enum OpenTag<'a> {
+    /// A `<FONT>` tag, along with the span whose font attributes are in effect
+    /// inside of it.
+    Font(&'a TextSpan),
+    Anchor,
+    Bold,
+    Italic,
+    Underline,
+}
+
+impl OpenTag<'_> {
+    fn end_tag(self) -> &'static WStr {
+        WStr::from_units(match self {
+            OpenTag::Font(_) => b"</FONT>",
+            OpenTag::Anchor => b"</A>",
+            OpenTag::Bold => b"</B>",
+            OpenTag::Italic => b"</I>",
+            OpenTag::Underline => b"</U>",
+        })
+    }
+}
+
 /// Holds required state for HTML formatting.
 struct FormatState<'a> {
     result: WString,
-    font_stack: VecDeque<&'a TextSpan>,
+
+    /// The inline tags currently open, from outermost to innermost.
+    ///
+    /// Tags are always closed in reverse order, so that the output stays
+    /// properly nested no matter where formatting boundaries fall.
+    tag_stack: Vec<OpenTag<'a>>,
+
     span: &'a TextSpan,
     is_open: bool,
 }
 
+/// Whether two spans would be written with the same `<FONT>` attributes.
+fn is_same_font(a: &TextSpan, b: &TextSpan) -> bool {
+    a.font == b.font
+        && a.size == b.size
+        && a.color == b.color
+        && a.letter_spacing == b.letter_spacing
+        && a.kerning == b.kerning
+}
+
 impl<'a> FormatState<'a> {
     fn open_tags(&mut self) {
         if self.is_open {
@@ -1259,55 +2409,65 @@ impl<'a> FormatState<'a> {
             self.span.letter_spacing,
             if self.span.kerning { "1" } else { "0" },
         );
-        self.font_stack.push_front(self.span);
+        self.tag_stack.push(OpenTag::Font(self.span));
 
-        if !self.span.url.is_empty() {
+        self.open_inline_tags(self.span);
+        self.is_open = true;
+    }
+
+    /// Open the link and style tags required by `span` that aren't open yet.
+    fn open_inline_tags(&mut self, span: &'a TextSpan) {
+        if !span.url.is_empty() && !self.has_open_tag(|tag| matches!(tag, OpenTag::Anchor)) {
             let _ = write!(
                 self.result,
                 "<A HREF=\"{}\" TARGET=\"{}\">",
-                self.span.url, self.span.target
+                span.url, span.target
             );
+            self.tag_stack.push(OpenTag::Anchor);
         }
 
-        if self.span.bold {
+        if span.bold && !self.has_open_tag(|tag| matches!(tag, OpenTag::Bold)) {
             self.result.push_str(WStr::from_units(b"<B>"));
+            self.tag_stack.push(OpenTag::Bold);
         }
 
-        if self.span.italic {
+        if span.italic && !self.has_open_tag(|tag| matches!(tag, OpenTag::Italic)) {
             self.result.push_str(WStr::from_units(b"<I>"));
+            self.tag_stack.push(OpenTag::Italic);
         }
 
-        if self.span.underline {
+        if span.underline && !self.has_open_tag(|tag| matches!(tag, OpenTag::Underline)) {
             self.result.push_str(WStr::from_units(b"<U>"));
+            self.tag_stack.push(OpenTag::Underline);
         }
-
-        self.is_open = true;
     }
 
-    fn close_tags(&mut self) {
-        if !self.is_open {
-            return;
-        }
-
-        if self.span.underline {
-            self.result.push_str(WStr::from_units(b"</U>"));
-        }
+    fn has_open_tag(&self, predicate: impl Fn(&OpenTag) -> bool) -> bool {
+        self.tag_stack.iter().any(predicate)
+    }
 
-        if self.span.italic {
-            self.result.push_str(WStr::from_units(b"</I>"));
+    /// Close every inline tag from `position` in the tag stack onwards.
+    fn close_inline_tags(&mut self, position: usize) {
+        while self.tag_stack.len() > position {
+            let tag = self.tag_stack.pop().unwrap();
+            self.result.push_str(tag.end_tag());
         }
+    }
 
-        if self.span.bold {
-            self.result.push_str(WStr::from_units(b"</B>"));
-        }
+    /// The span whose font attributes are in effect at the current position.
+    fn current_font(&self) -> Option<&'a TextSpan> {
+        self.tag_stack.iter().rev().find_map(|tag| match tag {
+            OpenTag::Font(span) => Some(*span),
+            _ => None,
+        })
+    }
 
-        if !self.span.url.is_empty() {
-            self.result.push_str(WStr::from_units(b"</A>"));
+    fn close_tags(&mut self) {
+        if !self.is_open {
+            return;
         }
 
-        self.result
-            .push_str(&WStr::from_units(b"</FONT>").repeat(self.font_stack.len()));
-        self.font_stack.clear();
+        self.close_inline_tags(0);
 
         if self.span.bullet {
             self.result.push_str(WStr::from_units(b"</LI>"));
@@ -1329,58 +2489,63 @@ impl<'a> FormatState<'a> {
     }
 
     fn set_span(&mut self, span: &'a TextSpan) {
-        if !span.underline && self.span.underline {
-            self.result.push_str(WStr::from_units(b"</U>"));
-        }
-
-        if !span.italic && self.span.italic {
-            self.result.push_str(WStr::from_units(b"</I>"));
+        let old_span = self.span;
+        self.span = span;
+        if !self.is_open {
+            // The tags for this span are written once its paragraph opens.
+            return;
         }
 
-        if !span.bold && self.span.bold {
-            self.result.push_str(WStr::from_units(b"</B>"));
+        // Find the outermost tag that doesn't apply to the new span. It has to
+        // be closed along with every tag nested inside of it, even if those
+        // still apply; they are reopened below.
+        let mut close_from = self.tag_stack.iter().position(|tag| match tag {
+            OpenTag::Font(_) => false,
+            OpenTag::Anchor => span.url != old_span.url || span.target != old_span.target,
+            OpenTag::Bold => !span.bold,
+            OpenTag::Italic => !span.italic,
+            OpenTag::Underline => !span.underline,
+        });
+
+        // A font change is undone by returning to an enclosing `<FONT>` with
+        // the same attributes, if there is one.
+        if !self
+            .current_font()
+            .map_or(false, |font| is_same_font(font, span))
+        {
+            let enclosing = self
+                .tag_stack
+                .iter()
+                .rposition(|tag| matches!(tag, OpenTag::Font(font) if is_same_font(font, span)));
+            if let Some(enclosing) = enclosing {
+                close_from = Some(close_from.map_or(enclosing + 1, |pos| pos.min(enclosing + 1)));
+            }
         }
 
-        if span.url != self.span.url && !self.span.url.is_empty() {
-            self.result.push_str(WStr::from_units(b"</A>"));
+        if let Some(close_from) = close_from {
+            self.close_inline_tags(close_from);
         }
 
-        if span.font != self.span.font
-            || span.size != self.span.size
-            || span.color != self.span.color
-            || span.letter_spacing != self.span.letter_spacing
-            || span.kerning != self.span.kerning
-        {
-            let pos = self.font_stack.iter().position(|font| {
-                span.font == font.font
-                    && span.size == font.size
-                    && span.color == font.color
-                    && span.letter_spacing == font.letter_spacing
-                    && span.kerning == font.kerning
-            });
-            if let Some(pos) = pos {
-                self.result
-                    .push_str(&WStr::from_units(b"</FONT>").repeat(pos));
-                self.font_stack.drain(0..pos);
-            } else {
+        if let Some(font) = self.current_font() {
+            if !is_same_font(font, span) {
                 self.result.push_str(WStr::from_units(b"<FONT"));
-                if span.font != self.span.font {
+                if span.font != font.font {
                     let _ = write!(self.result, " FACE=\"{}\"", span.font);
                 }
-                if span.size != self.span.size {
+                if span.size != font.size {
                     let _ = write!(self.result, " SIZE=\"{}\"", span.size);
                 }
-                if span.color != self.span.color {
+                if span.color != font.color {
                     let _ = write!(
                         self.result,
                         " COLOR=\"#{:0>2X}{:0>2X}{:0>2X}\"",
                         span.color.r, span.color.g, span.color.b
                     );
                 }
-                if span.letter_spacing != self.span.letter_spacing {
+                if span.letter_spacing != font.letter_spacing {
                     let _ = write!(self.result, " LETTERSPACING=\"{}\"", span.letter_spacing);
                 }
-                if span.kerning != self.span.kerning {
+                if span.kerning != font.kerning {
                     let _ = write!(
                         self.result,
                         " KERNING=\"{}\"",
@@ -1388,42 +2553,29 @@ impl<'a> FormatState<'a> {
                     );
                 }
                 self.result.push_byte(b'>');
-                self.font_stack.push_front(span);
+                self.tag_stack.push(OpenTag::Font(span));
             }
         }
 
-        if span.url != self.span.url && !span.url.is_empty() {
-            let _ = write!(
-                self.result,
-                "<A HREF=\"{}\" TARGET=\"{}\">",
-                span.url, span.target
-            );
-        }
-
-        if span.bold && !self.span.bold {
-            self.result.push_str(WStr::from_units(b"<B>"));
-        }
-
-        if span.italic && !self.span.italic {
-            self.result.push_str(WStr::from_units(b"<I>"));
-        }
-
-        if span.underline && !self.span.underline {
-            self.result.push_str(WStr::from_units(b"<U>"));
-        }
-
-        self.span = span;
+        self.open_inline_tags(span);
     }
 
     fn push_text(&mut self, text: &WStr) {
-        for (i, text) in text.split(&[b'\n', b'\r'][..]).enumerate() {
-            self.open_tags();
-            if i > 0 {
-                self.close_tags();
+        let mut lines = text.split(&[b'\n', b'\r'][..]).peekable();
+        while let Some(line) = lines.next() {
+            // A trailing line break only starts a new paragraph once more
+            // text follows it.
+            if !line.is_empty() || lines.peek().is_some() || self.result.is_empty() {
+                self.open_tags();
             }
-            let encoded = text.to_utf8_lossy();
+
+            let encoded = line.to_utf8_lossy();
             let escaped = escape(encoded.as_bytes());
             self.result.push_str(WStr::from_units(&*escaped));
+
+            if lines.peek().is_some() {
+                self.close_tags();
+            }
         }
     }
 }
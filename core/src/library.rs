@@ -3,7 +3,7 @@ use crate::avm2::{ClassObject as Avm2ClassObject, Domain as Avm2Domain};
 use crate::backend::audio::SoundHandle;
 use crate::character::Character;
 use crate::display_object::{Bitmap, Graphic, MorphShape, TDisplayObject, Text};
-use crate::font::{Font, FontDescriptor};
+use crate::font::{DeviceFont, Font, FontDescriptor};
 use crate::prelude::*;
 use crate::string::AvmString;
 use crate::tag_utils::SwfMovie;
@@ -347,6 +347,10 @@ pub struct Library<'gc> {
     /// The embedded device font.
     device_font: Option<Font<'gc>>,
 
+    /// Fonts built from the approximate metrics of each device font, created
+    /// on first use.
+    device_font_metrics: [Option<Font<'gc>>; 3],
+
     /// A list of the symbols associated with specific AVM2 constructor
     /// prototypes.
     avm2_class_registry: Avm2ClassRegistry<'gc>,
@@ -359,6 +363,9 @@ unsafe impl<'gc> gc_arena::Collect for Library<'gc> {
             val.trace(cc);
         }
         self.device_font.trace(cc);
+        for font in &self.device_font_metrics {
+            font.trace(cc);
+        }
         self.avm2_class_registry.trace(cc);
     }
 }
@@ -368,6 +375,7 @@ impl<'gc> Library<'gc> {
         Self {
             movie_libraries: PtrWeakKeyHashMap::new(),
             device_font: None,
+            device_font_metrics: [None; 3],
             avm2_class_registry: Default::default(),
         }
     }
@@ -392,6 +400,17 @@ impl<'gc> Library<'gc> {
         self.device_font = Some(font);
     }
 
+    /// Returns a font with the approximate metrics of the given device font,
+    /// for measuring text when no device font has been loaded.
+    pub fn device_font_metrics(
+        &mut self,
+        gc_context: MutationContext<'gc, '_>,
+        kind: DeviceFont,
+    ) -> Font<'gc> {
+        *self.device_font_metrics[kind as usize]
+            .get_or_insert_with(|| Font::from_device_metrics(gc_context, kind))
+    }
+
     /// Get the AVM2 class registry.
     pub fn avm2_class_registry(&self) -> &Avm2ClassRegistry<'gc> {
         &self.avm2_class_registry
@@ -542,6 +542,11 @@ impl Player {
         self.is_playing
     }
 
+    /// Returns the mouse cursor most recently requested from the UI backend.
+    pub fn mouse_cursor(&self) -> MouseCursor {
+        self.mouse_cursor
+    }
+
     /// Returns the master volume of the player. 1.0 is 100% volume.
     pub fn volume(&self) -> f32 {
         self.audio.volume()
@@ -997,6 +1002,20 @@ impl Player {
                         text.text_input(codepoint, context);
                     }
                 }
+
+                // In AVM1, an unhandled Tab moves the focus through the tab stops.
+                if matches!(
+                    event,
+                    PlayerEvent::KeyDown {
+                        key_code: KeyCode::Tab,
+                        ..
+                    }
+                ) && !context.is_action_script_3()
+                {
+                    let reverse = context.input.is_key_down(KeyCode::Shift);
+                    let tracker = context.focus_tracker;
+                    tracker.cycle(context, reverse);
+                }
             }
 
             // Propagate clip events.
@@ -1,6 +1,13 @@
 //! Garbage-collectable XML DOM impl
 
 mod iterators;
+mod names;
+mod options;
 mod tree;
 
-pub use tree::{XmlNode, ELEMENT_NODE, TEXT_NODE};
+pub use names::NameInterner;
+pub use options::ParseOptions;
+pub use tree::{attribute_as_bool, attribute_as_f64, XmlNode, ELEMENT_NODE, TEXT_NODE};
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,40 @@
+//! Sharing of repeated element and attribute names
+
+use crate::string::AvmString;
+use gc_arena::MutationContext;
+use std::collections::HashMap;
+
+/// Interns the element and attribute names of a document as it is parsed.
+///
+/// Large documents tend to repeat a handful of tag names and attribute keys
+/// many times over; interning them makes every occurrence share a single
+/// string. Interned names are ordinary `AvmString`s, and compare equal to
+/// any other string with the same contents.
+#[derive(Default)]
+pub struct NameInterner<'gc> {
+    names: HashMap<Vec<u8>, AvmString<'gc>>,
+}
+
+impl<'gc> NameInterner<'gc> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieve the string for the UTF-8 encoded `name`, allocating it only
+    /// the first time it is seen.
+    pub fn intern(&mut self, gc_context: MutationContext<'gc, '_>, name: &[u8]) -> AvmString<'gc> {
+        if let Some(interned) = self.names.get(name) {
+            return *interned;
+        }
+
+        let interned = AvmString::new_utf8_bytes(gc_context, name);
+        self.names.insert(name.to_vec(), interned);
+        interned
+    }
+
+    /// The number of distinct names interned so far.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+}
@@ -0,0 +1,96 @@
+//! Tokenizer configuration shared by the XML and HTML parsers
+
+use quick_xml::Reader;
+
+/// Options controlling how markup is tokenized and built into a tree.
+///
+/// Strict XML (as used by `XML.parseXML`) and lenient HTML (as used by
+/// `TextField.htmlText`) share a tokenizer, but configure it differently.
+/// Use `ParseOptions::xml()` or `ParseOptions::html()` as a starting point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Drop text nodes that consist solely of whitespace.
+    pub ignore_white: bool,
+
+    /// Collapse each run of whitespace in text to a single space.
+    pub condense_white: bool,
+
+    /// Tolerate malformed markup, such as mismatched end tags, and expand
+    /// empty elements into a start and end tag.
+    pub html_mode: bool,
+
+    /// Treat element names case-insensitively by lowercasing them.
+    pub case_insensitive: bool,
+
+    /// The maximum nesting depth of elements.
+    pub max_depth: usize,
+
+    /// The maximum total number of nodes (elements and text) in the document.
+    pub max_nodes: usize,
+
+    /// Share a single string between all occurrences of each element name
+    /// and attribute key, rather than allocating one per node.
+    pub intern_names: bool,
+}
+
+impl ParseOptions {
+    /// Options for strict XML documents.
+    pub const fn xml() -> Self {
+        Self {
+            ignore_white: false,
+            condense_white: false,
+            html_mode: false,
+            case_insensitive: false,
+            max_depth: 1024,
+            max_nodes: 1 << 20,
+            intern_names: true,
+        }
+    }
+
+    /// Options for the lenient HTML accepted by text fields.
+    pub const fn html() -> Self {
+        Self {
+            html_mode: true,
+            case_insensitive: true,
+            ..Self::xml()
+        }
+    }
+
+    /// Create a `quick_xml` reader over `data` configured for these options.
+    pub fn reader<'a>(&self, data: &'a [u8]) -> Reader<&'a [u8]> {
+        let mut reader = Reader::from_reader(data);
+        reader.expand_empty_elements(self.html_mode);
+        reader.check_end_names(!self.html_mode);
+        reader
+    }
+
+    /// Apply the whitespace options to a run of text.
+    ///
+    /// Returns `None` if the text should be dropped entirely.
+    pub fn process_white<'a>(&self, text: &'a [u8]) -> Option<std::borrow::Cow<'a, [u8]>> {
+        let is_whitespace_char = |c: &u8| matches!(*c, b'\t' | b'\n' | b'\r' | b' ');
+        if text.is_empty() || self.ignore_white && text.iter().all(is_whitespace_char) {
+            return None;
+        }
+
+        if !self.condense_white {
+            return Some(text.into());
+        }
+
+        let mut condensed = Vec::with_capacity(text.len());
+        for &c in text {
+            if !is_whitespace_char(&c) {
+                condensed.push(c);
+            } else if condensed.last() != Some(&b' ') {
+                condensed.push(b' ');
+            }
+        }
+        Some(condensed.into())
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::xml()
+    }
+}
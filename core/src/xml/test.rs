@@ -0,0 +1,648 @@
+//! Tests for XML module
+
+use crate::avm1::test_utils::with_avm;
+use crate::avm1::{
+    Activation, Attribute, Error, Object, ScriptObject, TObject, XmlObject, XmlParseError,
+    XmlStatus,
+};
+use crate::string::{AvmString, WStr};
+use crate::xml::{
+    attribute_as_bool, attribute_as_f64, NameInterner, ParseOptions, XmlNode, ELEMENT_NODE,
+    TEXT_NODE,
+};
+use quick_xml::events::BytesStart;
+
+/// Construct an element with the given attributes.
+fn element<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    name: &'static str,
+    attributes: &[(&'static str, &'static str)],
+) -> XmlNode<'gc> {
+    let mc = activation.context.gc_context;
+    let node = XmlNode::new(mc, ELEMENT_NODE, Some(name.into()));
+    for (key, value) in attributes.iter().rev() {
+        node.attributes().define_value(
+            mc,
+            AvmString::from(*key),
+            AvmString::from(*value).into(),
+            Attribute::empty(),
+        );
+    }
+    node
+}
+
+#[test]
+fn parse_attribute_as_f64() {
+    let parse = |value: &str| attribute_as_f64(WStr::from_units(value.as_bytes()));
+
+    assert_eq!(parse("12"), Some(12.0));
+    assert_eq!(parse(" -1.5 "), Some(-1.5));
+    assert_eq!(parse("12px"), None);
+    assert_eq!(parse(""), None);
+}
+
+#[test]
+fn set_attribute() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let node = element(activation, "x", &[("a", "1")]);
+
+        node.set_attribute(mc, "b".into(), "2".into());
+        assert_eq!(
+            node.attribute_value(activation, "b".into())?,
+            Some("2".into())
+        );
+
+        // Overwriting doesn't add a second attribute of the same name.
+        node.set_attribute(mc, "a".into(), "3".into());
+        assert_eq!(
+            node.attribute_value(activation, "a".into())?,
+            Some("3".into())
+        );
+        assert_eq!(node.attributes_ordered().len(), 2);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn parse_attribute_as_bool() {
+    let parse = |value: &str| attribute_as_bool(WStr::from_units(value.as_bytes()));
+
+    assert_eq!(parse("true"), Some(true));
+    assert_eq!(parse(" 1 "), Some(true));
+    assert_eq!(parse("FALSE"), Some(false));
+    assert_eq!(parse("0"), Some(false));
+    assert_eq!(parse("yes"), None);
+    assert_eq!(parse(""), None);
+}
+
+#[test]
+fn parse_limits() {
+    fn child_names<'gc>(node: XmlNode<'gc>) -> Vec<AvmString<'gc>> {
+        node.children()
+            .filter_map(|child| child.node_name())
+            .collect()
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let proto = activation.context.avm1.prototypes().object;
+
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let options = ParseOptions {
+            max_depth: 2,
+            ..ParseOptions::xml()
+        };
+        let result = document.replace_with_options(
+            activation,
+            WStr::from_units(b"<a><b><c/></b></a>"),
+            options,
+        );
+        assert!(matches!(result, Err(XmlParseError::TooDeep(2))));
+        assert_eq!(document.status(), XmlStatus::OutOfMemory);
+        let a = document.as_node().children().next().unwrap();
+        assert_eq!(child_names(a), vec![AvmString::from("b")]);
+
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let options = ParseOptions {
+            max_nodes: 3,
+            ..ParseOptions::xml()
+        };
+        let result = document.replace_with_options(
+            activation,
+            WStr::from_units(b"<a/><b/><c/><d/>"),
+            options,
+        );
+        assert!(matches!(result, Err(XmlParseError::TooManyNodes(3))));
+        assert_eq!(document.status(), XmlStatus::OutOfMemory);
+        assert_eq!(
+            child_names(document.as_node()),
+            vec![AvmString::from("a"), "b".into(), "c".into()]
+        );
+
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let result = document.replace_with_str(activation, WStr::from_units(b"<a><b/></a>"), false);
+        assert!(result.is_ok());
+        assert_eq!(document.status(), XmlStatus::NoError);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn parse_options_presets() {
+    let xml = ParseOptions::xml();
+    assert!(!xml.html_mode);
+    assert!(!xml.case_insensitive);
+    assert!(!xml.ignore_white);
+    assert!(!xml.condense_white);
+    assert!(xml.intern_names);
+    assert_eq!(ParseOptions::default(), xml);
+
+    let html = ParseOptions::html();
+    assert!(html.html_mode);
+    assert!(html.case_insensitive);
+    assert_eq!(html.max_depth, xml.max_depth);
+    assert_eq!(html.max_nodes, xml.max_nodes);
+
+    let options = ParseOptions {
+        ignore_white: true,
+        ..ParseOptions::xml()
+    };
+    assert_eq!(options.process_white(b" \n\t"), None);
+    assert_eq!(
+        options.process_white(b" a  b ").as_deref(),
+        Some(&b" a  b "[..])
+    );
+
+    let options = ParseOptions {
+        condense_white: true,
+        ..ParseOptions::xml()
+    };
+    assert_eq!(
+        options.process_white(b" a \r\n\t b ").as_deref(),
+        Some(&b" a b "[..])
+    );
+}
+
+#[test]
+fn parse_options_builder() {
+    fn names<'gc>(node: XmlNode<'gc>) -> Vec<AvmString<'gc>> {
+        node.children()
+            .map(|child| child.node_name().or_else(|| child.node_value()).unwrap())
+            .collect()
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let proto = activation.context.avm1.prototypes().object;
+        let data = WStr::from_units(b"<P>one</p>  <B>two</B>");
+
+        // Strict XML rejects the mismatched end tag.
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let result = document.replace_with_options(activation, data, ParseOptions::xml());
+        assert!(result.is_err());
+        assert_eq!(document.status(), XmlStatus::MismatchedEnd);
+
+        // HTML tolerates it and lowercases element names.
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let options = ParseOptions {
+            ignore_white: true,
+            ..ParseOptions::html()
+        };
+        document
+            .replace_with_options(activation, data, options)
+            .unwrap();
+        assert_eq!(document.status(), XmlStatus::NoError);
+        assert_eq!(
+            names(document.as_node()),
+            vec![AvmString::from("p"), "b".into()]
+        );
+
+        // Whitespace is condensed rather than dropped.
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let options = ParseOptions {
+            condense_white: true,
+            ..ParseOptions::html()
+        };
+        document
+            .replace_with_options(activation, data, options)
+            .unwrap();
+        assert_eq!(
+            names(document.as_node()),
+            vec![AvmString::from("p"), " ".into(), "b".into()]
+        );
+
+        Ok(())
+    });
+}
+
+/// Nodes don't store the document they belong to; it is always found by
+/// walking up the parent chain, so grafted subtrees can't go stale.
+#[test]
+fn siblings() {
+    fn name<'gc>(node: Option<XmlNode<'gc>>) -> Option<AvmString<'gc>> {
+        node.and_then(|node| node.node_name())
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let mut parent = element(activation, "parent", &[]);
+        let first = element(activation, "first", &[]);
+        let mut middle = element(activation, "middle", &[]);
+        let last = element(activation, "last", &[]);
+        for child in [first, middle, last] {
+            parent.append_child(mc, child);
+        }
+
+        assert_eq!(name(middle.prev_sibling()), Some("first".into()));
+        assert_eq!(name(middle.next_sibling()), Some("last".into()));
+        assert!(first.prev_sibling().is_none());
+        assert!(last.next_sibling().is_none());
+
+        // Nodes without a parent have no siblings.
+        assert!(parent.prev_sibling().is_none());
+        assert!(parent.next_sibling().is_none());
+        middle.remove_node(mc);
+        assert!(middle.prev_sibling().is_none());
+        assert!(middle.next_sibling().is_none());
+        assert_eq!(name(first.next_sibling()), Some("last".into()));
+        assert_eq!(name(last.prev_sibling()), Some("first".into()));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn first_and_last_child() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let mut parent = element(activation, "parent", &[]);
+        assert!(parent.first_child().is_none());
+        assert!(parent.last_child().is_none());
+
+        let text = XmlNode::new(mc, TEXT_NODE, Some("text".into()));
+        assert!(text.first_child().is_none());
+        assert!(text.last_child().is_none());
+
+        parent.append_child(mc, element(activation, "a", &[]));
+        assert_eq!(
+            parent.first_child().and_then(|child| child.node_name()),
+            Some("a".into())
+        );
+        assert_eq!(
+            parent.last_child().and_then(|child| child.node_name()),
+            Some("a".into())
+        );
+
+        parent.append_child(mc, element(activation, "b", &[]));
+        parent.append_child(mc, text);
+        assert_eq!(
+            parent.first_child().and_then(|child| child.node_name()),
+            Some("a".into())
+        );
+        assert_eq!(
+            parent.last_child().and_then(|child| child.node_value()),
+            Some("text".into())
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn grafted_subtree_belongs_to_new_document() {
+    fn document_name<'gc>(node: XmlNode<'gc>) -> Option<AvmString<'gc>> {
+        node.ancestors().last().and_then(|root| root.node_name())
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let mut old_document = element(activation, "old", &[]);
+        let mut new_document = element(activation, "new", &[]);
+
+        // <old><a><b><c/></b></a></old>
+        let mut a = element(activation, "a", &[]);
+        let mut b = element(activation, "b", &[]);
+        let c = element(activation, "c", &[]);
+        b.append_child(mc, c);
+        a.append_child(mc, b);
+        old_document.append_child(mc, a);
+
+        for node in [a, b, c] {
+            assert_eq!(document_name(node), Some("old".into()));
+        }
+
+        new_document.append_child(mc, a);
+
+        for node in [a, b, c] {
+            assert_eq!(document_name(node), Some("new".into()));
+        }
+        assert_eq!(old_document.children_len(), 0);
+        assert_eq!(new_document.children_len(), 1);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn insert_child() {
+    fn names(node: XmlNode<'_>) -> Vec<String> {
+        node.children()
+            .filter_map(|child| child.node_name())
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn name<'gc>(node: Option<XmlNode<'gc>>) -> Option<AvmString<'gc>> {
+        node.and_then(|node| node.node_name())
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let mut parent = element(activation, "parent", &[]);
+        let a = element(activation, "a", &[]);
+        let b = element(activation, "b", &[]);
+        let c = element(activation, "c", &[]);
+        let d = element(activation, "d", &[]);
+
+        parent.insert_child(mc, 0, b);
+        parent.insert_child(mc, 0, a);
+        parent.insert_child(mc, 100, d);
+        parent.insert_child(mc, 2, c);
+        assert_eq!(names(parent), ["a", "b", "c", "d"]);
+
+        for node in [a, b, c, d] {
+            assert_eq!(name(node.parent()), Some("parent".into()));
+        }
+        assert_eq!(name(c.prev_sibling()), Some("b".into()));
+        assert_eq!(name(c.next_sibling()), Some("d".into()));
+        assert_eq!(name(b.next_sibling()), Some("c".into()));
+        assert_eq!(name(d.prev_sibling()), Some("c".into()));
+        assert!(a.prev_sibling().is_none());
+        assert!(d.next_sibling().is_none());
+
+        // Moving a child within its parent doesn't duplicate it.
+        parent.insert_child(mc, 0, c);
+        assert_eq!(names(parent), ["c", "a", "b", "d"]);
+        assert_eq!(name(b.next_sibling()), Some("d".into()));
+        assert!(c.prev_sibling().is_none());
+        assert_eq!(name(c.next_sibling()), Some("a".into()));
+
+        // Moving a child to another parent unlinks it from its old siblings.
+        let mut other = element(activation, "other", &[]);
+        other.insert_child(mc, 0, a);
+        assert_eq!(names(parent), ["c", "b", "d"]);
+        assert_eq!(names(other), ["a"]);
+        assert_eq!(name(c.next_sibling()), Some("b".into()));
+        assert_eq!(name(b.prev_sibling()), Some("c".into()));
+        assert!(a.prev_sibling().is_none());
+        assert!(a.next_sibling().is_none());
+        assert_eq!(name(a.parent()), Some("other".into()));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn attributes_ordered() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let proto = activation.context.avm1.prototypes().object;
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        let result = document.replace_with_str(
+            activation,
+            WStr::from_units(br#"<a z="1" b="2" m="3" a="4"/>"#),
+            false,
+        );
+        assert!(result.is_ok());
+
+        let a = document.as_node().children().next().unwrap();
+        assert_eq!(
+            a.attributes_ordered(),
+            vec![AvmString::from("z"), "b".into(), "m".into(), "a".into()]
+        );
+
+        // Serialization follows the same order.
+        assert_eq!(
+            a.into_string(activation)?,
+            WStr::from_units(br#"<a z="1" b="2" m="3" a="4" />"#)
+        );
+
+        // Attributes added by script are enumerated first.
+        a.attributes().define_value(
+            activation.context.gc_context,
+            "extra",
+            "5".into(),
+            Attribute::empty(),
+        );
+        assert_eq!(a.attributes_ordered()[0], AvmString::from("extra"));
+        assert_eq!(a.attributes_ordered().len(), 5);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn duplicate_has_independent_script_objects() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mut parent = element(activation, "parent", &[]);
+        let mut child = element(activation, "child", &[]);
+        parent.append_child(activation.context.gc_context, child);
+
+        // Instantiate the source's script objects before and after cloning.
+        let parent_object = parent.script_object(activation);
+        let mut clone = parent.duplicate(activation.context.gc_context, true);
+        let child_object = child.script_object(activation);
+
+        let clone_object = clone.script_object(activation);
+        let mut clone_child = clone.children().next().unwrap();
+        let clone_child_object = clone_child.script_object(activation);
+
+        assert!(!Object::ptr_eq(parent_object, clone_object));
+        assert!(!Object::ptr_eq(child_object, clone_child_object));
+
+        // Each script object is bound to its own node.
+        clone_object.set("nodeName", "renamed".into(), activation)?;
+        assert_eq!(parent.node_name(), Some("parent".into()));
+        assert_eq!(clone.node_name(), Some("renamed".into()));
+        assert!(Object::ptr_eq(
+            parent.script_object(activation),
+            parent_object
+        ));
+        assert!(Object::ptr_eq(
+            clone.script_object(activation),
+            clone_object
+        ));
+
+        // Shallow copies get a fresh script object too.
+        let mut shallow = child.duplicate(activation.context.gc_context, false);
+        assert!(!Object::ptr_eq(
+            shallow.script_object(activation),
+            child_object
+        ));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn duplicate_is_independent() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let proto = activation.context.avm1.prototypes().object;
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        document
+            .replace_with_str(
+                activation,
+                WStr::from_units(br#"<p class="x">one <b id="y">two</b><br/></p>"#),
+                false,
+            )
+            .unwrap();
+        let p = document.as_node().children().next().unwrap();
+        let original_xml = p.into_string(activation)?;
+
+        // A deep copy replicates the whole subtree, detached from any parent.
+        let deep = p.duplicate(activation.context.gc_context, true);
+        assert!(deep.parent().is_none());
+        assert_eq!(deep.into_string(activation)?, original_xml);
+        for (copy, source) in deep.children().zip(p.children()) {
+            assert_eq!(
+                copy.parent().and_then(|parent| parent.node_name()),
+                Some("p".into())
+            );
+            assert_eq!(copy.node_name(), source.node_name());
+            assert_eq!(copy.node_value(), source.node_value());
+        }
+        assert_eq!(deep.children_len(), 3);
+
+        // Changing the copy leaves the original as it was.
+        let mc = activation.context.gc_context;
+        deep.attributes()
+            .set("class", "changed".into(), activation)?;
+        let b = deep.children().nth(1).unwrap();
+        b.attributes().set("id", "z".into(), activation)?;
+        let two = b.children().next().unwrap();
+        two.set_node_value(mc, "three".into());
+        assert_eq!(p.into_string(activation)?, original_xml);
+        assert_eq!(
+            deep.into_string(activation)?,
+            WStr::from_units(br#"<p class="changed">one <b id="z">three</b><br /></p>"#)
+        );
+
+        // A shallow copy has the node's own data only.
+        let shallow = p.duplicate(mc, false);
+        assert!(shallow.parent().is_none());
+        assert_eq!(shallow.children_len(), 0);
+        assert_eq!(shallow.node_name(), p.node_name());
+        assert_eq!(
+            shallow.attribute_value(activation, "class".into())?,
+            Some("x".into())
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn interned_names() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let id_map = ScriptObject::new(mc, None);
+        let mut names = NameInterner::new();
+        let mut root = XmlNode::new(mc, ELEMENT_NODE, None);
+        for _ in 0..1000 {
+            let bs = BytesStart::borrowed(br#"item class="row" title="x""#, 4);
+            let child = XmlNode::from_start_event(activation, bs, id_map, Some(&mut names))
+                .expect("valid start tag");
+            root.append_child(mc, child);
+        }
+
+        // One string each for `item`, `class` and `title`, however many nodes.
+        assert_eq!(names.len(), 3);
+        let first = root.children().next().unwrap();
+        let last = root.children().last().unwrap();
+        assert!(AvmString::ptr_eq(
+            &first.node_name().unwrap(),
+            &last.node_name().unwrap()
+        ));
+        assert_eq!(first.attributes_ordered(), last.attributes_ordered());
+
+        // Interned names still equal any other string with the same contents.
+        let uninterned = AvmString::new_utf8(mc, "item");
+        assert!(!AvmString::ptr_eq(&first.node_name().unwrap(), &uninterned));
+        assert_eq!(first.node_name(), Some(uninterned));
+
+        // Parsed documents share names unless interning is turned off.
+        let proto = activation.context.avm1.prototypes().object;
+        let data = WStr::from_units(b"<a/><a/>");
+        for intern_names in [true, false] {
+            let mut document = XmlObject::empty(activation.context.gc_context, proto);
+            let options = ParseOptions {
+                intern_names,
+                ..ParseOptions::xml()
+            };
+            document
+                .replace_with_options(activation, data, options)
+                .expect("valid document");
+            let mut children = document.as_node().children();
+            let first = children.next().unwrap().node_name().unwrap();
+            let second = children.next().unwrap().node_name().unwrap();
+            assert_eq!(first, second);
+            assert_eq!(AvmString::ptr_eq(&first, &second), intern_names);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn attribute_presence() {
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let proto = activation.context.avm1.prototypes().object;
+        let mut document = XmlObject::empty(activation.context.gc_context, proto);
+        document
+            .replace_with_str(activation, WStr::from_units(br#"<x a="" />"#), false)
+            .expect("valid document");
+        let x = document.as_node().children().next().unwrap();
+
+        // A present but empty attribute is told apart from a missing one.
+        assert_eq!(x.attribute_value(activation, "a".into())?, Some("".into()));
+        assert_eq!(x.attribute_value(activation, "b".into())?, None);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn transform() {
+    const COMMENT_NODE: u8 = 8;
+
+    fn describe(node: XmlNode<'_>) -> String {
+        let mut result = match node.node_type() {
+            ELEMENT_NODE => node.node_name().unwrap().to_string(),
+            _ => format!("{:?}", node.node_value().unwrap().to_string()),
+        };
+        if node.children_len() > 0 {
+            let children: Vec<_> = node.children().map(describe).collect();
+            result = format!("{}({})", result, children.join(" "));
+        }
+        result
+    }
+
+    with_avm(8, |activation, _this| -> Result<(), Error> {
+        let mc = activation.context.gc_context;
+        let mut root = element(activation, "root", &[]);
+        let mut item = element(activation, "item", &[("id", "1")]);
+        let mut old = element(activation, "old", &[]);
+        old.append_child(mc, XmlNode::new(mc, TEXT_NODE, Some("text".into())));
+        old.append_child(mc, XmlNode::new(mc, COMMENT_NODE, Some("inner".into())));
+        item.append_child(mc, old);
+        root.append_child(mc, XmlNode::new(mc, COMMENT_NODE, Some("outer".into())));
+        root.append_child(mc, item);
+        assert_eq!(describe(root), r#"root("outer" item(old("text" "inner")))"#);
+
+        // Drop every comment and rename `<old>` to `<new>`.
+        let transformed = root
+            .transform(mc, |node| match node.node_type() {
+                COMMENT_NODE => None,
+                ELEMENT_NODE if node.node_name() == Some("old".into()) => {
+                    node.set_node_value(mc, "new".into());
+                    Some(node)
+                }
+                _ => Some(node),
+            })
+            .expect("root is kept");
+        assert_eq!(describe(transformed), r#"root(item(new("text")))"#);
+
+        // Attributes are copied, and the original tree is untouched.
+        let item_copy = transformed.children().next().unwrap();
+        assert_eq!(
+            item_copy.attribute_value(activation, "id".into())?,
+            Some("1".into())
+        );
+        assert_eq!(describe(root), r#"root("outer" item(old("text" "inner")))"#);
+
+        // Dropping the root drops the whole tree.
+        assert!(root.transform(mc, |_| None).is_none());
+
+        Ok(())
+    });
+}
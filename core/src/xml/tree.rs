@@ -6,6 +6,7 @@ use crate::avm1::XmlNodeObject;
 use crate::avm1::{Error, Object, ScriptObject, TObject, Value};
 use crate::string::{AvmString, WStr, WString};
 use crate::xml;
+use crate::xml::NameInterner;
 use gc_arena::{Collect, GcCell, MutationContext};
 use quick_xml::escape::escape;
 use quick_xml::events::BytesStart;
@@ -75,29 +76,34 @@ impl<'gc> XmlNode<'gc> {
     ///
     /// The returned node will always be an `Element`, and it must only contain
     /// valid encoded UTF-8 data. (Other encoding support is planned later.)
+    ///
+    /// If `names` is given, the element name and attribute keys are interned
+    /// in it.
     pub fn from_start_event(
         activation: &mut Activation<'_, 'gc, '_>,
         bs: BytesStart<'_>,
         id_map: ScriptObject<'gc>,
+        mut names: Option<&mut NameInterner<'gc>>,
     ) -> Result<Self, quick_xml::Error> {
-        let name = AvmString::new_utf8_bytes(activation.context.gc_context, bs.name());
+        let gc_context = activation.context.gc_context;
+        let mut name_string = |name: &[u8]| match names.as_deref_mut() {
+            Some(names) => names.intern(gc_context, name),
+            None => AvmString::new_utf8_bytes(gc_context, name),
+        };
+
+        let name = name_string(bs.name());
         let mut node = Self::new(activation.context.gc_context, ELEMENT_NODE, Some(name));
 
         // Reverse attributes so they appear in the `PropertyMap` in their definition order.
         let attributes: Result<Vec<_>, _> = bs.attributes().collect();
         let attributes = attributes?;
         for attribute in attributes.iter().rev() {
-            let key = AvmString::new_utf8_bytes(activation.context.gc_context, attribute.key);
+            let key = name_string(attribute.key);
             let value_bytes = attribute.unescaped_value()?;
             let value = AvmString::new_utf8_bytes(activation.context.gc_context, &value_bytes);
 
             // Insert an attribute.
-            node.attributes().define_value(
-                activation.context.gc_context,
-                key,
-                value.into(),
-                Attribute::empty(),
-            );
+            node.set_attribute(activation.context.gc_context, key, value);
 
             // Update the ID map.
             if attribute.key == b"id" {
@@ -208,12 +214,13 @@ impl<'gc> XmlNode<'gc> {
     /// documents.
     ///
     /// The `position` parameter is the position of the new child in
-    /// this node's children list. This is used to find and link the child's
-    /// siblings to each other.
+    /// this node's children list, and is clamped to the end of the list. This
+    /// is used to find and link the child's siblings to each other. If
+    /// `child` is already a child of this node, it is moved to `position`.
     pub fn insert_child(
         &mut self,
         mc: MutationContext<'gc, '_>,
-        position: usize,
+        mut position: usize,
         mut child: XmlNode<'gc>,
     ) {
         let is_cyclic = self
@@ -223,13 +230,22 @@ impl<'gc> XmlNode<'gc> {
             return;
         }
 
-        if let Some(mut old_parent) = child.0.read().parent {
-            if !GcCell::ptr_eq(self.0, old_parent.0) {
-                old_parent.orphan_child(mc, child);
+        let old_parent = child.0.read().parent;
+        if let Some(mut old_parent) = old_parent {
+            if GcCell::ptr_eq(self.0, old_parent.0) {
+                // Removing the child shifts the nodes after it back by one.
+                if let Some(old_position) = self.child_position(child) {
+                    if old_position < position {
+                        position -= 1;
+                    }
+                }
             }
+            old_parent.orphan_child(mc, child);
+            child.disown_siblings(mc);
         }
 
         child.0.write(mc).parent = Some(*self);
+        let position = position.min(self.children_len());
 
         let children = &mut self.0.write(mc).children;
         children.insert(position, child);
@@ -328,6 +344,16 @@ impl<'gc> XmlNode<'gc> {
         self.0.read().children.get(index).cloned()
     }
 
+    /// Get the first child of this node, if it has any.
+    pub fn first_child(self) -> Option<XmlNode<'gc>> {
+        self.0.read().children.first().copied()
+    }
+
+    /// Get the last child of this node, if it has any.
+    pub fn last_child(self) -> Option<XmlNode<'gc>> {
+        self.0.read().children.last().copied()
+    }
+
     /// Returns an iterator that yields child nodes.
     pub fn children(self) -> impl DoubleEndedIterator<Item = XmlNode<'gc>> {
         xml::iterators::ChildIter::for_node(self)
@@ -374,9 +400,52 @@ impl<'gc> XmlNode<'gc> {
         self.0.read().attributes
     }
 
+    /// List the names of this node's attributes in enumeration order.
+    ///
+    /// Attributes parsed from a document are listed in the order they were
+    /// written in the source; attributes added afterwards by script come
+    /// first, matching the order `for..in` visits them in.
+    pub fn attributes_ordered(self) -> Vec<AvmString<'gc>> {
+        self.attributes()
+            .own_properties()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Look up the value of an attribute of this node, coerced to a string.
+    ///
+    /// Yields `None` if the attribute is not set, and `Some("")` if it is set
+    /// to an empty value.
+    pub fn attribute_value(
+        self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        name: AvmString<'gc>,
+    ) -> Result<Option<AvmString<'gc>>, Error<'gc>> {
+        self.attributes()
+            .get_local_stored(name, activation)
+            .map(|value| value.coerce_to_string(activation))
+            .transpose()
+    }
+
+    /// Set an attribute of this node, replacing any previous value.
+    pub fn set_attribute(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        name: AvmString<'gc>,
+        value: AvmString<'gc>,
+    ) {
+        self.attributes()
+            .define_value(gc_context, name, value.into(), Attribute::empty());
+    }
+
     /// Create a duplicate copy of this node.
     ///
     /// If the `deep` flag is set true, then the entire node tree will be cloned.
+    ///
+    /// No node of the copy ever shares a script object with its source; each
+    /// one gets its own the first time it is requested. Sharing one would tie
+    /// a single AVM object to two nodes and corrupt the object graph.
     pub fn duplicate(self, gc_context: MutationContext<'gc, '_>, deep: bool) -> Self {
         let attributes = ScriptObject::new(gc_context, None);
         for (key, value) in self.attributes().own_properties() {
@@ -406,6 +475,35 @@ impl<'gc> XmlNode<'gc> {
         clone
     }
 
+    /// Build a transformed copy of the tree rooted at this node.
+    ///
+    /// Each node is copied, its children transformed, and then `f` is called
+    /// with the copy, working from the leaves up. `f` may return the copy
+    /// (modified or not), a replacement node, or `None` to drop the node and
+    /// everything below it. This node's own tree is left untouched.
+    pub fn transform(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        f: impl Fn(XmlNode<'gc>) -> Option<XmlNode<'gc>>,
+    ) -> Option<XmlNode<'gc>> {
+        self.transform_with(gc_context, &f)
+    }
+
+    fn transform_with(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        f: &dyn Fn(XmlNode<'gc>) -> Option<XmlNode<'gc>>,
+    ) -> Option<XmlNode<'gc>> {
+        let mut copy = self.duplicate(gc_context, false);
+        for child in self.children() {
+            if let Some(child) = child.transform_with(gc_context, f) {
+                copy.append_child(gc_context, child);
+            }
+        }
+
+        f(copy)
+    }
+
     /// Look up the URI for the given prefix.
     ///
     /// XML namespaces are determined by `xmlns:` namespace attributes on the
@@ -452,8 +550,8 @@ impl<'gc> XmlNode<'gc> {
                 result.push_byte(b'<');
                 result.push_str(&tag_name);
 
-                for (key, value) in self.attributes().own_properties() {
-                    let value = value.coerce_to_string(activation)?;
+                for key in self.attributes_ordered() {
+                    let value = self.attribute_value(activation, key)?.unwrap_or_default();
                     let value = value.to_utf8_lossy();
                     let value = escape(value.as_bytes());
 
@@ -519,3 +617,26 @@ impl<'gc> fmt::Debug for XmlNode<'gc> {
             .finish()
     }
 }
+
+/// Parse an attribute value as a number.
+///
+/// Surrounding whitespace is ignored. Yields `None` if the value is not a
+/// valid number.
+pub fn attribute_as_f64(value: &WStr) -> Option<f64> {
+    value.trim().parse().ok()
+}
+
+/// Parse an attribute value as a boolean.
+///
+/// `"true"` and `"1"` are considered true, while `"false"` and `"0"` are
+/// considered false. Yields `None` for any other value.
+pub fn attribute_as_bool(value: &WStr) -> Option<bool> {
+    let value = value.trim();
+    if value == WStr::from_units(b"1") || value.eq_ignore_case(WStr::from_units(b"true")) {
+        Some(true)
+    } else if value == WStr::from_units(b"0") || value.eq_ignore_case(WStr::from_units(b"false")) {
+        Some(false)
+    } else {
+        None
+    }
+}